@@ -0,0 +1,327 @@
+use std::f32;
+use std::sync::Arc;
+
+use na::Matrix4;
+use na::Vector3;
+use na::Vector4;
+use na::U3;
+use primitives::Primitive;
+use rand::Rng;
+use rand::RngCore;
+use shading::Material;
+use util::math;
+use Hit;
+use Object;
+use Ray;
+
+// Points taken on each area light for every point shaded, more give softer
+// shadows with less noise
+pub const SAMPLES: usize = 16;
+
+// Surface of a primitive in model space that can be sampled for points to
+// light the scene from
+pub enum Surface
+{
+	Triangles(Vec<[Vector4<f32>; 3]>),
+	// Centre and radius
+	Sphere(Vector4<f32>, f32),
+}
+
+enum Shape
+{
+	Triangles
+	{
+		triangles: Vec<[Vector4<f32>; 3]>,
+		// Running total of triangle areas, to pick triangles by area
+		cumulative_areas: Vec<f32>,
+	},
+	Sphere
+	{
+		centre: Vector4<f32>,
+		radius: f32,
+	},
+}
+
+// Point on an area light, in world space
+pub struct LightSample
+{
+	pub point: Vector4<f32>,
+	// Unit normal of the light's surface at the point
+	pub normal: Vector4<f32>,
+	// World space area the sample stands for, one over its probability density
+	pub area: f32,
+	// Light given off from the point towards the point being lit
+	pub radiance: Vector3<f32>,
+}
+
+// Object whose material gives off light, sampled as a light source. Points are
+// spread evenly over the surface in model space and weighted by how much the
+// object's transform stretches the surface around them. Light samples have no
+// UVs, so emission that varies over a light should use solid textures.
+pub struct AreaLight
+{
+	primitive: Arc<dyn Primitive>,
+	shape: Shape,
+	// Surface area in model space
+	area: f32,
+	// World to model, as objects keep them
+	transform: Matrix4<f32>,
+	model_to_world: Matrix4<f32>,
+	// Scale of volumes going from model to world space
+	determinant: f32,
+	material: Arc<dyn Material>,
+}
+
+impl AreaLight
+{
+	// None for objects that don't glow or whose primitive can't be sampled
+	pub fn from_object(object: &Object) -> Option<Self>
+	{
+		let material = object.get_material();
+
+		if !material.is_emissive() {
+			return None;
+		}
+
+		AreaLight::new(
+			Arc::clone(object.get_primitive()),
+			object.get_transform(),
+			Arc::clone(material),
+		)
+	}
+
+	pub fn new(
+		primitive: Arc<dyn Primitive>,
+		transform: Matrix4<f32>,
+		material: Arc<dyn Material>,
+	) -> Option<Self>
+	{
+		let (shape, area) = match primitive.emitting_surface()? {
+			Surface::Triangles(triangles) => {
+				let mut cumulative_areas = Vec::with_capacity(triangles.len());
+				let mut total_area = 0.0;
+
+				for [v1, v2, v3] in &triangles {
+					total_area += math::cross_4d(v2 - v1, v3 - v1).norm() / 2.0;
+					cumulative_areas.push(total_area);
+				}
+
+				(
+					Shape::Triangles {
+						triangles: triangles,
+						cumulative_areas: cumulative_areas,
+					},
+					total_area,
+				)
+			},
+			Surface::Sphere(centre, radius) => (
+				Shape::Sphere {
+					centre: centre,
+					radius: radius,
+				},
+				4.0 * f32::consts::PI * radius * radius,
+			),
+		};
+
+		if area <= 0.0 {
+			return None;
+		}
+
+		let model_to_world = transform.try_inverse()?;
+		let determinant = model_to_world
+			.fixed_slice::<U3, U3>(0, 0)
+			.determinant()
+			.abs();
+
+		Some(AreaLight {
+			primitive: primitive,
+			shape: shape,
+			area: area,
+			transform: transform,
+			model_to_world: model_to_world,
+			determinant: determinant,
+			material: material,
+		})
+	}
+
+	// A point spread uniformly in model space over the part of the light's
+	// surface that may be seen from the given point in world space, along with
+	// the light it sends towards that point
+	pub fn sample(&self, towards: Vector4<f32>, rng: &mut dyn RngCore) -> LightSample
+	{
+		let (point, normal, area) = match &self.shape {
+			Shape::Triangles {
+				triangles,
+				cumulative_areas,
+			} => {
+				let target = rng.gen::<f32>() * self.area;
+				let index = usize::min(
+					cumulative_areas.partition_point(|&area| area < target),
+					triangles.len() - 1,
+				);
+				let [v1, v2, v3] = triangles[index];
+
+				// Uniform barycentric coordinates, folding the square onto the triangle
+				let (mut u, mut v) = (rng.gen::<f32>(), rng.gen::<f32>());
+				if u + v > 1.0 {
+					u = 1.0 - u;
+					v = 1.0 - v;
+				}
+
+				(
+					v1 * (1.0 - u - v) + v2 * u + v3 * v,
+					math::cross_4d(v2 - v1, v3 - v1).normalize(),
+					self.area,
+				)
+			},
+			Shape::Sphere { centre, radius } => {
+				let y = 1.0 - 2.0 * rng.gen::<f32>();
+				let angle = 2.0 * f32::consts::PI * rng.gen::<f32>();
+				let around = f32::sqrt(1.0 - y * y);
+
+				let normal = Vector4::new(around * angle.cos(), y, around * angle.sin(), 0.0);
+
+				let facing = self.transform * towards - centre;
+				let normal = if normal.dot(&facing) < 0.0 && facing.norm() > *radius {
+					-normal
+				} else {
+					normal
+				};
+
+				(
+					centre + normal * *radius,
+					normal,
+					self.sampled_area(towards),
+				)
+			},
+		};
+
+		// Stretching the surface scales the area around the point by the
+		// determinant times the length of the transformed unit normal
+		let world_normal = math::transform_normals(normal, self.transform);
+		let scale = self.determinant * world_normal.norm();
+		let world_normal = world_normal / world_normal.norm().max(f32::EPSILON);
+
+		let world_point = self.model_to_world * point;
+
+		let hit = Hit {
+			local_point: Some(point),
+			view: (towards - world_point).try_normalize(f32::EPSILON),
+			..Hit::new(0.0, world_normal, (0.0, 0.0), None)
+		};
+
+		LightSample {
+			point: world_point,
+			normal: world_normal,
+			area: area * scale,
+			radiance: self.material.emission(&hit),
+		}
+	}
+
+	// Model space area sample draws points from when lighting the given point.
+	// The far half of a sphere is always hidden behind the near half, which
+	// transforms keep true, so points outside one only sample the near half.
+	fn sampled_area(&self, towards: Vector4<f32>) -> f32
+	{
+		match &self.shape {
+			Shape::Triangles { .. } => self.area,
+			Shape::Sphere { centre, radius } => {
+				if (self.transform * towards - centre).norm() <= *radius {
+					self.area
+				} else {
+					self.area / 2.0
+				}
+			},
+		}
+	}
+
+	// Density over world space area that sample draws a point on the light
+	// with, from the light's world space unit normal at the point
+	pub fn density(&self, normal: Vector4<f32>, towards: Vector4<f32>) -> f32
+	{
+		// The inverse of the stretch sample weights by, from the world normal
+		let stretch =
+			self.determinant / math::transform_normals(normal, self.model_to_world).norm();
+
+		1.0 / (self.sampled_area(towards) * stretch)
+	}
+
+	// Where the ray meets the light, with the light given off back along it
+	pub fn hit(&self, ray: &Ray) -> Option<(Hit, Vector3<f32>)>
+	{
+		let hit = self.primitive.hit(ray, self.transform)?;
+
+		let origin = self.transform * ray.origin();
+		let direction = ray.point() - ray.origin();
+
+		let hit = Hit {
+			normal: hit.normal.normalize(),
+			local_point: Some(origin + hit.intersect * (self.transform * ray.point() - origin)),
+			view: (-direction).try_normalize(f32::EPSILON),
+			..hit
+		};
+
+		Some((hit, self.material.emission(&hit)))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use primitives::Rectangle;
+	use primitives::Sphere;
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+	use shading::Emissive;
+
+	use super::*;
+
+	#[test]
+	fn samples_cover_the_world_space_surface()
+	{
+		let material: Arc<dyn Material> =
+			Arc::new(Emissive::new(Vector3::repeat(1.0).into(), 2.0, None));
+		let mut rng = StdRng::seed_from_u64(0);
+		let towards = Vector4::new(0.0, 10.0, 10.0, 1.0);
+
+		// A unit sphere twice as big in the world, objects are given world to model transforms
+		let sphere = AreaLight::new(
+			Arc::new(Sphere::unit_sphere()),
+			Matrix4::new_scaling(0.5),
+			Arc::clone(&material),
+		)
+		.unwrap();
+
+		for _ in 0..10 {
+			let sample = sphere.sample(towards, &mut rng);
+
+			// Only the half facing the point being lit
+			assert!((sample.point.xyz().norm() - 2.0).abs() < 0.001);
+			assert!(sample.point.xyz().dot(&towards.xyz()) >= 0.0);
+			assert!((sample.area - 8.0 * f32::consts::PI).abs() < 0.01);
+			assert!((sample.radiance - Vector3::repeat(2.0)).norm() < 0.001);
+		}
+
+		// A unit square stretched to twice its width
+		let rectangle = AreaLight::new(
+			Arc::new(Rectangle::unit_rectangle()),
+			Matrix4::new_nonuniform_scaling(&Vector3::new(0.5, 1.0, 1.0)),
+			material,
+		)
+		.unwrap();
+
+		for _ in 0..10 {
+			let sample = rectangle.sample(towards, &mut rng);
+
+			assert!(sample.point.x.abs() <= 1.001 && sample.point.z.abs() <= 0.501);
+			assert!((sample.area - 2.0).abs() < 0.001);
+			assert!((sample.normal - Vector4::new(0.0, 1.0, 0.0, 0.0)).norm() < 0.001);
+			assert!((rectangle.density(sample.normal, towards) - 0.5).abs() < 0.001);
+
+			// Rays towards the sample find it again
+			let (hit, radiance) = rectangle.hit(&Ray::new(towards, sample.point)).unwrap();
+			assert!((hit.intersect - 1.0).abs() < 0.001);
+			assert!((radiance - Vector3::repeat(2.0)).norm() < 0.001);
+		}
+	}
+}
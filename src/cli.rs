@@ -16,6 +16,12 @@ pub struct Parameters
 
 	// Single pixel to trace (used for debugging)
 	pub single_pixel: Option<(u32, u32)>,
+
+	// Frame rate of Y4M video output as a numerator/denominator pair
+	pub frame_rate: (u32, u32),
+
+	// Trace wavelengths of light instead of RGB
+	pub spectral: bool,
 }
 
 pub fn parse_args() -> Parameters
@@ -33,10 +39,11 @@ pub fn parse_args() -> Parameters
 		)
 		.arg(
 			Arg::with_name("output file")
-				.help("Output PNG file")
+				.help("Output PNG or Y4M file")
 				.long_help(
 					"PNG file to render the image to, this will override the file specified in \
-					 the LUA file",
+					 the LUA file. Files with a .y4m extension are written as a YUV4MPEG2 video, \
+					 with each render call appending a frame",
 				)
 				.long("output")
 				.short("o")
@@ -54,6 +61,29 @@ pub fn parse_args() -> Parameters
 				.takes_value(true)
 				.validator(validate_resolution_value),
 		)
+		.arg(
+			Arg::with_name("frame rate")
+				.help("Frame rate of Y4M output. eg: 24 or 30000/1001")
+				.long_help("Set the frame rate written to the header of Y4M video output")
+				.long("frame-rate")
+				.short("f")
+				.takes_value(true)
+				.default_value("24")
+				.validator(validate_frame_rate_value),
+		)
+		.arg(
+			Arg::with_name("spectral")
+				.help("Render with wavelengths of light instead of RGB")
+				.long_help(
+					"Shade each pixel at several wavelengths drawn across the visible range, \
+					 converting colours to spectra and back through CIE XYZ. Light refracted \
+					 through surfaces with indices that vary with wavelength spreads into a \
+					 smooth band of colours, where RGB splits it into red, green and blue alone. \
+					 Slower than rendering in RGB",
+				)
+				.long("spectral")
+				.short("s"),
+		)
 		.arg(
 			Arg::with_name("input file")
 				.help("Input LUA file")
@@ -74,11 +104,17 @@ pub fn parse_args() -> Parameters
 		.value_of("resolution")
 		.map(|s| parse_resolution_value(s));
 
+	let frame_rate = parse_frame_rate_value(matches.value_of("frame rate").unwrap());
+
+	let spectral = matches.is_present("spectral");
+
 	Parameters {
 		input_file: String::from(input_file),
 		output_file: output_file,
 		resolution: resolution,
 		single_pixel: pixel,
+		frame_rate: frame_rate,
+		spectral: spectral,
 	}
 }
 
@@ -145,3 +181,33 @@ fn parse_resolution_value(resolution_string: &str) -> (u32, u32)
 		resolution[1].parse::<u32>().unwrap(),
 	)
 }
+
+fn validate_frame_rate_value(frame_rate_string: String) -> Result<(), String>
+{
+	let frame_rate: Vec<&str> = frame_rate_string.split('/').collect();
+
+	if frame_rate.len() > 2 {
+		return Err(String::from("Expected format is \"N\" or \"N/D\""));
+	}
+
+	for part in &frame_rate {
+		match part.parse::<u32>() {
+			Ok(value) if value > 0 => (),
+			_ => return Err(format!("Invalid frame rate component \"{}\"", part)),
+		}
+	}
+
+	Ok(())
+}
+
+fn parse_frame_rate_value(frame_rate_string: &str) -> (u32, u32)
+{
+	let frame_rate: Vec<&str> = frame_rate_string.split('/').collect();
+
+	(
+		frame_rate[0].parse::<u32>().unwrap(),
+		frame_rate
+			.get(1)
+			.map_or(1, |denominator| denominator.parse::<u32>().unwrap()),
+	)
+}
@@ -1,4 +1,6 @@
 use std::cmp::Ordering;
+use std::fmt;
+use std::mem;
 use std::sync::mpsc;
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
@@ -16,6 +18,10 @@ use Hit;
 use Object;
 use Ray;
 
+// Grid coordinates of a cell and the objects that might overlap it
+type CellCandidates = ((usize, usize, usize), Vec<Arc<Object>>);
+
+#[derive(fmt::Debug)]
 pub struct Grid
 {
 	position: Vector3<f32>,
@@ -23,23 +29,57 @@ pub struct Grid
 	num_cells: Vector3<usize>,
 	cell_size: f32,
 	cells: Vec<GridCell>,
+	unbounded_objects: Vec<Arc<Object>>,
 }
 
 impl Grid
 {
 	pub fn new(objects: Vec<Arc<Object>>) -> Self
 	{
+		// Unbounded objects have no meaningful extents so they can't be placed
+		// in grid cells, they are checked against every ray instead
+		let (objects, unbounded_objects): (Vec<Arc<Object>>, Vec<Arc<Object>>) =
+			objects.into_iter().partition(|object| object.is_bounded());
+
+		if objects.is_empty() {
+			return Grid {
+				position: Vector3::repeat(0.0),
+				size: Vector3::repeat(0.0),
+				num_cells: Vector3::repeat(0),
+				cell_size: 0.0,
+				cells: Vec::new(),
+				unbounded_objects: unbounded_objects,
+			};
+		}
+
 		let bbox_corners = Grid::get_bbox_corners_in_world_space(&objects);
 
 		let (grid_min, grid_max) = Grid::get_min_max_points(&bbox_corners);
 		let average_volume = Grid::get_average_bbox_volume(&bbox_corners);
 
-		let grid_cell_size = f32::powf(average_volume / 8.0, 1.0 / 3.0);
+		let mut grid_cell_size = f32::powf(average_volume / 8.0, 1.0 / 3.0);
 
+		// Flat objects (planes, disks) have no volume, fall back to sizing cells
+		// from the largest side of the grid
+		if !math::far_from_zero_pos(grid_cell_size) {
+			let grid_extent = grid_max - grid_min;
+			grid_cell_size = f32::max(grid_extent.max() / 8.0, 1.0);
+		}
+
+		// Every axis needs at least one cell, otherwise flat scenes have no cells at all
 		let num_cells = Vector3::new(
-			f32::ceil((grid_max.x - grid_min.x) / grid_cell_size) as usize,
-			f32::ceil((grid_max.y - grid_min.y) / grid_cell_size) as usize,
-			f32::ceil((grid_max.z - grid_min.z) / grid_cell_size) as usize,
+			usize::max(
+				f32::ceil((grid_max.x - grid_min.x) / grid_cell_size) as usize,
+				1,
+			),
+			usize::max(
+				f32::ceil((grid_max.y - grid_min.y) / grid_cell_size) as usize,
+				1,
+			),
+			usize::max(
+				f32::ceil((grid_max.z - grid_min.z) / grid_cell_size) as usize,
+				1,
+			),
 		);
 
 		let grid_size = Vector3::new(
@@ -71,12 +111,46 @@ impl Grid
 			num_cells.x, num_cells.y, num_cells.z
 		);
 
+		// Only objects whose world space bounding box overlaps a cell need the
+		// exact test against that cell, which keeps large scenes from testing
+		// every object against every cell
+		let mut cell_candidates = vec![Vec::new(); num_cells.x * num_cells.y * num_cells.z];
+
+		for (object, corners) in objects.iter().zip(&bbox_corners) {
+			let (object_min, object_max) = Grid::get_min_max_points(&vec![*corners]);
+
+			let to_cell = |offset: f32, count: usize| {
+				usize::min(f32::max(offset / grid_cell_size, 0.0) as usize, count - 1)
+			};
+
+			let lower = (
+				to_cell(object_min.x - grid_min.x, num_cells.x),
+				to_cell(object_min.y - grid_min.y, num_cells.y),
+				to_cell(object_min.z - grid_min.z, num_cells.z),
+			);
+			let upper = (
+				to_cell(object_max.x - grid_min.x, num_cells.x),
+				to_cell(object_max.y - grid_min.y, num_cells.y),
+				to_cell(object_max.z - grid_min.z, num_cells.z),
+			);
+
+			for z in lower.2..=upper.2 {
+				for y in lower.1..=upper.1 {
+					for x in lower.0..=upper.0 {
+						let index = x + (y * num_cells.x) + (z * num_cells.x * num_cells.y);
+						cell_candidates[index].push(Arc::clone(object));
+					}
+				}
+			}
+		}
+
 		// Generate list of all grid cells to be populated
 		let mut cell_list = Vec::new();
 		for z in 0..num_cells.z {
 			for y in 0..num_cells.y {
 				for x in 0..num_cells.x {
-					cell_list.push((x, y, z));
+					let index = x + (y * num_cells.x) + (z * num_cells.x * num_cells.y);
+					cell_list.push(((x, y, z), mem::take(&mut cell_candidates[index])));
 				}
 			}
 		}
@@ -84,8 +158,6 @@ impl Grid
 		// Shuffle the list to more evenly distribute work between threads
 		cell_list.shuffle(&mut rand::thread_rng());
 
-		// We will need to share objects between threads so move it in an Arc
-		let objects = Arc::new(objects);
 		let cells_per_thread = cell_list.len() / num_cpus::get();
 
 		let rx = {
@@ -101,11 +173,10 @@ impl Grid
 					},
 				);
 
-				let objects = Arc::clone(&objects);
 				let tx = mpsc::Sender::clone(&tx);
 
 				thread::spawn(move || {
-					Grid::fill_worker(grid_min, grid_cell_size, cell_list, &objects, tx);
+					Grid::fill_worker(grid_min, grid_cell_size, cell_list, tx);
 				});
 			}
 
@@ -132,19 +203,54 @@ impl Grid
 		// Remove the x,y,z coordinates from the list of cells, they are no longer needed
 		let cells = cells.into_iter().map(|(_, cell)| cell).collect();
 
-		// Take objects out of the Arc, it no longer needs to be shared between threads
-		//let objects = Arc::try_unwrap(objects).unwrap();
-
 		Grid {
 			position: grid_min,
 			size: grid_size,
 			num_cells: num_cells,
 			cell_size: grid_cell_size,
 			cells: cells,
+			unbounded_objects: unbounded_objects,
 		}
 	}
 
+	// Corners of the box around all bounded objects
+	pub fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		(self.position, self.position + self.size)
+	}
+
+	pub fn is_bounded(&self) -> bool
+	{
+		self.unbounded_objects.is_empty()
+	}
+
 	pub fn check_hit(&self, ray: &Ray) -> Option<(Hit, &dyn Material)>
+	{
+		let grid_hit = if self.cells.is_empty() {
+			None
+		} else {
+			self.check_cells_hit(ray)
+		};
+
+		self.unbounded_objects.iter().fold(
+			grid_hit,
+			|last_hit, object| -> Option<(Hit, &dyn Material)> {
+				match (object.check_hit(ray), last_hit) {
+					(Some(hit), Some(last_hit)) => {
+						if hit.0.intersect < last_hit.0.intersect {
+							Some(hit)
+						} else {
+							Some(last_hit)
+						}
+					},
+					(hit, None) => hit,
+					(None, last_hit) => last_hit,
+				}
+			},
+		)
+	}
+
+	fn check_cells_hit(&self, ray: &Ray) -> Option<(Hit, &dyn Material)>
 	{
 		let ray_direction = ray.point() - ray.origin();
 
@@ -242,13 +348,12 @@ impl Grid
 	fn fill_worker(
 		grid_min: Vector3<f32>,
 		cell_size: f32,
-		mut cell_list: Vec<(usize, usize, usize)>,
-		objects: &Vec<Arc<Object>>,
+		mut cell_list: Vec<CellCandidates>,
 		tx: Sender<((usize, usize, usize), GridCell)>,
 	)
 	{
 		loop {
-			let (x, y, z) = match cell_list.pop() {
+			let ((x, y, z), objects) = match cell_list.pop() {
 				Some(cell) => cell,
 				None => break,
 			};
@@ -260,7 +365,7 @@ impl Grid
 					cell_size * z as f32,
 				);
 
-			tx.send(((x, y, z), GridCell::new(position, cell_size, objects)))
+			tx.send(((x, y, z), GridCell::new(position, cell_size, &objects)))
 				.unwrap();
 		}
 	}
@@ -524,6 +629,7 @@ impl Grid
 	}
 }
 
+#[derive(fmt::Debug)]
 struct GridCell
 {
 	objects: Vec<Arc<Object>>,
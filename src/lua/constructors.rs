@@ -2,14 +2,57 @@ use std::sync::Arc;
 
 use lua;
 use na;
+use primitives::csg;
+use primitives::curve::CurveType;
+use primitives::implicit;
+use primitives::sdf;
+use primitives::sdf::DistanceField;
+use primitives::BezierPatch;
+use primitives::Capsule;
+use primitives::Cone;
+use primitives::Csg;
 use primitives::Cube;
+use primitives::Curve;
+use primitives::Cylinder;
+use primitives::Disk;
+use primitives::Heightfield;
+use primitives::Implicit;
+use primitives::Instance;
 use primitives::Mesh;
+use primitives::Plane;
+use primitives::Rectangle;
+use primitives::Sdf;
 use primitives::Sphere;
+use primitives::Torus;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rlua::AnyUserData;
 use rlua::Context;
 use rlua::FromLua;
+use rlua::Function;
+use rlua::Table;
 use rlua::Value;
+use rlua::Variadic;
+use shading::principled;
+use shading::rough_diffuse;
+use shading::surface_detail::Detail;
+use shading::texture;
+use shading::texture::ImageTexture;
+use shading::texture::WrapMode;
+use shading::Coated;
 use shading::CookTorrance;
+use shading::Emissive;
+use shading::KajiyaKay;
+use shading::Mix;
+use shading::Parameter;
 use shading::Phong;
+use shading::Principled;
+use shading::RoughDiffuse;
+use shading::SurfaceDetail;
+use shading::Texture;
+use spectrum;
+use util::math;
+use util::scatter;
 use Light;
 
 use super::SceneNode;
@@ -23,20 +66,21 @@ impl Phong
 	{
 		let (lua_diffuse, lua_specular, lua_shininess) = lua_value;
 
-		let diffuse = lua::Vector3::from_lua(lua_diffuse, lua)?;
-		let specular = lua::Vector3::from_lua(lua_specular, lua)?;
-		let shininess = f32::from_lua(lua_shininess, lua)?;
+		let diffuse = lua::Parameter::from_lua(lua_diffuse, lua)?;
+		let specular = lua::Parameter::from_lua(lua_specular, lua)?;
+		let shininess = lua::Parameter::from_lua(lua_shininess, lua)?;
 
 		Ok(lua::Material::new(Phong::new(
-			na::Vector3::from(diffuse),
-			na::Vector3::from(specular),
-			shininess,
+			diffuse.into(),
+			specular.into(),
+			shininess.into(),
 		)))
 	}
 }
 
 impl CookTorrance
 {
+	// Anisotropy and its rotation are optional trailing arguments
 	pub fn lua_new<'lua>(
 		lua: Context<'lua>,
 		lua_value: (
@@ -46,6 +90,8 @@ impl CookTorrance
 			Value<'lua>,
 			Value<'lua>,
 			Value<'lua>,
+			Value<'lua>,
+			Value<'lua>,
 		),
 	) -> rlua::Result<lua::Material>
 	{
@@ -56,108 +102,1344 @@ impl CookTorrance
 			lua_roughness,
 			lua_refractive_index,
 			lua_extinction_coefficient,
+			lua_anisotropy,
+			lua_rotation,
 		) = lua_value;
 
-		let diffuse_colour = lua::Vector3::from_lua(lua_diffuse_colour, lua)?;
-		let specular_colour = lua::Vector3::from_lua(lua_specular_colour, lua)?;
-		let diffuse_fraction = f32::from_lua(lua_diffuse_fraction, lua)?;
-		let roughness = f32::from_lua(lua_roughness, lua)?;
-		let refractive_index = f32::from_lua(lua_refractive_index, lua)?;
-		let extinction_coefficient = f32::from_lua(lua_extinction_coefficient, lua)?;
+		let diffuse_colour = lua::Parameter::from_lua(lua_diffuse_colour, lua)?;
+		let specular_colour = lua::Parameter::from_lua(lua_specular_colour, lua)?;
+		let diffuse_fraction = lua::Parameter::from_lua(lua_diffuse_fraction, lua)?;
+		let roughness = lua::Parameter::from_lua(lua_roughness, lua)?;
+		let anisotropy = Option::<lua::Parameter>::from_lua(lua_anisotropy, lua)?;
+		let rotation = Option::<lua::Parameter>::from_lua(lua_rotation, lua)?;
 
-		Ok(lua::Material::new(CookTorrance::new(
-			na::Vector3::from(diffuse_colour),
-			na::Vector3::from(specular_colour),
-			diffuse_fraction,
-			roughness,
-			refractive_index,
-			extinction_coefficient,
-		)))
+		let refractive_index = lua::RefractiveIndex::from_lua(lua_refractive_index, lua)?;
+
+		let material = CookTorrance::new(
+			diffuse_colour.into(),
+			specular_colour.into(),
+			diffuse_fraction.into(),
+			roughness.into(),
+			refractive_index.into(),
+			f32::from_lua(lua_extinction_coefficient, lua)?.into(),
+		);
+
+		let material = match anisotropy {
+			Some(anisotropy) => {
+				material.with_anisotropy(anisotropy.into(), rotation.map(Parameter::from))
+			},
+			None => material,
+		};
+
+		Ok(lua::Material::new(material))
 	}
 }
 
-impl Sphere
+impl CookTorrance
+{
+	// Metal from the table of measured indices, by name
+	pub fn lua_metal<'lua>(
+		lua: Context<'lua>,
+		(lua_name, lua_roughness): (String, Value<'lua>),
+	) -> rlua::Result<lua::Material>
+	{
+		let roughness = lua::Parameter::from_lua(lua_roughness, lua)?;
+
+		let (refractive_index, extinction_coefficient) = match spectrum::metal(&lua_name) {
+			Some(indices) => indices,
+			None => {
+				return Err(rlua::Error::RuntimeError(format!(
+					"Unknown metal \"{}\", expected one of {}",
+					lua_name,
+					spectrum::metal_names().collect::<Vec<_>>().join(", ")
+				)))
+			},
+		};
+
+		let material =
+			CookTorrance::metal(roughness.into(), refractive_index, extinction_coefficient);
+
+		Ok(lua::Material::new(material))
+	}
+}
+
+impl Principled
+{
+	// Inputs left out of the options table keep their defaults
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_options: Value<'lua>,
+	) -> rlua::Result<lua::Material>
+	{
+		let options = Option::<Table>::from_lua(lua_options, lua)?;
+		let mut inputs = principled::Inputs::default();
+
+		if let Some(options) = options {
+			let set = |key: &str, input: &mut Parameter| -> rlua::Result<()> {
+				if let Some(parameter) = options.get::<_, Option<lua::Parameter>>(key)? {
+					*input = parameter.into();
+				}
+
+				Ok(())
+			};
+
+			set("base_colour", &mut inputs.base_colour)?;
+			set("metallic", &mut inputs.metallic)?;
+			set("roughness", &mut inputs.roughness)?;
+			set("specular", &mut inputs.specular)?;
+			set("specular_tint", &mut inputs.specular_tint)?;
+			set("anisotropic", &mut inputs.anisotropic)?;
+			set("sheen", &mut inputs.sheen)?;
+			set("sheen_tint", &mut inputs.sheen_tint)?;
+			set("clearcoat", &mut inputs.clearcoat)?;
+			set("clearcoat_gloss", &mut inputs.clearcoat_gloss)?;
+			set("subsurface", &mut inputs.subsurface)?;
+			set("transmission", &mut inputs.transmission)?;
+
+			if let Some(ior) = options.get::<_, Option<lua::RefractiveIndex>>("ior")? {
+				inputs.refractive_index = ior.into();
+			}
+		}
+
+		Ok(lua::Material::new(Principled::new(inputs)))
+	}
+}
+
+impl KajiyaKay
 {
 	pub fn lua_new<'lua>(
 		lua: Context<'lua>,
 		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
-	) -> rlua::Result<lua::SceneNode>
+	) -> rlua::Result<lua::Material>
 	{
-		let (lua_name, lua_position, lua_radius) = lua_value;
+		let (lua_diffuse, lua_specular, lua_shininess) = lua_value;
 
-		let name = String::from_lua(lua_name, lua)?;
-		let position = lua::Vector3::from_lua(lua_position, lua)?;
-		let radius = f32::from_lua(lua_radius, lua)?;
+		let diffuse = lua::Vector3::from_lua(lua_diffuse, lua)?;
+		let specular = lua::Vector3::from_lua(lua_specular, lua)?;
+		let shininess = f32::from_lua(lua_shininess, lua)?;
 
-		let node = lua::SceneNode::new(&name, Some(Arc::new(Sphere::new(position.into(), radius))));
+		Ok(lua::Material::new(KajiyaKay::new(
+			na::Vector3::from(diffuse),
+			na::Vector3::from(specular),
+			shininess,
+		)))
+	}
+}
 
-		Ok(node)
+impl SurfaceDetail
+{
+	pub fn lua_normal_map<'lua>(
+		lua: Context<'lua>,
+		lua_value: (AnyUserData<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Material>
+	{
+		let (lua_material, lua_texture, lua_strength) = lua_value;
+
+		let material = lua_material.borrow::<lua::Material>()?.get_inner().clone();
+		let texture = lua::Pointer::<dyn Texture>::from_lua(lua_texture, lua)?;
+		let strength = Option::<f32>::from_lua(lua_strength, lua)?.unwrap_or(1.0);
+
+		let detail = Detail::NormalMap {
+			texture: texture.into(),
+			strength: strength,
+		};
+
+		Ok(lua::Material::new(SurfaceDetail::new(material, detail)))
 	}
 
-	pub fn lua_unit_sphere<'lua>(
+	pub fn lua_bump_map<'lua>(
 		lua: Context<'lua>,
-		lua_name: Value<'lua>,
-	) -> rlua::Result<lua::SceneNode>
+		lua_value: (AnyUserData<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Material>
 	{
-		let name = String::from_lua(lua_name, lua)?;
+		let (lua_material, lua_texture, lua_scale) = lua_value;
 
-		let node = lua::SceneNode::new(&name, Some(Arc::new(Sphere::unit_sphere())));
+		let material = lua_material.borrow::<lua::Material>()?.get_inner().clone();
+		let texture = lua::Pointer::<dyn Texture>::from_lua(lua_texture, lua)?;
+		let scale = f32::from_lua(lua_scale, lua)?;
 
-		Ok(node)
+		let detail = Detail::BumpMap {
+			texture: texture.into(),
+			scale: scale,
+		};
+
+		Ok(lua::Material::new(SurfaceDetail::new(material, detail)))
 	}
 }
 
-impl Cube
+impl RoughDiffuse
 {
-	pub fn lua_unit_cube<'lua>(
+	pub fn lua_oren_nayar<'lua>(
 		lua: Context<'lua>,
-		lua_name: Value<'lua>,
-	) -> rlua::Result<lua::SceneNode>
+		lua_value: (AnyUserData<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Material>
 	{
-		let name = String::from_lua(lua_name, lua)?;
+		let (lua_material, lua_roughness) = lua_value;
 
-		let node = lua::SceneNode::new(&name, Some(Arc::new(Cube::unit_cube())));
+		let material = lua_material.borrow::<lua::Material>()?.get_inner().clone();
+		let roughness = lua::Parameter::from_lua(lua_roughness, lua)?;
 
-		Ok(node)
+		let model = rough_diffuse::Model::OrenNayar {
+			roughness: roughness.into(),
+		};
+
+		Ok(lua::Material::new(RoughDiffuse::new(material, model)))
+	}
+
+	pub fn lua_retroreflective<'lua>(
+		lua: Context<'lua>,
+		lua_value: (AnyUserData<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Material>
+	{
+		let (lua_material, lua_roughness) = lua_value;
+
+		let material = lua_material.borrow::<lua::Material>()?.get_inner().clone();
+		let roughness = lua::Parameter::from_lua(lua_roughness, lua)?;
+
+		let model = rough_diffuse::Model::Retroreflective {
+			roughness: roughness.into(),
+		};
+
+		Ok(lua::Material::new(RoughDiffuse::new(material, model)))
 	}
+}
 
+impl Mix
+{
 	pub fn lua_new<'lua>(
 		lua: Context<'lua>,
-		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
-	) -> rlua::Result<lua::SceneNode>
+		lua_value: (AnyUserData<'lua>, AnyUserData<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Material>
 	{
-		let (lua_name, lua_position, lua_size) = lua_value;
+		let (lua_a, lua_b, lua_factor) = lua_value;
 
-		let name = String::from_lua(lua_name, lua)?;
-		let position = lua::Vector3::from_lua(lua_position, lua)?;
-		let size = f32::from_lua(lua_size, lua)?;
+		let a = lua_a.borrow::<lua::Material>()?.get_inner().clone();
+		let b = lua_b.borrow::<lua::Material>()?.get_inner().clone();
+		let factor = lua::Parameter::from_lua(lua_factor, lua)?;
 
-		let node = lua::SceneNode::new(&name, Some(Arc::new(Cube::new(position.into(), size))));
+		Ok(lua::Material::new(Mix::new(a, b, factor.into())))
+	}
+}
 
-		Ok(node)
+impl Coated
+{
+	// The index of refraction defaults to 1.5, as for most varnishes and
+	// lacquers, and the coat is clear unless given a tint
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (AnyUserData<'lua>, Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Material>
+	{
+		let (lua_material, lua_roughness, lua_refractive_index, lua_tint) = lua_value;
+
+		let material = lua_material.borrow::<lua::Material>()?.get_inner().clone();
+		let roughness = lua::Parameter::from_lua(lua_roughness, lua)?;
+		let refractive_index = Option::<f32>::from_lua(lua_refractive_index, lua)?.unwrap_or(1.5);
+		let tint = match Option::<lua::Parameter>::from_lua(lua_tint, lua)? {
+			Some(tint) => tint.into(),
+			None => na::Vector3::repeat(1.0).into(),
+		};
+
+		Ok(lua::Material::new(Coated::new(
+			material,
+			roughness.into(),
+			refractive_index,
+			tint,
+		)))
 	}
 }
 
-impl Mesh
+impl Emissive
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Option<AnyUserData<'lua>>),
+	) -> rlua::Result<lua::Material>
+	{
+		let (lua_colour, lua_strength, lua_material) = lua_value;
+
+		let colour = lua::Parameter::from_lua(lua_colour, lua)?;
+		let strength = Option::<f32>::from_lua(lua_strength, lua)?.unwrap_or(1.0);
+
+		// Without a material to shade it with, only the light given off is seen
+		let material = match lua_material {
+			Some(lua_material) => Some(lua_material.borrow::<lua::Material>()?.get_inner().clone()),
+			None => None,
+		};
+
+		Ok(lua::Material::new(Emissive::new(
+			colour.into(),
+			strength,
+			material,
+		)))
+	}
+}
+
+impl ImageTexture
 {
 	pub fn lua_new<'lua>(
 		lua: Context<'lua>,
 		lua_value: (Value<'lua>, Value<'lua>),
-	) -> rlua::Result<lua::SceneNode>
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
 	{
-		let (lua_name, lua_file_name) = lua_value;
+		let (lua_file_name, lua_options) = lua_value;
 
-		let name = String::from_lua(lua_name, lua)?;
 		let file_name = String::from_lua(lua_file_name, lua)?;
+		let options = Option::<Table>::from_lua(lua_options, lua)?;
 
-		let mesh = match Mesh::from_file(&file_name) {
-			Ok(mesh) => mesh,
+		let (wrap, srgb) = match options {
+			Some(options) => (
+				options.get::<_, Option<String>>("wrap")?,
+				options.get::<_, Option<bool>>("srgb")?.unwrap_or(false),
+			),
+			None => (None, false),
+		};
+
+		let wrap = match wrap.as_deref() {
+			None | Some("repeat") => WrapMode::Repeat,
+			Some("clamp") => WrapMode::Clamp,
+			Some("mirror") => WrapMode::Mirror,
+			Some(other) => {
+				return Err(rlua::Error::RuntimeError(format!(
+					"Unknown texture wrap mode \"{}\", expected repeat, clamp or mirror",
+					other
+				)))
+			},
+		};
+
+		let texture = match ImageTexture::from_file(&file_name, wrap, srgb) {
+			Ok(texture) => texture,
 			Err(read_error) => return Err(rlua::Error::ExternalError(Arc::new(read_error))),
 		};
 
-		let node = lua::SceneNode::new(&name, Some(Arc::new(mesh)));
+		Ok(lua::Pointer::from(Arc::new(texture) as Arc<dyn Texture>))
+	}
+}
 
-		Ok(node)
+impl texture::Colours
+{
+	// The two colours of a pattern, from the colours option of a constructor
+	fn lua_from_options(options: &Table) -> rlua::Result<Self>
+	{
+		match options.get::<_, Option<Table>>("colours")? {
+			Some(colours) => {
+				let low = colours.get::<_, lua::Parameter>(1)?;
+				let high = colours.get::<_, lua::Parameter>(2)?;
+
+				Ok(texture::Colours::new(low.into(), high.into()))
+			},
+			None => Ok(texture::Colours::new(0.0.into(), 1.0.into())),
+		}
+	}
+}
+
+impl texture::Space
+{
+	fn lua_from_options(options: &Table) -> rlua::Result<Self>
+	{
+		match options.get::<_, Option<String>>("space")?.as_deref() {
+			None | Some("object") => Ok(texture::Space::Object),
+			Some("uv") => Ok(texture::Space::Uv),
+			Some(other) => Err(rlua::Error::RuntimeError(format!(
+				"Unknown texture space \"{}\", expected object or uv",
+				other
+			))),
+		}
+	}
+}
+
+impl texture::Checker
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_options: Value<'lua>,
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		let options = Table::from_lua(lua_options, lua)?;
+
+		let checker = texture::Checker::new(
+			texture::Colours::lua_from_options(&options)?,
+			options.get::<_, Option<f32>>("frequency")?.unwrap_or(1.0),
+			texture::Space::lua_from_options(&options)?,
+		);
+
+		Ok(lua::Pointer::from(Arc::new(checker) as Arc<dyn Texture>))
+	}
+}
+
+impl texture::NoiseTexture
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_options: Value<'lua>,
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		let options = Table::from_lua(lua_options, lua)?;
+
+		let noise_type = match options.get::<_, Option<String>>("type")?.as_deref() {
+			None | Some("perlin") => texture::NoiseType::Perlin,
+			Some("fbm") => texture::NoiseType::Fbm,
+			Some("turbulence") => texture::NoiseType::Turbulence,
+			Some("worley") => texture::NoiseType::Worley,
+			Some(other) => {
+				return Err(rlua::Error::RuntimeError(format!(
+					"Unknown noise type \"{}\", expected perlin, fbm, turbulence or worley",
+					other
+				)))
+			},
+		};
+
+		let noise = texture::NoiseTexture::new(
+			noise_type,
+			texture::Colours::lua_from_options(&options)?,
+			options.get::<_, Option<f32>>("frequency")?.unwrap_or(1.0),
+			options.get::<_, Option<u32>>("octaves")?.unwrap_or(4),
+			options.get::<_, Option<u64>>("seed")?.unwrap_or(0),
+			texture::Space::lua_from_options(&options)?,
+		);
+
+		Ok(lua::Pointer::from(Arc::new(noise) as Arc<dyn Texture>))
+	}
+}
+
+impl texture::Pattern
+{
+	pub fn lua_marble<'lua>(
+		lua: Context<'lua>,
+		lua_options: Value<'lua>,
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		texture::Pattern::lua_new(texture::PatternType::Marble, lua, lua_options)
+	}
+
+	pub fn lua_wood<'lua>(
+		lua: Context<'lua>,
+		lua_options: Value<'lua>,
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		texture::Pattern::lua_new(texture::PatternType::Wood, lua, lua_options)
+	}
+
+	fn lua_new<'lua>(
+		pattern_type: texture::PatternType,
+		lua: Context<'lua>,
+		lua_options: Value<'lua>,
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		let options = Table::from_lua(lua_options, lua)?;
+
+		let pattern = texture::Pattern::new(
+			pattern_type,
+			texture::Colours::lua_from_options(&options)?,
+			options.get::<_, Option<f32>>("frequency")?.unwrap_or(1.0),
+			options.get::<_, Option<u32>>("octaves")?.unwrap_or(4),
+			options.get::<_, Option<u64>>("seed")?.unwrap_or(0),
+			texture::Space::lua_from_options(&options)?,
+		);
+
+		Ok(lua::Pointer::from(Arc::new(pattern) as Arc<dyn Texture>))
+	}
+}
+
+impl texture::Mix
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		let (lua_a, lua_b, lua_factor) = lua_value;
+
+		let a = lua::Parameter::from_lua(lua_a, lua)?;
+		let b = lua::Parameter::from_lua(lua_b, lua)?;
+		let factor = lua::Parameter::from_lua(lua_factor, lua)?;
+
+		let mix = texture::Mix::new(a.into(), b.into(), factor.into());
+
+		Ok(lua::Pointer::from(Arc::new(mix) as Arc<dyn Texture>))
+	}
+}
+
+impl texture::Multiply
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		let (lua_a, lua_b) = lua_value;
+
+		let a = lua::Parameter::from_lua(lua_a, lua)?;
+		let b = lua::Parameter::from_lua(lua_b, lua)?;
+
+		let multiply = texture::Multiply::new(a.into(), b.into());
+
+		Ok(lua::Pointer::from(Arc::new(multiply) as Arc<dyn Texture>))
+	}
+}
+
+impl texture::Remap
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		let (lua_input, lua_from, lua_to) = lua_value;
+
+		let input = lua::Parameter::from_lua(lua_input, lua)?;
+
+		// Ranges are given as {min, max}
+		let range = |lua_range: Value<'lua>| -> rlua::Result<(f32, f32)> {
+			let range = Table::from_lua(lua_range, lua)?;
+
+			Ok((range.get(1)?, range.get(2)?))
+		};
+
+		let remap = texture::Remap::new(input.into(), range(lua_from)?, range(lua_to)?);
+
+		Ok(lua::Pointer::from(Arc::new(remap) as Arc<dyn Texture>))
+	}
+}
+
+impl texture::Fresnel
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_refractive_index: Value<'lua>,
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		let refractive_index = f32::from_lua(lua_refractive_index, lua)?;
+
+		let fresnel = texture::Fresnel::new(refractive_index);
+
+		Ok(lua::Pointer::from(Arc::new(fresnel) as Arc<dyn Texture>))
+	}
+}
+
+impl texture::Coordinates
+{
+	pub fn lua_uv(_: Context<'_>, _: ()) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		Ok(lua::Pointer::from(
+			Arc::new(texture::Coordinates::Uv) as Arc<dyn Texture>
+		))
+	}
+
+	pub fn lua_object_position(_: Context<'_>, _: ()) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		Ok(lua::Pointer::from(
+			Arc::new(texture::Coordinates::ObjectPosition) as Arc<dyn Texture>,
+		))
+	}
+
+	pub fn lua_world_normal(_: Context<'_>, _: ()) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		Ok(lua::Pointer::from(
+			Arc::new(texture::Coordinates::WorldNormal) as Arc<dyn Texture>,
+		))
+	}
+}
+
+impl texture::Mapping
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn Texture>>
+	{
+		let (lua_texture, lua_coordinates) = lua_value;
+
+		let texture = lua::Parameter::from_lua(lua_texture, lua)?;
+		let coordinates = lua::Parameter::from_lua(lua_coordinates, lua)?;
+
+		let mapping = texture::Mapping::new(texture.into(), coordinates.into());
+
+		Ok(lua::Pointer::from(Arc::new(mapping) as Arc<dyn Texture>))
+	}
+}
+
+impl Sphere
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_position, lua_radius) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let position = lua::Vector3::from_lua(lua_position, lua)?;
+		let radius = f32::from_lua(lua_radius, lua)?;
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Sphere::new(position.into(), radius))));
+
+		Ok(node)
+	}
+
+	pub fn lua_unit_sphere<'lua>(
+		lua: Context<'lua>,
+		lua_name: Value<'lua>,
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let name = String::from_lua(lua_name, lua)?;
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Sphere::unit_sphere())));
+
+		Ok(node)
+	}
+}
+
+impl Cube
+{
+	pub fn lua_unit_cube<'lua>(
+		lua: Context<'lua>,
+		lua_name: Value<'lua>,
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let name = String::from_lua(lua_name, lua)?;
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Cube::unit_cube())));
+
+		Ok(node)
+	}
+
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_position, lua_size) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let position = lua::Vector3::from_lua(lua_position, lua)?;
+		let size = f32::from_lua(lua_size, lua)?;
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Cube::new(position.into(), size))));
+
+		Ok(node)
+	}
+}
+
+impl Plane
+{
+	pub fn lua_new<'lua>(lua: Context<'lua>, lua_name: Value<'lua>)
+		-> rlua::Result<lua::SceneNode>
+	{
+		let name = String::from_lua(lua_name, lua)?;
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Plane::new())));
+
+		Ok(node)
+	}
+}
+
+impl Disk
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_position, lua_radius) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let position = lua::Vector3::from_lua(lua_position, lua)?;
+		let radius = f32::from_lua(lua_radius, lua)?;
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Disk::new(position.into(), radius))));
+
+		Ok(node)
+	}
+}
+
+impl Rectangle
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_position, lua_width, lua_height) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let position = lua::Vector3::from_lua(lua_position, lua)?;
+		let width = f32::from_lua(lua_width, lua)?;
+		let height = f32::from_lua(lua_height, lua)?;
+
+		let node = lua::SceneNode::new(
+			&name,
+			Some(Arc::new(Rectangle::new(position.into(), width, height))),
+		);
+
+		Ok(node)
+	}
+}
+
+impl Cylinder
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_radius, lua_height, lua_capped) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let radius = f32::from_lua(lua_radius, lua)?;
+		let height = f32::from_lua(lua_height, lua)?;
+		let capped = Option::<bool>::from_lua(lua_capped, lua)?.unwrap_or(true);
+
+		let node =
+			lua::SceneNode::new(&name, Some(Arc::new(Cylinder::new(radius, height, capped))));
+
+		Ok(node)
+	}
+}
+
+impl Cone
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_radius, lua_height, lua_capped) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let radius = f32::from_lua(lua_radius, lua)?;
+		let height = f32::from_lua(lua_height, lua)?;
+		let capped = Option::<bool>::from_lua(lua_capped, lua)?.unwrap_or(true);
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Cone::new(radius, height, capped))));
+
+		Ok(node)
+	}
+}
+
+impl Capsule
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_radius, lua_height) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let radius = f32::from_lua(lua_radius, lua)?;
+		let height = f32::from_lua(lua_height, lua)?;
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Capsule::new(radius, height))));
+
+		Ok(node)
+	}
+}
+
+impl Torus
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_major_radius, lua_minor_radius) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let major_radius = f32::from_lua(lua_major_radius, lua)?;
+		let minor_radius = f32::from_lua(lua_minor_radius, lua)?;
+
+		let node = lua::SceneNode::new(
+			&name,
+			Some(Arc::new(Torus::new(major_radius, minor_radius))),
+		);
+
+		Ok(node)
+	}
+}
+
+impl Sdf
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_field) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let field = lua::Pointer::<dyn DistanceField>::from_lua(lua_field, lua)?;
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Sdf::new(field.into()))));
+
+		Ok(node)
+	}
+}
+
+impl sdf::SphereField
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_radius: Value<'lua>,
+	) -> rlua::Result<lua::Pointer<dyn DistanceField>>
+	{
+		let radius = f32::from_lua(lua_radius, lua)?;
+
+		Ok(lua::Pointer::from(
+			Arc::new(sdf::SphereField::new(radius)) as Arc<dyn DistanceField>
+		))
+	}
+}
+
+impl sdf::BoxField
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_size: Value<'lua>,
+	) -> rlua::Result<lua::Pointer<dyn DistanceField>>
+	{
+		let size = lua::Vector3::from_lua(lua_size, lua)?;
+
+		Ok(lua::Pointer::from(
+			Arc::new(sdf::BoxField::new(na::Vector3::from(size))) as Arc<dyn DistanceField>,
+		))
+	}
+}
+
+impl sdf::TorusField
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn DistanceField>>
+	{
+		let (lua_major_radius, lua_minor_radius) = lua_value;
+
+		let major_radius = f32::from_lua(lua_major_radius, lua)?;
+		let minor_radius = f32::from_lua(lua_minor_radius, lua)?;
+
+		Ok(lua::Pointer::from(
+			Arc::new(sdf::TorusField::new(major_radius, minor_radius)) as Arc<dyn DistanceField>,
+		))
+	}
+}
+
+impl sdf::Translation
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn DistanceField>>
+	{
+		let (lua_field, lua_offset) = lua_value;
+
+		let field = lua::Pointer::<dyn DistanceField>::from_lua(lua_field, lua)?;
+		let offset = lua::Vector3::from_lua(lua_offset, lua)?;
+
+		Ok(lua::Pointer::from(Arc::new(sdf::Translation::new(
+			field.into(),
+			na::Vector3::from(offset),
+		)) as Arc<dyn DistanceField>))
+	}
+}
+
+impl sdf::SmoothUnion
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn DistanceField>>
+	{
+		let (lua_first, lua_second, lua_k) = lua_value;
+
+		let first = lua::Pointer::<dyn DistanceField>::from_lua(lua_first, lua)?;
+		let second = lua::Pointer::<dyn DistanceField>::from_lua(lua_second, lua)?;
+		let k = f32::from_lua(lua_k, lua)?;
+
+		Ok(lua::Pointer::from(
+			Arc::new(sdf::SmoothUnion::new(first.into(), second.into(), k))
+				as Arc<dyn DistanceField>,
+		))
+	}
+}
+
+impl sdf::Repetition
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn DistanceField>>
+	{
+		let (lua_field, lua_spacing, lua_count) = lua_value;
+
+		let field = lua::Pointer::<dyn DistanceField>::from_lua(lua_field, lua)?;
+		let spacing = lua::Vector3::from_lua(lua_spacing, lua)?;
+		let count = lua::Vector3::<u32>::from_lua(lua_count, lua)?;
+
+		Ok(lua::Pointer::from(Arc::new(sdf::Repetition::new(
+			field.into(),
+			na::Vector3::from(spacing),
+			na::Vector3::from(count),
+		)) as Arc<dyn DistanceField>))
+	}
+}
+
+impl sdf::Twist
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn DistanceField>>
+	{
+		let (lua_field, lua_degrees_per_unit) = lua_value;
+
+		let field = lua::Pointer::<dyn DistanceField>::from_lua(lua_field, lua)?;
+		let degrees_per_unit = f32::from_lua(lua_degrees_per_unit, lua)?;
+
+		Ok(lua::Pointer::from(
+			Arc::new(sdf::Twist::new(field.into(), degrees_per_unit)) as Arc<dyn DistanceField>,
+		))
+	}
+}
+
+impl sdf::Mandelbulb
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::Pointer<dyn DistanceField>>
+	{
+		let (lua_power, lua_iterations) = lua_value;
+
+		let power = Option::<f32>::from_lua(lua_power, lua)?.unwrap_or(8.0);
+		let iterations = Option::<u32>::from_lua(lua_iterations, lua)?.unwrap_or(10);
+
+		if power <= 1.0 {
+			return Err(rlua::Error::RuntimeError(format!(
+				"gr.sdf_mandelbulb needs a power above 1, got {}",
+				power
+			)));
+		}
+
+		Ok(lua::Pointer::from(
+			Arc::new(sdf::Mandelbulb::new(power, iterations)) as Arc<dyn DistanceField>,
+		))
+	}
+}
+
+impl Implicit
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_function, lua_bounds, lua_resolution) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let function = Function::from_lua(lua_function, lua)?;
+		let bounds = Table::from_lua(lua_bounds, lua)?;
+		let resolution = Option::<usize>::from_lua(lua_resolution, lua)?.unwrap_or(64);
+
+		let lower = lua::Vector3::<f32>::from_lua(bounds.get(1)?, lua)?;
+		let upper = lua::Vector3::<f32>::from_lua(bounds.get(2)?, lua)?;
+
+		// Lua can only be called from this thread, so the function is sampled
+		// here rather than while rendering
+		let field = implicit::SampledField::from_fn(
+			na::Vector3::from(lower),
+			na::Vector3::from(upper),
+			resolution,
+			|point| function.call::<_, f32>((point.x, point.y, point.z)),
+		)?;
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Implicit::new(Box::new(field)))));
+
+		Ok(node)
+	}
+}
+
+impl implicit::Metaballs
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_balls, lua_threshold) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let lua_balls = Table::from_lua(lua_balls, lua)?;
+		let threshold = Option::<f32>::from_lua(lua_threshold, lua)?.unwrap_or(0.5);
+
+		let mut balls = Vec::new();
+		for lua_ball in lua_balls.sequence_values::<Table>() {
+			let lua_ball = lua_ball?;
+
+			let center = lua::Vector3::<f32>::from_lua(lua_ball.get(1)?, lua)?;
+			let radius = f32::from_lua(lua_ball.get(2)?, lua)?;
+
+			balls.push(implicit::Ball::new(na::Vector3::from(center), radius));
+		}
+
+		if balls.is_empty() {
+			return Err(rlua::Error::RuntimeError(format!(
+				"Metaballs {} need at least one ball",
+				name
+			)));
+		}
+
+		let metaballs = implicit::Metaballs::new(balls, threshold);
+		let node = lua::SceneNode::new(&name, Some(Arc::new(Implicit::new(Box::new(metaballs)))));
+
+		Ok(node)
+	}
+}
+
+impl Csg
+{
+	pub fn lua_union<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Variadic<Value<'lua>>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		Csg::lua_new(lua, lua_value, csg::Operation::Union)
+	}
+
+	pub fn lua_intersection<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Variadic<Value<'lua>>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		Csg::lua_new(lua, lua_value, csg::Operation::Intersection)
+	}
+
+	pub fn lua_difference<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Variadic<Value<'lua>>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		Csg::lua_new(lua, lua_value, csg::Operation::Difference)
+	}
+
+	fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Variadic<Value<'lua>>),
+		operation: csg::Operation,
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_children) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+
+		let mut children = Vec::new();
+		for lua_child in lua_children {
+			children.push(lua::SceneNode::from_lua(lua_child, lua)?);
+		}
+
+		if children.len() < 2 {
+			return Err(rlua::Error::RuntimeError(format!(
+				"CSG node {} needs at least two child nodes",
+				name
+			)));
+		}
+
+		Ok(lua::SceneNode::new_csg(&name, operation, children))
+	}
+}
+
+impl Mesh
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_file_name, lua_options) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let file_name = String::from_lua(lua_file_name, lua)?;
+		let options = Option::<Table>::from_lua(lua_options, lua)?;
+
+		let mut mesh = match Mesh::from_file(&file_name) {
+			Ok(mesh) => mesh,
+			Err(read_error) => return Err(rlua::Error::ExternalError(Arc::new(read_error))),
+		};
+
+		if let Some(options) = options {
+			let subdivide = options.get::<_, Option<u32>>("subdivide")?.unwrap_or(0);
+			let crease_angle = options.get::<_, Option<f32>>("crease_angle")?;
+
+			mesh.subdivide(subdivide, crease_angle);
+		}
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(mesh)));
+
+		Ok(node)
+	}
+
+	// Returns a list of {position = {x, y, z}, normal = {x, y, z}} tables
+	// spread over a mesh node's surface, placed by the node's transform as its
+	// children would be, for scattering objects over it
+	pub fn lua_sample<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<Table<'lua>>
+	{
+		let (lua_node, lua_count, lua_seed) = lua_value;
+
+		let node = lua::SceneNode::from_lua(lua_node, lua)?;
+		let count = usize::from_lua(lua_count, lua)?;
+		let seed = Option::<u64>::from_lua(lua_seed, lua)?.unwrap_or(0);
+
+		let transform = node.get_transform();
+
+		let points = node.get_primitive().and_then(|primitive| {
+			primitive.sample_surface(count, &mut StdRng::seed_from_u64(seed))
+		});
+
+		let (points, normal_transform) = match (points, transform.try_inverse()) {
+			(Some(points), Some(normal_transform)) => (points, normal_transform),
+			_ => {
+				return Err(rlua::Error::RuntimeError(String::from(
+					"gr.sample_mesh needs a mesh node with a transform that can be inverted",
+				)))
+			},
+		};
+
+		let samples = lua.create_table()?;
+
+		for (index, (position, normal)) in points.into_iter().enumerate() {
+			let position = transform * position;
+			let normal = math::transform_normals(normal, normal_transform).normalize();
+
+			let sample = lua.create_table()?;
+			sample.set("position", vec![position.x, position.y, position.z])?;
+			sample.set("normal", vec![normal.x, normal.y, normal.z])?;
+
+			samples.set(index + 1, sample)?;
+		}
+
+		Ok(samples)
+	}
+}
+
+impl BezierPatch
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_file_name, lua_rate) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let file_name = String::from_lua(lua_file_name, lua)?;
+		let rate = Option::<usize>::from_lua(lua_rate, lua)?.unwrap_or(8);
+
+		let patches = match BezierPatch::from_file(&file_name, rate) {
+			Ok(patches) => patches,
+			Err(read_error) => return Err(rlua::Error::ExternalError(Arc::new(read_error))),
+		};
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(patches)));
+
+		Ok(node)
+	}
+}
+
+impl Heightfield
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_file_name, lua_scale) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let file_name = String::from_lua(lua_file_name, lua)?;
+		let scale = Option::<f32>::from_lua(lua_scale, lua)?.unwrap_or(1.0);
+
+		let heightfield = match Heightfield::from_file(&file_name, scale) {
+			Ok(heightfield) => heightfield,
+			Err(read_error) => return Err(rlua::Error::ExternalError(Arc::new(read_error))),
+		};
+
+		let node = lua::SceneNode::new(&name, Some(Arc::new(heightfield)));
+
+		Ok(node)
+	}
+}
+
+impl Curve
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>, Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_points, lua_widths, lua_type) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let lua_points = Table::from_lua(lua_points, lua)?;
+		let curve_type = Option::<String>::from_lua(lua_type, lua)?;
+
+		let mut points = Vec::new();
+		for lua_point in lua_points.sequence_values::<Value>() {
+			points.push(na::Vector3::from(lua::Vector3::<f32>::from_lua(
+				lua_point?, lua,
+			)?));
+		}
+
+		if points.len() < 4 || !(points.len() - 1).is_multiple_of(3) {
+			return Err(rlua::Error::RuntimeError(format!(
+				"Curve {} needs 3n + 1 control points, found {}",
+				name,
+				points.len()
+			)));
+		}
+
+		// Either one width for the whole curve or a width per control point
+		let widths = match lua_widths {
+			Value::Table(lua_widths) => lua_widths
+				.sequence_values::<f32>()
+				.collect::<rlua::Result<Vec<f32>>>()?,
+			lua_width => vec![f32::from_lua(lua_width, lua)?; points.len()],
+		};
+
+		if widths.len() != points.len() {
+			return Err(rlua::Error::RuntimeError(format!(
+				"Curve {} has {} control points but {} widths",
+				name,
+				points.len(),
+				widths.len()
+			)));
+		}
+
+		let curve_type = match curve_type.as_deref() {
+			None | Some("cylinder") => CurveType::Cylinder,
+			Some("flat") => CurveType::Flat,
+			Some(other) => {
+				return Err(rlua::Error::RuntimeError(format!(
+					"Unknown curve type \"{}\", expected \"cylinder\" or \"flat\"",
+					other
+				)))
+			},
+		};
+
+		let node = lua::SceneNode::new(
+			&name,
+			Some(Arc::new(Curve::new(points, widths, curve_type))),
+		);
+
+		Ok(node)
+	}
+}
+
+impl Instance
+{
+	pub fn lua_new<'lua>(
+		lua: Context<'lua>,
+		lua_value: (Value<'lua>, Value<'lua>),
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let (lua_name, lua_prototype) = lua_value;
+
+		let name = String::from_lua(lua_name, lua)?;
+		let prototype = lua::SceneNode::from_lua(lua_prototype, lua)?;
+
+		Ok(lua::SceneNode::new_instance(&name, prototype))
+	}
+}
+
+impl SceneNode
+{
+	// Takes a table of options, the surface, prototype and count are required
+	pub fn lua_scatter<'lua>(
+		lua: Context<'lua>,
+		lua_options: Value<'lua>,
+	) -> rlua::Result<lua::SceneNode>
+	{
+		let options = Table::from_lua(lua_options, lua)?;
+
+		let name = options
+			.get::<_, Option<String>>("name")?
+			.unwrap_or_else(|| String::from("scatter"));
+		let surface = options.get::<_, SceneNode>("surface")?;
+		let prototype = options.get::<_, SceneNode>("prototype")?;
+		let scale_range = options
+			.get::<_, Option<Vec<f32>>>("scale_range")?
+			.unwrap_or_else(|| vec![1.0, 1.0]);
+
+		if scale_range.len() != 2 {
+			return Err(rlua::Error::RuntimeError(format!(
+				"Scatter {} expected a scale_range of {{min, max}}",
+				name
+			)));
+		}
+
+		let (min_scale, max_scale) = (scale_range[0], scale_range[1]);
+
+		// Instances scaled to nothing have no inverse to be hit through
+		if min_scale <= 0.0 || min_scale > max_scale {
+			return Err(rlua::Error::RuntimeError(format!(
+				"Scatter {} needs a scale_range with 0 < min <= max, got {{{}, {}}}",
+				name, min_scale, max_scale
+			)));
+		}
+
+		let min_distance = options.get::<_, Option<f32>>("min_distance")?;
+
+		if let Some(min_distance) = min_distance.filter(|&min_distance| min_distance <= 0.0) {
+			return Err(rlua::Error::RuntimeError(format!(
+				"Scatter {} needs a positive min_distance, got {}",
+				name, min_distance
+			)));
+		}
+
+		let density = match options.get::<_, Option<String>>("density")? {
+			Some(file_name) => match scatter::DensityMap::from_file(&file_name) {
+				Ok(density) => Some(density),
+				Err(read_error) => return Err(rlua::Error::ExternalError(Arc::new(read_error))),
+			},
+			None => None,
+		};
+
+		let scatter_options = scatter::Options {
+			count: options.get("count")?,
+			seed: options.get::<_, Option<u64>>("seed")?.unwrap_or(0),
+			random_rotation: options
+				.get::<_, Option<bool>>("random_rotation")?
+				.unwrap_or(false),
+			scale_range: (min_scale, max_scale),
+			align_to_normal: options
+				.get::<_, Option<bool>>("align_to_normal")?
+				.unwrap_or(false),
+			min_distance: min_distance,
+			density: density,
+		};
+
+		// The points themselves are drawn once the scene is built
+		let can_sample = surface.get_primitive().is_some_and(|primitive| {
+			primitive
+				.sample_surface(0, &mut StdRng::seed_from_u64(0))
+				.is_some()
+		});
+
+		if !can_sample {
+			return Err(rlua::Error::RuntimeError(format!(
+				"Scatter {} needs a mesh as its surface",
+				name
+			)));
+		}
+
+		Ok(SceneNode::new_scatter(
+			&name,
+			prototype,
+			surface,
+			scatter_options,
+		))
 	}
 }
 
@@ -1,13 +1,17 @@
 pub mod constructors;
 pub mod light;
 pub mod material;
+pub mod parameter;
 pub mod pointer;
+pub mod refractive_index;
 pub mod scene_builder;
 pub mod scene_node;
 pub mod vector3;
 
 pub use self::material::Material;
+pub use self::parameter::Parameter;
 pub use self::pointer::Pointer;
+pub use self::refractive_index::RefractiveIndex;
 pub use self::scene_builder::SceneBuilder;
 pub use self::scene_node::SceneNode;
 pub use self::vector3::Vector3;
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use lua;
+use rlua::Context;
+use rlua::FromLua;
+use rlua::Result;
+use rlua::Value;
+use shading;
+use shading::Texture;
+
+// Material parameter given as a texture, a {r, g, b} table or a number
+pub struct Parameter(shading::Parameter);
+
+impl From<Parameter> for shading::Parameter
+{
+	fn from(lua_parameter: Parameter) -> Self
+	{
+		lua_parameter.0
+	}
+}
+
+impl<'lua> FromLua<'lua> for Parameter
+{
+	fn from_lua(lua_value: Value<'lua>, lua: Context<'lua>) -> Result<Self>
+	{
+		let parameter = match lua_value {
+			Value::UserData(_) => {
+				let texture = lua::Pointer::<dyn Texture>::from_lua(lua_value, lua)?;
+
+				shading::Parameter::Texture(Arc::from(texture))
+			},
+			Value::Integer(_) | Value::Number(_) => f32::from_lua(lua_value, lua)?.into(),
+			_ => shading::Parameter::Constant(lua::Vector3::from_lua(lua_value, lua)?.into()),
+		};
+
+		Ok(Parameter(parameter))
+	}
+}
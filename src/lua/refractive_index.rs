@@ -0,0 +1,35 @@
+use rlua::Context;
+use rlua::FromLua;
+use rlua::Result;
+use rlua::Table;
+use rlua::Value;
+use spectrum;
+
+// Index of refraction given as a number, or as a table of Cauchy's two
+// coefficients for an index that varies with wavelength
+pub struct RefractiveIndex(spectrum::RefractiveIndex);
+
+impl From<RefractiveIndex> for spectrum::RefractiveIndex
+{
+	fn from(lua_refractive_index: RefractiveIndex) -> Self
+	{
+		lua_refractive_index.0
+	}
+}
+
+impl<'lua> FromLua<'lua> for RefractiveIndex
+{
+	fn from_lua(lua_value: Value<'lua>, lua: Context<'lua>) -> Result<Self>
+	{
+		let refractive_index = match lua_value {
+			Value::Table(_) => {
+				let coefficients = Table::from_lua(lua_value, lua)?;
+
+				spectrum::RefractiveIndex::Cauchy(coefficients.get(1)?, coefficients.get(2)?)
+			},
+			_ => spectrum::RefractiveIndex::Constant(f32::from_lua(lua_value, lua)?),
+		};
+
+		Ok(RefractiveIndex(refractive_index))
+	}
+}
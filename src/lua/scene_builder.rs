@@ -1,18 +1,46 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
+use std::sync::Arc;
+use std::sync::Mutex;
 
 use cli;
 use lua;
+use primitives::implicit;
+use primitives::sdf;
+use primitives::BezierPatch;
+use primitives::Capsule;
+use primitives::Cone;
+use primitives::Csg;
 use primitives::Cube;
+use primitives::Curve;
+use primitives::Cylinder;
+use primitives::Disk;
+use primitives::Heightfield;
+use primitives::Implicit;
+use primitives::Instance;
 use primitives::Mesh;
+use primitives::Plane;
+use primitives::Rectangle;
+use primitives::Sdf;
 use primitives::Sphere;
+use primitives::Torus;
 use render;
 use rlua::Context;
 use rlua::FromLua;
 use rlua::Lua;
 use rlua::Value;
+use shading::texture;
+use shading::texture::ImageTexture;
+use shading::Coated;
 use shading::CookTorrance;
+use shading::Emissive;
+use shading::KajiyaKay;
+use shading::Mix;
 use shading::Phong;
+use shading::Principled;
+use shading::RoughDiffuse;
+use shading::SurfaceDetail;
 use Light;
 
 pub struct SceneBuilder
@@ -20,6 +48,14 @@ pub struct SceneBuilder
 	lua: Lua,
 }
 
+// State shared by every gr.render call of a script
+struct RenderSession
+{
+	cli_parameters: cli::Parameters,
+	// Y4M streams stay open between render calls so each call appends a frame
+	video_streams: render::VideoStreams,
+}
+
 impl SceneBuilder
 {
 	pub fn new(parameters: cli::Parameters) -> Self
@@ -84,11 +120,133 @@ impl SceneBuilder
 				.create_function(Cube::lua_unit_cube)
 				.expect("Failed to create cube constructor");
 
+			// Plane Constructor
+			let plane_ctor = lua_ctx
+				.create_function(Plane::lua_new)
+				.expect("Failed to create plane constructor");
+
+			// Disk Constructor
+			let disk_ctor = lua_ctx
+				.create_function(Disk::lua_new)
+				.expect("Failed to create disk constructor");
+
+			// Rectangle Constructor
+			let rect_ctor = lua_ctx
+				.create_function(Rectangle::lua_new)
+				.expect("Failed to create rect constructor");
+
+			// Cylinder Constructor
+			let cylinder_ctor = lua_ctx
+				.create_function(Cylinder::lua_new)
+				.expect("Failed to create cylinder constructor");
+
+			// Cone Constructor
+			let cone_ctor = lua_ctx
+				.create_function(Cone::lua_new)
+				.expect("Failed to create cone constructor");
+
+			// Capsule Constructor
+			let capsule_ctor = lua_ctx
+				.create_function(Capsule::lua_new)
+				.expect("Failed to create capsule constructor");
+
+			// Torus Constructor
+			let torus_ctor = lua_ctx
+				.create_function(Torus::lua_new)
+				.expect("Failed to create torus constructor");
+
+			// CSG Constructors
+			let union_ctor = lua_ctx
+				.create_function(Csg::lua_union)
+				.expect("Failed to create union constructor");
+
+			let intersection_ctor = lua_ctx
+				.create_function(Csg::lua_intersection)
+				.expect("Failed to create intersection constructor");
+
+			let difference_ctor = lua_ctx
+				.create_function(Csg::lua_difference)
+				.expect("Failed to create difference constructor");
+
+			// Distance field constructors
+			let sdf_ctor = lua_ctx
+				.create_function(Sdf::lua_new)
+				.expect("Failed to create sdf constructor");
+
+			let sdf_sphere_ctor = lua_ctx
+				.create_function(sdf::SphereField::lua_new)
+				.expect("Failed to create sdf_sphere constructor");
+
+			let sdf_box_ctor = lua_ctx
+				.create_function(sdf::BoxField::lua_new)
+				.expect("Failed to create sdf_box constructor");
+
+			let sdf_torus_ctor = lua_ctx
+				.create_function(sdf::TorusField::lua_new)
+				.expect("Failed to create sdf_torus constructor");
+
+			let sdf_translate_ctor = lua_ctx
+				.create_function(sdf::Translation::lua_new)
+				.expect("Failed to create sdf_translate constructor");
+
+			let sdf_smooth_union_ctor = lua_ctx
+				.create_function(sdf::SmoothUnion::lua_new)
+				.expect("Failed to create sdf_smooth_union constructor");
+
+			let sdf_repeat_ctor = lua_ctx
+				.create_function(sdf::Repetition::lua_new)
+				.expect("Failed to create sdf_repeat constructor");
+
+			let sdf_twist_ctor = lua_ctx
+				.create_function(sdf::Twist::lua_new)
+				.expect("Failed to create sdf_twist constructor");
+
+			let sdf_mandelbulb_ctor = lua_ctx
+				.create_function(sdf::Mandelbulb::lua_new)
+				.expect("Failed to create sdf_mandelbulb constructor");
+
+			// Implicit surface constructors
+			let implicit_ctor = lua_ctx
+				.create_function(Implicit::lua_new)
+				.expect("Failed to create implicit constructor");
+
+			let metaballs_ctor = lua_ctx
+				.create_function(implicit::Metaballs::lua_new)
+				.expect("Failed to create metaballs constructor");
+
 			// Mesh Constructor
 			let mesh_ctor = lua_ctx
 				.create_function(Mesh::lua_new)
 				.expect("Failed to create mesh constructor");
 
+			let sample_mesh_fn = lua_ctx
+				.create_function(Mesh::lua_sample)
+				.expect("Failed to create sample_mesh function");
+
+			// Curve Constructor
+			let curve_ctor = lua_ctx
+				.create_function(Curve::lua_new)
+				.expect("Failed to create curve constructor");
+
+			// Bezier Patch Constructor
+			let bezier_ctor = lua_ctx
+				.create_function(BezierPatch::lua_new)
+				.expect("Failed to create bezier constructor");
+
+			// Heightfield Constructor
+			let heightfield_ctor = lua_ctx
+				.create_function(Heightfield::lua_new)
+				.expect("Failed to create heightfield constructor");
+
+			// Instance Constructor
+			let instance_ctor = lua_ctx
+				.create_function(Instance::lua_new)
+				.expect("Failed to create instance constructor");
+
+			let scatter_fn = lua_ctx
+				.create_function(lua::SceneNode::lua_scatter)
+				.expect("Failed to create scatter function");
+
 			// Material Constructor
 			let material_ctor = lua_ctx
 				.create_function(Phong::lua_new)
@@ -99,11 +257,114 @@ impl SceneBuilder
 				.create_function(CookTorrance::lua_new)
 				.expect("Failed to create mesh constructor");
 
+			// Measured Metal Constructor
+			let metal_ctor = lua_ctx
+				.create_function(CookTorrance::lua_metal)
+				.expect("Failed to create metal constructor");
+
+			// Hair Material Constructor
+			let hair_ctor = lua_ctx
+				.create_function(KajiyaKay::lua_new)
+				.expect("Failed to create hair constructor");
+
+			// Surface Detail Constructors
+			let normal_map_ctor = lua_ctx
+				.create_function(SurfaceDetail::lua_normal_map)
+				.expect("Failed to create normal_map constructor");
+
+			let bump_map_ctor = lua_ctx
+				.create_function(SurfaceDetail::lua_bump_map)
+				.expect("Failed to create bump_map constructor");
+
+			// RoughDiffuse Material Constructors
+			let oren_nayar_ctor = lua_ctx
+				.create_function(RoughDiffuse::lua_oren_nayar)
+				.expect("Failed to create oren_nayar constructor");
+			let retroreflective_ctor = lua_ctx
+				.create_function(RoughDiffuse::lua_retroreflective)
+				.expect("Failed to create retroreflective constructor");
+
+			// Layered Material Constructors
+			let mix_material_ctor = lua_ctx
+				.create_function(Mix::lua_new)
+				.expect("Failed to create mix_material constructor");
+			let coated_ctor = lua_ctx
+				.create_function(Coated::lua_new)
+				.expect("Failed to create coated constructor");
+
+			let principled_ctor = lua_ctx
+				.create_function(Principled::lua_new)
+				.expect("Failed to create principled constructor");
+
+			let emissive_ctor = lua_ctx
+				.create_function(Emissive::lua_new)
+				.expect("Failed to create emissive constructor");
+
+			// Texture Constructor
+			let texture_ctor = lua_ctx
+				.create_function(ImageTexture::lua_new)
+				.expect("Failed to create texture constructor");
+
+			// Procedural Texture Constructors
+			let checker_ctor = lua_ctx
+				.create_function(texture::Checker::lua_new)
+				.expect("Failed to create checker constructor");
+
+			let noise_ctor = lua_ctx
+				.create_function(texture::NoiseTexture::lua_new)
+				.expect("Failed to create noise constructor");
+
+			let marble_ctor = lua_ctx
+				.create_function(texture::Pattern::lua_marble)
+				.expect("Failed to create marble constructor");
+
+			let wood_ctor = lua_ctx
+				.create_function(texture::Pattern::lua_wood)
+				.expect("Failed to create wood constructor");
+
+			// Shader Node Constructors
+			let mix_ctor = lua_ctx
+				.create_function(texture::Mix::lua_new)
+				.expect("Failed to create mix constructor");
+
+			let multiply_ctor = lua_ctx
+				.create_function(texture::Multiply::lua_new)
+				.expect("Failed to create multiply constructor");
+
+			let remap_ctor = lua_ctx
+				.create_function(texture::Remap::lua_new)
+				.expect("Failed to create remap constructor");
+
+			let fresnel_ctor = lua_ctx
+				.create_function(texture::Fresnel::lua_new)
+				.expect("Failed to create fresnel constructor");
+
+			let uv_ctor = lua_ctx
+				.create_function(texture::Coordinates::lua_uv)
+				.expect("Failed to create uv constructor");
+
+			let object_position_ctor = lua_ctx
+				.create_function(texture::Coordinates::lua_object_position)
+				.expect("Failed to create object_position constructor");
+
+			let world_normal_ctor = lua_ctx
+				.create_function(texture::Coordinates::lua_world_normal)
+				.expect("Failed to create world_normal constructor");
+
+			let mapping_ctor = lua_ctx
+				.create_function(texture::Mapping::lua_new)
+				.expect("Failed to create mapping constructor");
+
 			// Light Constructor
 			let light_ctor = lua_ctx
 				.create_function(Light::lua_new)
 				.expect("Failed to create light constructor");
 
+			let session = RenderSession {
+				cli_parameters: parameters,
+				video_streams: Arc::new(Mutex::new(HashMap::new())),
+			};
+
 			// Render function
 			let render = lua_ctx
 				.create_function(
@@ -132,7 +393,7 @@ impl SceneBuilder
 							lua_fov_y,
 							lua_ambient,
 							lua_lights,
-							&parameters,
+							&session,
 						)
 					},
 				)
@@ -148,12 +409,112 @@ impl SceneBuilder
 				.expect("Failed to assign Sphere constructor to gr.sphere");
 			gr.set("cube", cube_ctor)
 				.expect("Failed to assign Cube constructor to gr.cube");
+			gr.set("plane", plane_ctor)
+				.expect("Failed to assign Plane constructor to gr.plane");
+			gr.set("disk", disk_ctor)
+				.expect("Failed to assign Disk constructor to gr.disk");
+			gr.set("rect", rect_ctor)
+				.expect("Failed to assign Rectangle constructor to gr.rect");
+			gr.set("cylinder", cylinder_ctor)
+				.expect("Failed to assign Cylinder constructor to gr.cylinder");
+			gr.set("cone", cone_ctor)
+				.expect("Failed to assign Cone constructor to gr.cone");
+			gr.set("capsule", capsule_ctor)
+				.expect("Failed to assign Capsule constructor to gr.capsule");
+			gr.set("torus", torus_ctor)
+				.expect("Failed to assign Torus constructor to gr.torus");
+			gr.set("union", union_ctor)
+				.expect("Failed to assign union constructor to gr.union");
+			gr.set("intersection", intersection_ctor)
+				.expect("Failed to assign intersection constructor to gr.intersection");
+			gr.set("difference", difference_ctor)
+				.expect("Failed to assign difference constructor to gr.difference");
+			gr.set("sdf", sdf_ctor)
+				.expect("Failed to assign sdf constructor to gr.sdf");
+			gr.set("sdf_sphere", sdf_sphere_ctor)
+				.expect("Failed to assign sdf_sphere constructor to gr.sdf_sphere");
+			gr.set("sdf_box", sdf_box_ctor)
+				.expect("Failed to assign sdf_box constructor to gr.sdf_box");
+			gr.set("sdf_torus", sdf_torus_ctor)
+				.expect("Failed to assign sdf_torus constructor to gr.sdf_torus");
+			gr.set("sdf_translate", sdf_translate_ctor)
+				.expect("Failed to assign sdf_translate constructor to gr.sdf_translate");
+			gr.set("sdf_smooth_union", sdf_smooth_union_ctor)
+				.expect("Failed to assign sdf_smooth_union constructor to gr.sdf_smooth_union");
+			gr.set("sdf_repeat", sdf_repeat_ctor)
+				.expect("Failed to assign sdf_repeat constructor to gr.sdf_repeat");
+			gr.set("sdf_twist", sdf_twist_ctor)
+				.expect("Failed to assign sdf_twist constructor to gr.sdf_twist");
+			gr.set("sdf_mandelbulb", sdf_mandelbulb_ctor)
+				.expect("Failed to assign sdf_mandelbulb constructor to gr.sdf_mandelbulb");
+			gr.set("implicit", implicit_ctor)
+				.expect("Failed to assign implicit constructor to gr.implicit");
+			gr.set("metaballs", metaballs_ctor)
+				.expect("Failed to assign metaballs constructor to gr.metaballs");
 			gr.set("mesh", mesh_ctor)
 				.expect("Failed to assign Mesh constructor to gr.mesh");
+			gr.set("sample_mesh", sample_mesh_fn)
+				.expect("Failed to assign sample_mesh function to gr.sample_mesh");
+			gr.set("curve", curve_ctor)
+				.expect("Failed to assign Curve constructor to gr.curve");
+			gr.set("bezier", bezier_ctor)
+				.expect("Failed to assign BezierPatch constructor to gr.bezier");
+			gr.set("heightfield", heightfield_ctor)
+				.expect("Failed to assign Heightfield constructor to gr.heightfield");
+			gr.set("instance", instance_ctor)
+				.expect("Failed to assign Instance constructor to gr.instance");
+			gr.set("scatter", scatter_fn)
+				.expect("Failed to assign scatter function to gr.scatter");
 			gr.set("material", material_ctor)
 				.expect("Failed to assign Material constructor to gr.material");
 			gr.set("cook_torrance", cook_torrance_ctor)
 				.expect("Failed to assign Material constructor to gr.material");
+			gr.set("metal", metal_ctor)
+				.expect("Failed to assign CookTorrance constructor to gr.metal");
+			gr.set("hair", hair_ctor)
+				.expect("Failed to assign KajiyaKay constructor to gr.hair");
+			gr.set("normal_map", normal_map_ctor)
+				.expect("Failed to assign SurfaceDetail constructor to gr.normal_map");
+			gr.set("bump_map", bump_map_ctor)
+				.expect("Failed to assign SurfaceDetail constructor to gr.bump_map");
+			gr.set("oren_nayar", oren_nayar_ctor)
+				.expect("Failed to assign RoughDiffuse constructor to gr.oren_nayar");
+			gr.set("retroreflective", retroreflective_ctor)
+				.expect("Failed to assign RoughDiffuse constructor to gr.retroreflective");
+			gr.set("mix_material", mix_material_ctor)
+				.expect("Failed to assign Mix constructor to gr.mix_material");
+			gr.set("coated", coated_ctor)
+				.expect("Failed to assign Coated constructor to gr.coated");
+			gr.set("principled", principled_ctor)
+				.expect("Failed to assign Principled constructor to gr.principled");
+			gr.set("emissive", emissive_ctor)
+				.expect("Failed to assign Emissive constructor to gr.emissive");
+			gr.set("texture", texture_ctor)
+				.expect("Failed to assign ImageTexture constructor to gr.texture");
+			gr.set("checker", checker_ctor)
+				.expect("Failed to assign Checker constructor to gr.checker");
+			gr.set("noise", noise_ctor)
+				.expect("Failed to assign NoiseTexture constructor to gr.noise");
+			gr.set("marble", marble_ctor)
+				.expect("Failed to assign Pattern constructor to gr.marble");
+			gr.set("wood", wood_ctor)
+				.expect("Failed to assign Pattern constructor to gr.wood");
+			gr.set("mix", mix_ctor)
+				.expect("Failed to assign Mix constructor to gr.mix");
+			gr.set("multiply", multiply_ctor)
+				.expect("Failed to assign Multiply constructor to gr.multiply");
+			gr.set("remap", remap_ctor)
+				.expect("Failed to assign Remap constructor to gr.remap");
+			gr.set("fresnel", fresnel_ctor)
+				.expect("Failed to assign Fresnel constructor to gr.fresnel");
+			gr.set("uv", uv_ctor)
+				.expect("Failed to assign Coordinates constructor to gr.uv");
+			gr.set("object_position", object_position_ctor)
+				.expect("Failed to assign Coordinates constructor to gr.object_position");
+			gr.set("world_normal", world_normal_ctor)
+				.expect("Failed to assign Coordinates constructor to gr.world_normal");
+			gr.set("mapping", mapping_ctor)
+				.expect("Failed to assign Mapping constructor to gr.mapping");
 			gr.set("light", light_ctor)
 				.expect("Failed to assign Light constructor to gr.light");
 			gr.set("render", render)
@@ -179,9 +540,11 @@ impl SceneBuilder
 		lua_fov_y: Value<'lua>,
 		lua_ambient: Value<'lua>,
 		lua_lights: Value<'lua>,
-		cli_parameters: &cli::Parameters,
+		session: &RenderSession,
 	) -> rlua::Result<()>
 	{
+		let cli_parameters = &session.cli_parameters;
+
 		let objects = match lua_scene_root {
 			Value::UserData(user_data) => match user_data.borrow::<lua::SceneNode>() {
 				Ok(root_node) => root_node.convert_to_object_list(),
@@ -232,6 +595,9 @@ impl SceneBuilder
 			vertical_fov: fov_y,
 			ambient_light: na::Vector3::from(ambient),
 			single_pixel: cli_parameters.single_pixel,
+			frame_rate: cli_parameters.frame_rate,
+			video_streams: Arc::clone(&session.video_streams),
+			spectral: cli_parameters.spectral,
 		};
 
 		render::render(render_parameters);
@@ -1,5 +1,6 @@
 use std::cell::Cell;
 use std::clone::Clone;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -7,6 +8,9 @@ use lua;
 use na::Matrix4;
 use na::Unit;
 use na::Vector3;
+use primitives::csg;
+use primitives::Csg;
+use primitives::Instance;
 use primitives::Primitive;
 use rlua;
 use rlua::FromLua;
@@ -14,6 +18,8 @@ use rlua::UserData;
 use rlua::UserDataMethods;
 use rlua::Value;
 use shading::Material;
+use util::scatter;
+use Grid;
 use Object;
 
 pub struct SceneNodeInner
@@ -23,9 +29,30 @@ pub struct SceneNodeInner
 	children: Vec<SceneNode>,
 	primitive: Option<Arc<dyn Primitive>>,
 	material: Option<Arc<dyn Material>>,
+	csg_operation: Option<csg::Operation>,
+	prototype: Option<SceneNode>,
+	// Transforms of each copy of the prototype relative to the node
+	placements: Vec<Matrix4<f32>>,
+	scatter: Option<Scatter>,
 	objects_built: Cell<usize>,
 }
 
+// Copies of the prototype spread over another node's surface. They are placed
+// when the scene is built, once the surface's place in the scene is known.
+struct Scatter
+{
+	surface: SceneNode,
+	options: scatter::Options,
+}
+
+// Grids of the prototypes built so far, keyed by node, along with a material
+// for the objects placing them. Each prototype is built once however many
+// instances of it there are.
+type Prototypes = HashMap<usize, Option<(Arc<Grid>, Arc<dyn Material>)>>;
+
+// Transforms taking each node in the scene to world space, keyed by node
+type WorldTransforms = HashMap<usize, Matrix4<f32>>;
+
 pub struct SceneNode
 {
 	inner: Arc<Mutex<SceneNodeInner>>,
@@ -40,6 +67,10 @@ impl SceneNode
 			children: Vec::new(),
 			primitive: primitive,
 			material: None,
+			csg_operation: None,
+			prototype: None,
+			placements: Vec::new(),
+			scatter: None,
 			objects_built: Cell::new(0),
 		};
 
@@ -48,9 +79,80 @@ impl SceneNode
 		}
 	}
 
+	// The children of a CSG node are combined into a single object rather than
+	// being built as objects of their own
+	pub fn new_csg(name: &str, operation: csg::Operation, children: Vec<SceneNode>) -> Self
+	{
+		let node = SceneNode::new(name, None);
+
+		{
+			let mut inner = node.inner.lock().unwrap();
+			inner.csg_operation = Some(operation);
+			inner.children = children;
+		}
+
+		node
+	}
+
+	// Instances share the objects of their prototype instead of copying them,
+	// transforms and materials set on the prototype itself apply to every copy
+	pub fn new_instance(name: &str, prototype: SceneNode) -> Self
+	{
+		let node = SceneNode::new(name, None);
+
+		{
+			let mut inner = node.inner.lock().unwrap();
+			inner.prototype = Some(prototype);
+			inner.placements = vec![Matrix4::identity()];
+		}
+
+		node
+	}
+
+	// A single node holding many copies of the prototype spread over the
+	// surface, wherever the surface ends up in the scene. Surfaces that aren't
+	// part of the scene are placed as if they were children of its root.
+	pub fn new_scatter(
+		name: &str,
+		prototype: SceneNode,
+		surface: SceneNode,
+		options: scatter::Options,
+	) -> Self
+	{
+		let node = SceneNode::new(name, None);
+
+		{
+			let mut inner = node.inner.lock().unwrap();
+			inner.prototype = Some(prototype);
+			inner.scatter = Some(Scatter {
+				surface: surface,
+				options: options,
+			});
+		}
+
+		node
+	}
+
 	pub fn convert_to_object_list(&self) -> Vec<Arc<Object>>
 	{
-		self.convert_to_object_list_private(Matrix4::identity())
+		let mut world_transforms = HashMap::new();
+		self.find_world_transforms(Matrix4::identity(), &mut world_transforms);
+
+		self.convert_to_object_list_private(
+			Matrix4::identity(),
+			&world_transforms,
+			&mut HashMap::new(),
+		)
+	}
+
+	pub fn get_primitive(&self) -> Option<Arc<dyn Primitive>>
+	{
+		self.inner.lock().unwrap().primitive.clone()
+	}
+
+	pub fn get_transform(&self) -> Matrix4<f32>
+	{
+		self.inner.lock().unwrap().transform
 	}
 
 	pub fn set_material(&mut self, material: Arc<dyn Material>)
@@ -97,19 +199,59 @@ impl SceneNode
 		node.children.push(child);
 	}
 
-	fn convert_to_object_list_private(&self, transform: Matrix4<f32>) -> Vec<Arc<Object>>
+	fn key(&self) -> usize
+	{
+		Arc::as_ptr(&self.inner) as usize
+	}
+
+	// Nodes added in more than one place keep the first transform found
+	fn find_world_transforms(&self, transform: Matrix4<f32>, world_transforms: &mut WorldTransforms)
+	{
+		let node = self.inner.lock().unwrap();
+		let cumulative_transform = transform * node.transform;
+
+		world_transforms
+			.entry(self.key())
+			.or_insert(cumulative_transform);
+
+		for child in &node.children {
+			child.find_world_transforms(cumulative_transform, world_transforms);
+		}
+	}
+
+	fn convert_to_object_list_private(
+		&self,
+		transform: Matrix4<f32>,
+		world_transforms: &WorldTransforms,
+		prototypes: &mut Prototypes,
+	) -> Vec<Arc<Object>>
 	{
 		let node = self.inner.lock().unwrap();
 
 		let mut list = Vec::new();
 		let cumulative_transform = transform * node.transform;
 
-		if let Some(object) = self.build_object_with_transform(&node, cumulative_transform) {
+		if node.prototype.is_some() {
+			list.append(&mut SceneNode::build_instances(
+				&node,
+				cumulative_transform,
+				world_transforms,
+				prototypes,
+			));
+		} else if let Some(object) = self.build_object_with_transform(&node, cumulative_transform) {
 			list.push(Arc::new(object));
 		}
 
+		if node.csg_operation.is_some() {
+			return list;
+		}
+
 		for child in &node.children {
-			let mut objects = child.convert_to_object_list_private(cumulative_transform);
+			let mut objects = child.convert_to_object_list_private(
+				cumulative_transform,
+				world_transforms,
+				prototypes,
+			);
 			list.append(&mut objects);
 		}
 
@@ -122,6 +264,26 @@ impl SceneNode
 		transform: Matrix4<f32>,
 	) -> Option<Object>
 	{
+		if let Some(operation) = node.csg_operation {
+			let csg = SceneNode::build_csg(operation, &node.children);
+
+			// CSG nodes without a material fall back to the first operand material
+			let material = node.material.clone().or_else(|| {
+				csg.get_operands()
+					.iter()
+					.find_map(|operand| operand.get_material().cloned())
+			});
+
+			if let Some(material) = material {
+				let object_number = node.objects_built.replace(node.objects_built.get() + 1);
+				let object_name = format!("<{}>:{}", node.name, object_number);
+
+				return Some(Object::new(object_name, transform, Arc::new(csg), material));
+			}
+
+			return None;
+		}
+
 		if let Some(primitive) = node.primitive.clone() {
 			if let Some(material) = node.material.clone() {
 				let object_number = node.objects_built.replace(node.objects_built.get() + 1);
@@ -133,6 +295,130 @@ impl SceneNode
 
 		None
 	}
+
+	// Objects in the prototype keep their own materials, the instance objects
+	// borrow one of them since they never shade anything themselves
+	fn build_instances(
+		node: &SceneNodeInner,
+		transform: Matrix4<f32>,
+		world_transforms: &WorldTransforms,
+		prototypes: &mut Prototypes,
+	) -> Vec<Arc<Object>>
+	{
+		let prototype = node
+			.prototype
+			.as_ref()
+			.and_then(|prototype| prototype.build_prototype(world_transforms, prototypes));
+
+		let (grid, material) = match prototype {
+			Some(prototype) => prototype,
+			None => return Vec::new(),
+		};
+
+		let instance: Arc<dyn Primitive> = Arc::new(Instance::new(grid));
+
+		let placements = match &node.scatter {
+			Some(scatter) => SceneNode::place_scatter(scatter, transform, world_transforms),
+			None => node.placements.clone(),
+		};
+
+		placements
+			.iter()
+			.map(|placement| {
+				let object_number = node.objects_built.replace(node.objects_built.get() + 1);
+				let object_name = format!("<{}>:{}", node.name, object_number);
+
+				Arc::new(Object::new(
+					object_name,
+					transform * placement,
+					Arc::clone(&instance),
+					Arc::clone(&material),
+				))
+			})
+			.collect()
+	}
+
+	// Placements relative to the scatter node, which is placed by transform
+	fn place_scatter(
+		scatter: &Scatter,
+		transform: Matrix4<f32>,
+		world_transforms: &WorldTransforms,
+	) -> Vec<Matrix4<f32>>
+	{
+		let surface_transform = world_transforms
+			.get(&scatter.surface.key())
+			.cloned()
+			.unwrap_or_else(|| scatter.surface.get_transform());
+
+		let placements = transform.try_inverse().and_then(|to_node| {
+			scatter.surface.get_primitive().and_then(|primitive| {
+				scatter::scatter(
+					primitive.as_ref(),
+					to_node * surface_transform,
+					&scatter.options,
+				)
+			})
+		});
+
+		placements.unwrap_or_default()
+	}
+
+	fn build_prototype(
+		&self,
+		world_transforms: &WorldTransforms,
+		prototypes: &mut Prototypes,
+	) -> Option<(Arc<Grid>, Arc<dyn Material>)>
+	{
+		let key = self.key();
+
+		if let Some(prototype) = prototypes.get(&key) {
+			return prototype.clone();
+		}
+
+		let objects =
+			self.convert_to_object_list_private(Matrix4::identity(), world_transforms, prototypes);
+
+		let prototype = objects
+			.first()
+			.map(|object| object.get_material().clone())
+			.map(|material| (Arc::new(Grid::new(objects)), material));
+
+		prototypes.insert(key, prototype.clone());
+
+		prototype
+	}
+
+	fn build_csg(operation: csg::Operation, children: &[SceneNode]) -> Csg
+	{
+		let operands = children
+			.iter()
+			.filter_map(|child| {
+				let child = child.inner.lock().unwrap();
+
+				// Plain group nodes inside a CSG node act as the union of their children
+				let primitive: Arc<dyn Primitive> = match child.csg_operation {
+					Some(child_operation) => {
+						Arc::new(SceneNode::build_csg(child_operation, &child.children))
+					},
+					None => match child.primitive.clone() {
+						Some(primitive) => primitive,
+						None if !child.children.is_empty() => {
+							Arc::new(SceneNode::build_csg(csg::Operation::Union, &child.children))
+						},
+						None => return None,
+					},
+				};
+
+				Some(csg::Operand::new(
+					primitive,
+					child.transform,
+					child.material.clone(),
+				))
+			})
+			.collect();
+
+		Csg::new(operation, operands)
+	}
 }
 
 impl Clone for SceneNode
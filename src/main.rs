@@ -3,27 +3,33 @@ extern crate failure;
 extern crate image;
 extern crate nalgebra as na;
 extern crate num_cpus;
+extern crate png;
 extern crate rand;
 extern crate rlua;
 extern crate thread_local;
 
+pub use self::area_light::AreaLight;
 pub use self::grid::Grid;
 pub use self::light::Light;
 pub use self::object::Object;
 pub use self::ray::Hit;
+pub use self::ray::Interval;
 pub use self::ray::Ray;
 pub use self::scene::Scene;
 
+pub mod area_light;
 pub mod cli;
 pub mod grid;
 pub mod light;
 pub mod lua;
 pub mod object;
+pub mod output;
 pub mod primitives;
 pub mod ray;
 pub mod render;
 pub mod scene;
 pub mod shading;
+pub mod spectrum;
 pub mod util;
 
 use lua::SceneBuilder;
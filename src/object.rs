@@ -55,20 +55,54 @@ impl Object
 		&self.bounding_box
 	}
 
+	pub fn get_primitive(&self) -> &Arc<dyn Primitive>
+	{
+		&self.primitive
+	}
+
+	pub fn get_material(&self) -> &Arc<dyn Material>
+	{
+		&self.material
+	}
+
 	pub fn get_transform(&self) -> Matrix4<f32>
 	{
 		self.transform
 	}
 
+	pub fn is_bounded(&self) -> bool
+	{
+		self.primitive.is_bounded()
+	}
+
 	pub fn check_hit(&self, ray: &Ray) -> Option<(Hit, &dyn Material)>
 	{
 		if self.ray_previously_visited(ray) {
 			return None;
 		}
 
-		if self.bounding_box.hit(ray, self.transform) {
-			if let Some(hit) = self.primitive.hit(ray, self.transform) {
-				Some((hit, self.material.borrow()))
+		if !self.is_bounded() || self.bounding_box.hit(ray, self.transform) {
+			if let Some((hit, material)) = self.primitive.hit_with_material(ray, self.transform) {
+				// Instances report points in the space of the prototype's objects
+				let local_point = hit.local_point.unwrap_or_else(|| {
+					let origin = self.transform * ray.origin();
+
+					origin + hit.intersect * (self.transform * ray.point() - origin)
+				});
+				let local_tangents = match hit.local_point {
+					Some(_) => hit.local_tangents,
+					None => hit
+						.tangents
+						.map(|(dpdu, dpdv)| (self.transform * dpdu, self.transform * dpdv)),
+				};
+
+				let hit = Hit {
+					local_point: Some(local_point),
+					local_tangents: local_tangents,
+					..hit
+				};
+
+				Some((hit, material.unwrap_or_else(|| self.material.borrow())))
 			} else {
 				None
 			}
@@ -0,0 +1,214 @@
+use std::fmt;
+use std::io;
+use std::io::Write;
+
+pub const FILE_EXTENSION: &str = "y4m";
+
+#[derive(fmt::Debug)]
+pub enum Error
+{
+	ResolutionMismatch
+	{
+		expected: (u32, u32),
+		found: (u32, u32),
+	},
+	IOError(io::Error),
+}
+
+impl fmt::Display for Error
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error>
+	{
+		match self {
+			Self::ResolutionMismatch { expected, found } => write!(
+				f,
+				"frame resolution {}x{} does not match stream resolution {}x{}",
+				found.0, found.1, expected.0, expected.1
+			),
+			Self::IOError(error) => write!(f, "{}", error),
+		}
+	}
+}
+
+impl From<io::Error> for Error
+{
+	fn from(error: io::Error) -> Self
+	{
+		Self::IOError(error)
+	}
+}
+
+// Writes a sequence of RGB frames as an uncompressed YUV4MPEG2 stream
+// using full range BT.601 colour with 4:2:0 chroma subsampling
+pub struct Encoder<W: Write>
+{
+	writer: W,
+	width: u32,
+	height: u32,
+	frames_written: usize,
+}
+
+impl<W: Write> Encoder<W>
+{
+	pub fn new(
+		mut writer: W,
+		width: u32,
+		height: u32,
+		frame_rate: (u32, u32),
+	) -> Result<Self, Error>
+	{
+		writeln!(
+			writer,
+			"YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C420jpeg XCOLORRANGE=FULL",
+			width, height, frame_rate.0, frame_rate.1
+		)?;
+
+		Ok(Encoder {
+			writer: writer,
+			width: width,
+			height: height,
+			frames_written: 0,
+		})
+	}
+
+	pub fn frames_written(&self) -> usize
+	{
+		self.frames_written
+	}
+
+	pub fn encode_frame(&mut self, rgb: &[u8], width: u32, height: u32) -> Result<(), Error>
+	{
+		if (width, height) != (self.width, self.height) {
+			return Err(Error::ResolutionMismatch {
+				expected: (self.width, self.height),
+				found: (width, height),
+			});
+		}
+
+		let (y, u, v) = rgb_to_yuv420(rgb, width as usize, height as usize);
+
+		self.writer.write_all(b"FRAME\n")?;
+		self.writer.write_all(&y)?;
+		self.writer.write_all(&u)?;
+		self.writer.write_all(&v)?;
+
+		// Flush every frame so an interrupted render still leaves a playable file
+		self.writer.flush()?;
+
+		self.frames_written += 1;
+
+		Ok(())
+	}
+}
+
+pub fn rgb_to_yuv420(rgb: &[u8], width: usize, height: usize) -> (Vec<u8>, Vec<u8>, Vec<u8>)
+{
+	let chroma_width = width.div_ceil(2);
+	let chroma_height = height.div_ceil(2);
+
+	let mut y_plane = Vec::with_capacity(width * height);
+	let mut u_plane = Vec::with_capacity(chroma_width * chroma_height);
+	let mut v_plane = Vec::with_capacity(chroma_width * chroma_height);
+
+	let pixel_at = |x: usize, y: usize| -> (f32, f32, f32) {
+		let offset = (y * width + x) * 3;
+		(
+			rgb[offset] as f32,
+			rgb[offset + 1] as f32,
+			rgb[offset + 2] as f32,
+		)
+	};
+
+	for y in 0..height {
+		for x in 0..width {
+			let (r, g, b) = pixel_at(x, y);
+			y_plane.push(clamp_to_u8(0.299 * r + 0.587 * g + 0.114 * b));
+		}
+	}
+
+	// Each chroma sample is the average of the (up to) four pixels it covers,
+	// odd dimensions leave the last row/column covering a single pixel
+	for cy in 0..chroma_height {
+		for cx in 0..chroma_width {
+			let mut u_sum = 0.0;
+			let mut v_sum = 0.0;
+			let mut samples = 0.0;
+
+			for y in (cy * 2)..usize::min(cy * 2 + 2, height) {
+				for x in (cx * 2)..usize::min(cx * 2 + 2, width) {
+					let (r, g, b) = pixel_at(x, y);
+
+					u_sum += 128.0 - 0.168_736 * r - 0.331_264 * g + 0.5 * b;
+					v_sum += 128.0 + 0.5 * r - 0.418_688 * g - 0.081_312 * b;
+					samples += 1.0;
+				}
+			}
+
+			u_plane.push(clamp_to_u8(u_sum / samples));
+			v_plane.push(clamp_to_u8(v_sum / samples));
+		}
+	}
+
+	(y_plane, u_plane, v_plane)
+}
+
+fn clamp_to_u8(value: f32) -> u8
+{
+	value.round().clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn yuv420_plane_sizes_round_up()
+	{
+		let rgb = vec![0; 3 * 5 * 3];
+		let (y, u, v) = rgb_to_yuv420(&rgb, 5, 3);
+
+		assert_eq!(y.len(), 15);
+		assert_eq!(u.len(), 6);
+		assert_eq!(v.len(), 6);
+	}
+
+	#[test]
+	fn yuv420_primary_colours()
+	{
+		let white = [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255];
+		let (y, u, v) = rgb_to_yuv420(&white, 2, 2);
+
+		assert_eq!(y, vec![255, 255, 255, 255]);
+		assert_eq!(u, vec![128]);
+		assert_eq!(v, vec![128]);
+
+		let red = [255, 0, 0, 255, 0, 0, 255, 0, 0, 255, 0, 0];
+		let (y, u, v) = rgb_to_yuv420(&red, 2, 2);
+
+		assert_eq!(y, vec![76, 76, 76, 76]);
+		assert_eq!(u, vec![85]);
+		assert_eq!(v, vec![255]);
+	}
+
+	#[test]
+	fn encoder_writes_header_and_frames()
+	{
+		let mut buffer = Vec::new();
+
+		{
+			let mut encoder = Encoder::new(&mut buffer, 2, 2, (24, 1)).unwrap();
+			encoder.encode_frame(&[0; 12], 2, 2).unwrap();
+			encoder.encode_frame(&[0; 12], 2, 2).unwrap();
+
+			assert!(encoder.encode_frame(&[0; 27], 3, 3).is_err());
+			assert_eq!(encoder.frames_written(), 2);
+		}
+
+		let header = b"YUV4MPEG2 W2 H2 F24:1 Ip A1:1 C420jpeg XCOLORRANGE=FULL\n";
+		let frame_size = b"FRAME\n".len() + 4 + 1 + 1;
+
+		assert!(buffer.starts_with(header));
+		assert_eq!(buffer.len(), header.len() + 2 * frame_size);
+	}
+}
@@ -0,0 +1,182 @@
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+
+use na::Vector3;
+
+pub const FILE_EXTENSION: &str = "bpt";
+
+#[derive(fmt::Debug)]
+pub enum Error
+{
+	ParseError
+	{
+		file_name: String,
+		line_number: usize,
+		message: String,
+	},
+	IOError
+	{
+		file_name: String,
+		error: io::Error,
+	},
+}
+
+impl fmt::Display for Error
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error>
+	{
+		match self {
+			Self::ParseError {
+				file_name,
+				line_number,
+				message,
+			} => write!(f, "{}:{} error: {}", file_name, line_number, message),
+			Self::IOError { file_name, error } => {
+				write!(f, "Failed to read {}: {}", file_name, error)
+			},
+		}
+	}
+}
+
+impl error::Error for Error {}
+
+impl Error
+{
+	fn parse_error(file_name: &str, line_number: usize, message: String) -> Self
+	{
+		Self::ParseError {
+			file_name: String::from(file_name),
+			line_number: line_number,
+			message: message,
+		}
+	}
+
+	fn io_error(file_name: &str, error: io::Error) -> Self
+	{
+		Self::IOError {
+			file_name: String::from(file_name),
+			error: error,
+		}
+	}
+}
+
+// Reads the control points of every patch in a .bpt file. The file starts
+// with the number of patches, and each patch is its degree in u and v
+// followed by its control points with u varying fastest. Only bicubic
+// patches are supported.
+pub fn read_file(path: &str) -> Result<Vec<[Vector3<f32>; 16]>, Error>
+{
+	let bpt_file = File::open(path).map_err(|error| Error::io_error(path, error))?;
+
+	// Blank lines and comments carry no data, so only the others are kept
+	// along with their line numbers for error messages
+	let mut lines = Vec::new();
+	for (index, line) in BufReader::new(bpt_file).lines().enumerate() {
+		let line = line.map_err(|error| Error::io_error(path, error))?;
+		let line = line.trim();
+
+		if !line.is_empty() && !line.starts_with('#') {
+			lines.push((index + 1, String::from(line)));
+		}
+	}
+
+	let mut lines = lines.into_iter();
+	let mut next_line = |expected: &str| {
+		lines.next().ok_or_else(|| {
+			Error::parse_error(
+				path,
+				0,
+				format!("Unexpected end of file, expected {}", expected),
+			)
+		})
+	};
+
+	let (line_number, line) = next_line("the number of patches")?;
+	let patch_count = line.parse::<usize>().map_err(|_| {
+		Error::parse_error(
+			path,
+			line_number,
+			format!("Invalid patch count \"{}\"", line),
+		)
+	})?;
+
+	// The count isn't trusted to size anything, a short file runs out of lines
+	let mut patches = Vec::new();
+
+	for _ in 0..patch_count {
+		let (line_number, line) = next_line("the degree of a patch")?;
+		let degrees: Vec<&str> = line.split_whitespace().collect();
+
+		if degrees != ["3", "3"] {
+			return Err(Error::parse_error(
+				path,
+				line_number,
+				format!(
+					"Only bicubic patches are supported, found degree \"{}\"",
+					line
+				),
+			));
+		}
+
+		let mut control_points = [Vector3::zeros(); 16];
+
+		for control_point in control_points.iter_mut() {
+			let (line_number, line) = next_line("a control point")?;
+			*control_point = parse_point(&line)
+				.map_err(|message| Error::parse_error(path, line_number, message))?;
+		}
+
+		patches.push(control_points);
+	}
+
+	Ok(patches)
+}
+
+fn parse_point(line: &str) -> Result<Vector3<f32>, String>
+{
+	let parts: Vec<&str> = line.split_whitespace().collect();
+
+	if parts.len() != 3 {
+		return Err(format!("Expected 3 coordinates, found {}", parts.len()));
+	}
+
+	let mut point = Vector3::zeros();
+
+	for (component, part) in point.iter_mut().zip(parts) {
+		*component = part
+			.parse::<f32>()
+			.map_err(|_| format!("Failed to parse control point data \"{}\"", part))?;
+	}
+
+	Ok(point)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::env;
+	use std::fs;
+
+	use super::*;
+
+	#[test]
+	fn counts_past_the_end_of_the_file_are_parse_errors()
+	{
+		let path = env::temp_dir().join("aurora_bpt_count_past_end.bpt");
+		fs::write(&path, format!("{}\n3 3\n0 0 0\n", usize::MAX)).unwrap();
+
+		let result = read_file(path.to_str().unwrap());
+		fs::remove_file(&path).unwrap();
+
+		match result {
+			Err(Error::ParseError { message, .. }) => {
+				assert!(message.contains("Unexpected end of file"), "{}", message)
+			},
+			_ => panic!("expected a parse error"),
+		}
+	}
+}
@@ -0,0 +1,335 @@
+mod file;
+
+use std::error;
+use std::f32;
+use std::fmt;
+use std::path::Path;
+
+use na::Matrix4;
+use na::Vector3;
+use na::Vector4;
+use primitives::primitive;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+use self::file::bpt;
+
+#[derive(fmt::Debug)]
+pub enum Error
+{
+	FileTypeError(String),
+	FileReadError(Box<dyn error::Error + Send + Sync>),
+}
+
+impl Error
+{
+	fn read_error<E: error::Error + Send + Sync + 'static>(err: E) -> Self
+	{
+		Self::FileReadError(Box::new(err))
+	}
+}
+
+impl fmt::Display for Error
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error>
+	{
+		match self {
+			Self::FileReadError(err) => err.fmt(f),
+			Self::FileTypeError(msg) => write!(f, "{}", msg),
+		}
+	}
+}
+
+impl error::Error for Error {}
+
+// Bicubic Bezier patches sharing one object, such as the pieces of a model
+// loaded from a .bpt file. Rays are intersected with a tessellation of each
+// patch made when it is loaded, but normals, tangents and UVs come from the
+// exact surface at the parameters of the hit.
+#[derive(fmt::Debug)]
+pub struct BezierPatch
+{
+	patches: Vec<Patch>,
+}
+
+#[derive(fmt::Debug)]
+struct Patch
+{
+	control_points: [Vector3<f32>; 16],
+	rate: usize,
+	vertices: Vec<Vector3<f32>>,
+	lower: Vector3<f32>,
+	upper: Vector3<f32>,
+}
+
+impl BezierPatch
+{
+	// Control points of each patch are given with u varying fastest, every
+	// patch is split into rate x rate quads for intersection
+	pub fn new(patches: Vec<[Vector3<f32>; 16]>, rate: usize) -> Self
+	{
+		BezierPatch {
+			patches: patches
+				.into_iter()
+				.map(|control_points| Patch::new(control_points, rate.max(1)))
+				.collect(),
+		}
+	}
+
+	pub fn from_file(file_name: &str, rate: usize) -> Result<Self, Error>
+	{
+		let file_extension = Path::new(file_name)
+			.extension()
+			.map(|extension| extension.to_string_lossy().into_owned());
+
+		match file_extension.as_deref() {
+			Some(bpt::FILE_EXTENSION) => {
+				let patches = bpt::read_file(file_name).map_err(Error::read_error)?;
+
+				Ok(BezierPatch::new(patches, rate))
+			},
+			_ => Err(Error::FileTypeError(format!(
+				"Failed to read {}: unknown patch file type",
+				file_name
+			))),
+		}
+	}
+}
+
+impl Patch
+{
+	fn new(control_points: [Vector3<f32>; 16], rate: usize) -> Self
+	{
+		let mut patch = Patch {
+			control_points: control_points,
+			rate: rate,
+			vertices: Vec::with_capacity((rate + 1) * (rate + 1)),
+			lower: Vector3::repeat(f32::INFINITY),
+			upper: Vector3::repeat(f32::NEG_INFINITY),
+		};
+
+		for j in 0..=rate {
+			for i in 0..=rate {
+				let (point, _, _) = patch.evaluate(i as f32 / rate as f32, j as f32 / rate as f32);
+
+				patch.lower = patch.lower.inf(&point);
+				patch.upper = patch.upper.sup(&point);
+				patch.vertices.push(point);
+			}
+		}
+
+		patch
+	}
+
+	// Position on the patch and its derivatives with respect to u and v
+	fn evaluate(&self, u: f32, v: f32) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>)
+	{
+		let (basis_u, derivative_u) = bernstein(u);
+		let (basis_v, derivative_v) = bernstein(v);
+
+		let mut point = Vector3::zeros();
+		let mut dpdu = Vector3::zeros();
+		let mut dpdv = Vector3::zeros();
+
+		for j in 0..4 {
+			for i in 0..4 {
+				let control_point = self.control_points[j * 4 + i];
+
+				point += control_point * (basis_u[i] * basis_v[j]);
+				dpdu += control_point * (derivative_u[i] * basis_v[j]);
+				dpdv += control_point * (basis_u[i] * derivative_v[j]);
+			}
+		}
+
+		(point, dpdu, dpdv)
+	}
+
+	// Nearest hit on the tessellation in front of the ray as the intersect
+	// and the patch parameters at that point
+	fn hit(&self, origin: Vector3<f32>, direction: Vector3<f32>) -> Option<(f32, f32, f32)>
+	{
+		math::ray_box_intersection(origin, direction, self.lower, self.upper)?;
+
+		let row = self.rate + 1;
+		let step = 1.0 / self.rate as f32;
+		let mut closest: Option<(f32, f32, f32)> = None;
+
+		for j in 0..self.rate {
+			for i in 0..self.rate {
+				// Each quad is split into two triangles, given as their corners
+				// in the vertex grid
+				let quads = [
+					[(i, j), (i + 1, j), (i + 1, j + 1)],
+					[(i, j), (i + 1, j + 1), (i, j + 1)],
+				];
+
+				for corners in quads.iter() {
+					let vertex = |(x, y): (usize, usize)| self.vertices[y * row + x];
+
+					let (t, b1, b2) = match math::ray_triangle_intersection(
+						origin,
+						direction,
+						vertex(corners[0]),
+						vertex(corners[1]),
+						vertex(corners[2]),
+					) {
+						Some(hit) => hit,
+						None => continue,
+					};
+
+					if !math::far_from_zero_pos(t)
+						|| closest.is_some_and(|(closest_t, _, _)| t >= closest_t)
+					{
+						continue;
+					}
+
+					let parameter = |index: usize| {
+						let (a, b, c) = (corners[0], corners[1], corners[2]);
+						let coordinate = |corner: (usize, usize)| {
+							let coordinate = if index == 0 { corner.0 } else { corner.1 };
+
+							coordinate as f32
+						};
+
+						(coordinate(a) * (1.0 - b1 - b2) + coordinate(b) * b1 + coordinate(c) * b2)
+							* step
+					};
+
+					closest = Some((t, parameter(0), parameter(1)));
+				}
+			}
+		}
+
+		closest
+	}
+
+	// Exact surface derivatives at the given parameters. Where one vanishes,
+	// like at the collapsed edge of a patch closing off a pole, they are taken
+	// from a point nudged towards the middle of the patch instead.
+	fn derivatives(&self, u: f32, v: f32) -> (Vector3<f32>, Vector3<f32>)
+	{
+		const NUDGE: f32 = 0.001;
+
+		let (_, dpdu, dpdv) = self.evaluate(u, v);
+
+		if dpdu.cross(&dpdv).norm_squared() > 0.0 {
+			return (dpdu, dpdv);
+		}
+
+		let (_, dpdu, dpdv) = self.evaluate(u + (0.5 - u) * NUDGE, v + (0.5 - v) * NUDGE);
+
+		(dpdu, dpdv)
+	}
+}
+
+// Cubic Bernstein polynomials and their derivatives at t
+fn bernstein(t: f32) -> ([f32; 4], [f32; 4])
+{
+	let s = 1.0 - t;
+
+	(
+		[s * s * s, 3.0 * t * s * s, 3.0 * t * t * s, t * t * t],
+		[
+			-3.0 * s * s,
+			3.0 * s * s - 6.0 * t * s,
+			6.0 * t * s - 3.0 * t * t,
+			3.0 * t * t,
+		],
+	)
+}
+
+impl Primitive for BezierPatch
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		let (intersect, patch, u, v) = self
+			.patches
+			.iter()
+			.filter_map(|patch| {
+				patch
+					.hit(origin.xyz(), direction.xyz())
+					.map(|(t, u, v)| (t, patch, u, v))
+			})
+			.min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())?;
+
+		let (dpdu, dpdv) = patch.derivatives(u, v);
+
+		let mut normal = dpdu.cross(&dpdv).normalize().insert_row(3, 0.0);
+		if normal.dot(&direction) > 0.0 {
+			normal = -normal;
+		}
+
+		Some(Hit::new(
+			intersect,
+			math::transform_normals(normal, transform),
+			(u, v),
+			primitive::tangents_to_world(
+				Some((dpdu.insert_row(3, 0.0), dpdv.insert_row(3, 0.0))),
+				transform,
+			),
+		))
+	}
+
+	// Patches are treated as open surfaces, nothing guarantees a set of
+	// patches encloses a volume
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		primitive::flat_interval(self.hit(ray, transform))
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let (lower, upper) = self.patches.iter().fold(
+			(
+				Vector3::repeat(f32::INFINITY),
+				Vector3::repeat(f32::NEG_INFINITY),
+			),
+			|(lower, upper), patch| (lower.inf(&patch.lower), upper.sup(&patch.upper)),
+		);
+
+		(lower.insert_row(3, 1.0), upper.insert_row(3, 1.0))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn hits_curved_patch_with_exact_normal()
+	{
+		// Patch bulging up along u with a parabolic profile z = 1 - (2u - 1)^2
+		let mut control_points = [Vector3::zeros(); 16];
+		for j in 0..4 {
+			for i in 0..4 {
+				let height = if i == 1 || i == 2 { 4.0 / 3.0 } else { 0.0 };
+				control_points[j * 4 + i] = Vector3::new(i as f32 / 3.0, j as f32 / 3.0, height);
+			}
+		}
+
+		let patch = BezierPatch::new(vec![control_points], 16);
+
+		let ray = Ray::new(
+			Vector4::new(0.25, 0.5, 5.0, 1.0),
+			Vector4::new(0.25, 0.5, 4.0, 1.0),
+		);
+		let hit = patch.hit(&ray, Matrix4::identity()).unwrap();
+
+		assert!((hit.intersect - 4.25).abs() < 0.01);
+		assert!((hit.uv.0 - 0.25).abs() < 0.01);
+
+		// The slope dz/dx = -4(2x - 1) is 2 at x = 0.25
+		let expected_normal = Vector4::new(-2.0, 0.0, 1.0, 0.0).normalize();
+		assert!((hit.normal.normalize() - expected_normal).norm() < 0.02);
+
+		let (dpdu, _) = hit.tangents.unwrap();
+		assert!((dpdu.normalize() - Vector4::new(1.0, 0.0, 2.0, 0.0).normalize()).norm() < 0.02);
+	}
+}
@@ -0,0 +1,122 @@
+use std::f32;
+use std::fmt;
+
+use na::Matrix4;
+use na::Vector4;
+use primitives::cylinder;
+use primitives::primitive;
+use primitives::primitive::Crossing;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+// Capsule around the Y axis centred on the origin, the height is the length of
+// the cylindrical section between the centres of the two hemispherical ends
+#[derive(fmt::Debug)]
+pub struct Capsule
+{
+	radius: f32,
+	height: f32,
+}
+
+impl Capsule
+{
+	pub fn new(radius: f32, height: f32) -> Self
+	{
+		Capsule {
+			radius: radius,
+			height: height,
+		}
+	}
+
+	fn v_coordinate(&self, y: f32) -> f32
+	{
+		let total_height = self.height + 2.0 * self.radius;
+
+		(y + total_height / 2.0) / total_height
+	}
+
+	// Every crossing of the surface along the ray, including those behind the origin
+	fn candidates(&self, origin: Vector4<f32>, direction: Vector4<f32>) -> Vec<Crossing>
+	{
+		let half_height = self.height / 2.0;
+		let mut candidates = Vec::with_capacity(6);
+
+		for t in cylinder::side_intersections(origin, direction, self.radius) {
+			let contact_point = origin + (t * direction);
+
+			if contact_point.y.abs() <= half_height {
+				candidates.push((
+					t,
+					Vector4::new(contact_point.x, 0.0, contact_point.z, 0.0),
+					(
+						cylinder::angle_around_y(contact_point),
+						self.v_coordinate(contact_point.y),
+					),
+					None,
+				));
+			}
+		}
+
+		// Each end only keeps the hemisphere beyond the cylindrical section
+		for &centre_y in [half_height, -half_height].iter() {
+			let centre = Vector4::new(0.0, centre_y, 0.0, 1.0);
+			let oc = origin - centre;
+
+			let a = direction.dot(&direction);
+			let b = 2.0 * direction.dot(&oc);
+			let c = oc.dot(&oc) - self.radius * self.radius;
+
+			if let math::QuadRoots::Two(root_one, root_two) = math::quadratic_roots(a, b, c) {
+				for &t in [root_one, root_two].iter() {
+					let contact_point = origin + (t * direction);
+
+					if (contact_point.y - centre_y) * centre_y.signum() >= 0.0 {
+						candidates.push((
+							t,
+							contact_point - centre,
+							(
+								cylinder::angle_around_y(contact_point),
+								self.v_coordinate(contact_point.y),
+							),
+							None,
+						));
+					}
+				}
+			}
+		}
+
+		candidates
+	}
+}
+
+impl Primitive for Capsule
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::closest_candidate(self.candidates(origin, direction), direction, transform)
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::candidate_intervals(self.candidates(origin, direction), transform)
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let half_extent = self.height / 2.0 + self.radius;
+
+		(
+			Vector4::new(-self.radius, -half_extent, -self.radius, 1.0),
+			Vector4::new(self.radius, half_extent, self.radius, 1.0),
+		)
+	}
+}
@@ -0,0 +1,128 @@
+use std::f32;
+use std::fmt;
+
+use na::Matrix4;
+use na::Vector4;
+use primitives::cylinder;
+use primitives::primitive;
+use primitives::primitive::Crossing;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+// Cone around the Y axis centred on the origin, with its apex at the top and a
+// base of the given radius at the bottom
+#[derive(fmt::Debug)]
+pub struct Cone
+{
+	radius: f32,
+	height: f32,
+	capped: bool,
+}
+
+impl Cone
+{
+	pub fn new(radius: f32, height: f32, capped: bool) -> Self
+	{
+		Cone {
+			radius: radius,
+			height: height,
+			capped: capped,
+		}
+	}
+
+	// Every crossing of the surface along the ray, including those behind the origin
+	fn candidates(&self, origin: Vector4<f32>, direction: Vector4<f32>) -> Vec<Crossing>
+	{
+		let half_height = self.height / 2.0;
+
+		// The radius at height y is k * (apex - y)
+		let k = self.radius / self.height;
+		let k2 = k * k;
+		let apex_offset = half_height - origin.y;
+
+		let a =
+			direction.x * direction.x + direction.z * direction.z - k2 * direction.y * direction.y;
+		let b = 2.0
+			* (origin.x * direction.x + origin.z * direction.z + k2 * apex_offset * direction.y);
+		let c = origin.x * origin.x + origin.z * origin.z - k2 * apex_offset * apex_offset;
+
+		let roots = match math::quadratic_roots(a, b, c) {
+			math::QuadRoots::Two(root_one, root_two) => vec![root_one, root_two],
+			math::QuadRoots::One(root) => vec![root],
+			math::QuadRoots::Zero => Vec::new(),
+		};
+
+		let mut candidates = Vec::with_capacity(3);
+
+		for t in roots {
+			let contact_point = origin + (t * direction);
+
+			// Discard the mirrored cone above the apex and anything below the base
+			if contact_point.y.abs() <= half_height {
+				let radius_at_point = f32::sqrt(
+					contact_point.x * contact_point.x + contact_point.z * contact_point.z,
+				);
+
+				// The slant runs out from the axis as V falls
+				let outward = Vector4::new(contact_point.x, 0.0, contact_point.z, 0.0)
+					/ radius_at_point.max(f32::EPSILON);
+
+				candidates.push((
+					t,
+					Vector4::new(contact_point.x, k * radius_at_point, contact_point.z, 0.0),
+					(
+						cylinder::angle_around_y(contact_point),
+						(contact_point.y + half_height) / self.height,
+					),
+					Some((
+						cylinder::tangent_around_y(contact_point),
+						(Vector4::new(0.0, 1.0, 0.0, 0.0) - outward * k) * self.height,
+					)),
+				));
+			}
+		}
+
+		if self.capped {
+			candidates.extend(cylinder::cap_intersection(
+				origin,
+				direction,
+				self.radius,
+				-half_height,
+			));
+		}
+
+		candidates
+	}
+}
+
+impl Primitive for Cone
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::closest_candidate(self.candidates(origin, direction), direction, transform)
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::candidate_intervals(self.candidates(origin, direction), transform)
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let half_height = self.height / 2.0;
+
+		(
+			Vector4::new(-self.radius, -half_height, -self.radius, 1.0),
+			Vector4::new(self.radius, half_height, self.radius, 1.0),
+		)
+	}
+}
@@ -0,0 +1,392 @@
+use std::f32;
+use std::fmt;
+use std::sync::Arc;
+
+use na::Matrix4;
+use na::Vector4;
+use primitives::Primitive;
+use shading::Material;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+#[derive(Clone, Copy, fmt::Debug)]
+pub enum Operation
+{
+	Union,
+	Intersection,
+	Difference,
+}
+
+impl Operation
+{
+	fn contains(&self, inside_left: bool, inside_right: bool) -> bool
+	{
+		match self {
+			Operation::Union => inside_left || inside_right,
+			Operation::Intersection => inside_left && inside_right,
+			Operation::Difference => inside_left && !inside_right,
+		}
+	}
+}
+
+#[derive(fmt::Debug)]
+pub struct Operand
+{
+	primitive: Arc<dyn Primitive>,
+	transform: Matrix4<f32>,
+	material: Option<Arc<dyn Material>>,
+}
+
+impl Operand
+{
+	// The transform places the operand in the model space of the CSG node
+	pub fn new(
+		primitive: Arc<dyn Primitive>,
+		transform: Matrix4<f32>,
+		material: Option<Arc<dyn Material>>,
+	) -> Self
+	{
+		Operand {
+			primitive: primitive,
+			transform: transform.try_inverse().unwrap(), // We need the model to operand matrix here
+			material: material,
+		}
+	}
+
+	pub fn get_material(&self) -> Option<&Arc<dyn Material>>
+	{
+		self.material.as_ref()
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let mut intervals = self
+			.primitive
+			.hit_intervals(ray, self.transform * transform);
+
+		// Surfaces without a material of their own take the material of the operand
+		if let Some(material) = &self.material {
+			for interval in intervals.iter_mut() {
+				interval.entry_material = interval.entry_material.or(Some(material.as_ref()));
+				interval.exit_material = interval.exit_material.or(Some(material.as_ref()));
+			}
+		}
+
+		intervals
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let (min, max) = self.primitive.get_extents();
+		let model_transform = self.transform.try_inverse().unwrap();
+
+		let corners = [
+			Vector4::new(min.x, min.y, min.z, 1.0),
+			Vector4::new(min.x, min.y, max.z, 1.0),
+			Vector4::new(min.x, max.y, min.z, 1.0),
+			Vector4::new(min.x, max.y, max.z, 1.0),
+			Vector4::new(max.x, min.y, min.z, 1.0),
+			Vector4::new(max.x, min.y, max.z, 1.0),
+			Vector4::new(max.x, max.y, min.z, 1.0),
+			Vector4::new(max.x, max.y, max.z, 1.0),
+		];
+
+		corners.iter().fold(
+			(
+				Vector4::new(f32::INFINITY, f32::INFINITY, f32::INFINITY, 1.0),
+				Vector4::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY, 1.0),
+			),
+			|(lower, upper), corner| {
+				let corner = model_transform * corner;
+
+				(
+					Vector4::new(
+						f32::min(lower.x, corner.x),
+						f32::min(lower.y, corner.y),
+						f32::min(lower.z, corner.z),
+						1.0,
+					),
+					Vector4::new(
+						f32::max(upper.x, corner.x),
+						f32::max(upper.y, corner.y),
+						f32::max(upper.z, corner.z),
+						1.0,
+					),
+				)
+			},
+		)
+	}
+}
+
+// Constructive solid geometry node, operands are combined left to right so a
+// difference subtracts every operand after the first from the first
+#[derive(fmt::Debug)]
+pub struct Csg
+{
+	operation: Operation,
+	operands: Vec<Operand>,
+}
+
+impl Csg
+{
+	pub fn new(operation: Operation, operands: Vec<Operand>) -> Self
+	{
+		Csg {
+			operation: operation,
+			operands: operands,
+		}
+	}
+
+	pub fn get_operands(&self) -> &Vec<Operand>
+	{
+		&self.operands
+	}
+}
+
+impl Primitive for Csg
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		self.hit_with_material(ray, transform).map(|(hit, _)| hit)
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let mut operands = self.operands.iter();
+
+		let first = match operands.next() {
+			Some(operand) => operand.hit_intervals(ray, transform),
+			None => return Vec::new(),
+		};
+
+		operands.fold(first, |intervals, operand| {
+			// Nothing is left to intersect with or subtract from
+			if intervals.is_empty() {
+				match self.operation {
+					Operation::Intersection | Operation::Difference => return intervals,
+					Operation::Union => (),
+				}
+			}
+
+			combine_intervals(
+				self.operation,
+				intervals,
+				operand.hit_intervals(ray, transform),
+			)
+		})
+	}
+
+	fn hit_with_material(
+		&self,
+		ray: &Ray,
+		transform: Matrix4<f32>,
+	) -> Option<(Hit, Option<&dyn Material>)>
+	{
+		for interval in self.hit_intervals(ray, transform) {
+			if math::far_from_zero_pos(interval.entry.intersect) {
+				return Some((interval.entry, interval.entry_material));
+			}
+
+			// The ray starts inside the solid, the exit normal has to face the ray
+			if math::far_from_zero_pos(interval.exit.intersect) {
+				if interval.exit.intersect.is_finite() {
+					return Some((interval.exit.flipped(), interval.exit_material));
+				}
+
+				return None;
+			}
+		}
+
+		None
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let bounded_extents: Vec<(Vector4<f32>, Vector4<f32>)> = self
+			.operands
+			.iter()
+			.filter(|operand| operand.primitive.is_bounded())
+			.map(|operand| operand.get_extents())
+			.collect();
+
+		let extents = match self.operation {
+			Operation::Union => bounded_extents,
+			Operation::Intersection => {
+				let intersection = bounded_extents.into_iter().fold(
+					(
+						Vector4::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY, 1.0),
+						Vector4::new(f32::INFINITY, f32::INFINITY, f32::INFINITY, 1.0),
+					),
+					|(lower, upper), (min, max)| (lower.sup(&min), upper.inf(&max)),
+				);
+
+				vec![intersection]
+			},
+			Operation::Difference => self
+				.operands
+				.first()
+				.map(|operand| operand.get_extents())
+				.into_iter()
+				.collect(),
+		};
+
+		extents.into_iter().fold(
+			(
+				Vector4::new(f32::INFINITY, f32::INFINITY, f32::INFINITY, 1.0),
+				Vector4::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY, 1.0),
+			),
+			|(lower, upper), (min, max)| (lower.inf(&min), upper.sup(&max)),
+		)
+	}
+
+	fn is_bounded(&self) -> bool
+	{
+		match self.operation {
+			Operation::Union => self
+				.operands
+				.iter()
+				.all(|operand| operand.primitive.is_bounded()),
+			Operation::Intersection => self
+				.operands
+				.iter()
+				.any(|operand| operand.primitive.is_bounded()),
+			Operation::Difference => self
+				.operands
+				.first()
+				.is_none_or(|operand| operand.primitive.is_bounded()),
+		}
+	}
+}
+
+// Merges two sorted interval lists by sweeping over their boundaries in order
+// and tracking whether the ray is inside each side
+fn combine_intervals<'a>(
+	operation: Operation,
+	left: Vec<Interval<'a>>,
+	right: Vec<Interval<'a>>,
+) -> Vec<Interval<'a>>
+{
+	let mut boundaries = Vec::with_capacity(2 * (left.len() + right.len()));
+
+	for (intervals, is_left) in [(left, true), (right, false)] {
+		for interval in intervals {
+			boundaries.push((interval.entry, interval.entry_material, true, is_left));
+			boundaries.push((interval.exit, interval.exit_material, false, is_left));
+		}
+	}
+
+	boundaries.sort_by(|a, b| a.0.intersect.partial_cmp(&b.0.intersect).unwrap());
+
+	let mut inside_left = false;
+	let mut inside_right = false;
+	let mut inside = false;
+	let mut entry: Option<(Hit, Option<&dyn Material>)> = None;
+	let mut intervals = Vec::new();
+
+	for (hit, material, is_entry, is_left) in boundaries {
+		if is_left {
+			inside_left = is_entry;
+		} else {
+			inside_right = is_entry;
+		}
+
+		let now_inside = operation.contains(inside_left, inside_right);
+
+		if now_inside == inside {
+			continue;
+		}
+
+		// Leaving a subtracted solid enters the result, so its normal is reversed
+		let hit = if is_entry == now_inside {
+			hit
+		} else {
+			hit.flipped()
+		};
+
+		if now_inside {
+			entry = Some((hit, material));
+		} else if let Some((entry_hit, entry_material)) = entry.take() {
+			intervals.push(Interval {
+				entry: entry_hit,
+				exit: hit,
+				entry_material: entry_material,
+				exit_material: material,
+			});
+		}
+
+		inside = now_inside;
+	}
+
+	intervals
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn interval<'a>(entry: f32, exit: f32) -> Interval<'a>
+	{
+		let hit = |intersect: f32, normal: f32| {
+			Hit::new(
+				intersect,
+				Vector4::new(normal, 0.0, 0.0, 0.0),
+				(0.0, 0.0),
+				None,
+			)
+		};
+
+		Interval::new(hit(entry, -1.0), hit(exit, 1.0))
+	}
+
+	fn spans(intervals: &[Interval]) -> Vec<(f32, f32)>
+	{
+		intervals
+			.iter()
+			.map(|interval| (interval.entry.intersect, interval.exit.intersect))
+			.collect()
+	}
+
+	#[test]
+	fn union_merges_overlapping_intervals()
+	{
+		let result = combine_intervals(
+			Operation::Union,
+			vec![interval(1.0, 3.0), interval(5.0, 6.0)],
+			vec![interval(2.0, 4.0)],
+		);
+
+		assert_eq!(spans(&result), vec![(1.0, 4.0), (5.0, 6.0)]);
+	}
+
+	#[test]
+	fn intersection_keeps_overlap()
+	{
+		let result = combine_intervals(
+			Operation::Intersection,
+			vec![interval(1.0, 3.0), interval(5.0, 6.0)],
+			vec![interval(2.0, 5.5)],
+		);
+
+		assert_eq!(spans(&result), vec![(2.0, 3.0), (5.0, 5.5)]);
+	}
+
+	#[test]
+	fn difference_flips_subtracted_normals()
+	{
+		let result = combine_intervals(
+			Operation::Difference,
+			vec![interval(1.0, 6.0)],
+			vec![interval(0.0, 2.0), interval(3.0, 4.0)],
+		);
+
+		assert_eq!(spans(&result), vec![(2.0, 3.0), (4.0, 6.0)]);
+
+		// Entry at 2.0 is where the ray leaves the subtracted solid, the
+		// normal must point back along the ray
+		assert_eq!(result[0].entry.normal.x, -1.0);
+		assert_eq!(result[0].exit.normal.x, 1.0);
+	}
+}
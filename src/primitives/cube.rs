@@ -4,9 +4,11 @@ use std::fmt;
 use na::Matrix4;
 use na::Vector3;
 use na::Vector4;
+use primitives::primitive;
+use primitives::primitive::Crossing;
 use primitives::Primitive;
-use util::math;
 use Hit;
+use Interval;
 use Ray;
 
 #[derive(fmt::Debug)]
@@ -33,11 +35,9 @@ impl Cube
 			size: size,
 		}
 	}
-}
 
-impl Primitive for Cube
-{
-	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	// Every crossing of the surface along the ray, including those behind the origin
+	fn candidates(&self, origin: Vector4<f32>, direction: Vector4<f32>) -> Vec<Crossing>
 	{
 		enum Faces
 		{
@@ -49,11 +49,7 @@ impl Primitive for Cube
 			Right,
 		}
 
-		let point = transform * ray.point();
-		let origin = transform * ray.origin();
-
-		let ray_direction = point - origin;
-		let inv_direction = Vector4::repeat(1.0).component_div(&ray_direction);
+		let inv_direction = Vector4::repeat(1.0).component_div(&direction);
 
 		let min = (self.position.x - origin.x) * inv_direction.x;
 		let max = (self.position.x + self.size - origin.x) * inv_direction.x;
@@ -74,7 +70,7 @@ impl Primitive for Cube
 		};
 
 		if (t_min > ty_max) || (ty_min > t_max) {
-			return None;
+			return Vec::new();
 		}
 
 		if ty_min > t_min {
@@ -97,7 +93,7 @@ impl Primitive for Cube
 		};
 
 		if (t_min > tz_max) || (tz_min > t_max) {
-			return None;
+			return Vec::new();
 		}
 
 		if tz_min > t_min {
@@ -110,15 +106,7 @@ impl Primitive for Cube
 			face_max = z_face_max;
 		}
 
-		let (intersect, face) = if math::far_from_zero_pos(t_min) {
-			(t_min, face_min)
-		} else if math::far_from_zero_pos(t_max) {
-			(t_max, face_max)
-		} else {
-			return None;
-		};
-
-		let local_normal = match face {
+		let face_normal = |face: Faces| match face {
 			Faces::Right => Vector4::new(1.0, 0.0, 0.0, 0.0),
 			Faces::Left => Vector4::new(-1.0, 0.0, 0.0, 0.0),
 			Faces::Top => Vector4::new(0.0, 1.0, 0.0, 0.0),
@@ -127,15 +115,68 @@ impl Primitive for Cube
 			Faces::Back => Vector4::new(0.0, 0.0, -1.0, 0.0),
 		};
 
-		let world_normal = math::transform_normals(local_normal, transform);
+		// Each face is mapped once over the whole of UV, upright when looking at
+		// it from outside, and the top and bottom as seen from the front
+		let size = self.size;
+		let face_coordinates = |face: &Faces, t: f32| {
+			let local = ((origin + t * direction) - self.position) / size;
+
+			let (uv, dpdu, dpdv) = match face {
+				Faces::Front => ((local.x, local.y), (size, 0.0, 0.0), (0.0, size, 0.0)),
+				Faces::Back => (
+					(1.0 - local.x, local.y),
+					(-size, 0.0, 0.0),
+					(0.0, size, 0.0),
+				),
+				Faces::Right => (
+					(1.0 - local.z, local.y),
+					(0.0, 0.0, -size),
+					(0.0, size, 0.0),
+				),
+				Faces::Left => ((local.z, local.y), (0.0, 0.0, size), (0.0, size, 0.0)),
+				Faces::Top => (
+					(local.x, 1.0 - local.z),
+					(size, 0.0, 0.0),
+					(0.0, 0.0, -size),
+				),
+				Faces::Bottom => ((local.x, local.z), (size, 0.0, 0.0), (0.0, 0.0, size)),
+			};
+
+			(
+				uv,
+				Some((
+					Vector4::new(dpdu.0, dpdu.1, dpdu.2, 0.0),
+					Vector4::new(dpdv.0, dpdv.1, dpdv.2, 0.0),
+				)),
+			)
+		};
+
+		let (uv_min, tangents_min) = face_coordinates(&face_min, t_min);
+		let (uv_max, tangents_max) = face_coordinates(&face_max, t_max);
+
+		vec![
+			(t_min, face_normal(face_min), uv_min, tangents_min),
+			(t_max, face_normal(face_max), uv_max, tangents_max),
+		]
+	}
+}
+
+impl Primitive for Cube
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::closest_candidate(self.candidates(origin, direction), direction, transform)
+	}
 
-		// TODO: UV value calculation
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
 
-		Some(Hit {
-			intersect: intersect,
-			normal: world_normal,
-			uv: (0.0, 0.0),
-		})
+		primitive::candidate_intervals(self.candidates(origin, direction), transform)
 	}
 
 	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
@@ -0,0 +1,393 @@
+use std::f32;
+use std::fmt;
+
+use na::Matrix4;
+use na::Vector3;
+use na::Vector4;
+use primitives::primitive;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+const MAX_SUBDIVISION_DEPTH: i32 = 10;
+
+#[derive(Clone, Copy, fmt::Debug, PartialEq)]
+pub enum CurveType
+{
+	// Shaded as a tube of the curve's width
+	Cylinder,
+	// Shaded as a flat strip facing the ray
+	Flat,
+}
+
+// Thin curve made of cubic Bezier segments, e.g. hair or grass. Segments
+// share their end points, so n segments have 3n + 1 control points, and the
+// width is interpolated along each segment from a width per control point.
+// The curve is always intersected as a strip facing the ray, which is exact
+// for curves much thinner than they are long.
+#[derive(fmt::Debug)]
+pub struct Curve
+{
+	segments: Vec<Segment>,
+	curve_type: CurveType,
+}
+
+#[derive(Clone, Copy, fmt::Debug)]
+struct Segment
+{
+	points: [Vector3<f32>; 4],
+	widths: [f32; 4],
+	// Range of the whole curve's parameter covered by the segment
+	u_range: (f32, f32),
+}
+
+impl Curve
+{
+	pub fn new(points: Vec<Vector3<f32>>, widths: Vec<f32>, curve_type: CurveType) -> Self
+	{
+		assert!(points.len() >= 4 && (points.len() - 1).is_multiple_of(3));
+		assert!(widths.len() == points.len());
+
+		let segment_count = (points.len() - 1) / 3;
+
+		let segments = (0..segment_count)
+			.map(|index| {
+				let first = index * 3;
+
+				Segment {
+					points: [
+						points[first],
+						points[first + 1],
+						points[first + 2],
+						points[first + 3],
+					],
+					widths: [
+						widths[first],
+						widths[first + 1],
+						widths[first + 2],
+						widths[first + 3],
+					],
+					u_range: (
+						index as f32 / segment_count as f32,
+						(index + 1) as f32 / segment_count as f32,
+					),
+				}
+			})
+			.collect();
+
+		Curve {
+			segments: segments,
+			curve_type: curve_type,
+		}
+	}
+}
+
+impl Segment
+{
+	fn split(&self) -> (Segment, Segment)
+	{
+		let (left_points, right_points) = split_bezier(self.points);
+		let (left_widths, right_widths) = split_bezier(self.widths);
+		let middle = (self.u_range.0 + self.u_range.1) / 2.0;
+
+		(
+			Segment {
+				points: left_points,
+				widths: left_widths,
+				u_range: (self.u_range.0, middle),
+			},
+			Segment {
+				points: right_points,
+				widths: right_widths,
+				u_range: (middle, self.u_range.1),
+			},
+		)
+	}
+
+	fn max_width(&self) -> f32
+	{
+		self.widths.iter().cloned().fold(0.0, f32::max)
+	}
+}
+
+// Splits a cubic Bezier in half with de Casteljau's algorithm
+fn split_bezier<T>(points: [T; 4]) -> ([T; 4], [T; 4])
+where
+	T: Copy + std::ops::Add<Output = T> + std::ops::Mul<f32, Output = T>,
+{
+	let mid = |a: T, b: T| (a + b) * 0.5;
+
+	let p01 = mid(points[0], points[1]);
+	let p12 = mid(points[1], points[2]);
+	let p23 = mid(points[2], points[3]);
+	let p012 = mid(p01, p12);
+	let p123 = mid(p12, p23);
+	let p0123 = mid(p012, p123);
+
+	([points[0], p01, p012, p0123], [p0123, p123, p23, points[3]])
+}
+
+fn evaluate_bezier<T>(points: [T; 4], t: f32) -> T
+where
+	T: Copy + std::ops::Add<Output = T> + std::ops::Mul<f32, Output = T>,
+{
+	let s = 1.0 - t;
+
+	points[0] * (s * s * s)
+		+ points[1] * (3.0 * s * s * t)
+		+ points[2] * (3.0 * s * t * t)
+		+ points[3] * (t * t * t)
+}
+
+fn bezier_derivative(points: [Vector3<f32>; 4], t: f32) -> Vector3<f32>
+{
+	let s = 1.0 - t;
+
+	(points[1] - points[0]) * (3.0 * s * s)
+		+ (points[2] - points[1]) * (6.0 * s * t)
+		+ (points[3] - points[2]) * (3.0 * t * t)
+}
+
+impl Curve
+{
+	// Finds the closest hit on a segment given in ray space, where the ray
+	// starts at the origin and runs along +z. The segment is split until it
+	// is close enough to a line, then tested against a line of its width.
+	fn hit_segment(&self, segment: &Segment, depth: i32, max_z: f32) -> Option<(f32, f32)>
+	{
+		let half_width = segment.max_width() / 2.0;
+
+		let (lower, upper) = segment.points.iter().fold(
+			(
+				Vector3::repeat(f32::INFINITY),
+				Vector3::repeat(f32::NEG_INFINITY),
+			),
+			|(lower, upper), point| (lower.inf(point), upper.sup(point)),
+		);
+
+		if lower.x - half_width > 0.0
+			|| upper.x + half_width < 0.0
+			|| lower.y - half_width > 0.0
+			|| upper.y + half_width < 0.0
+			|| upper.z + half_width < 0.0
+			|| lower.z - half_width > max_z
+		{
+			return None;
+		}
+
+		if depth > 0 {
+			let (left, right) = segment.split();
+
+			return match self.hit_segment(&left, depth - 1, max_z) {
+				Some((z, u)) => Some(self.hit_segment(&right, depth - 1, z).unwrap_or((z, u))),
+				None => self.hit_segment(&right, depth - 1, max_z),
+			};
+		}
+
+		// Closest approach of the ray to the segment's chord in the XY plane
+		let start = segment.points[0].xy();
+		let chord = segment.points[3].xy() - start;
+		let length_squared = chord.norm_squared();
+
+		let w = if length_squared > 0.0 {
+			(-start.dot(&chord) / length_squared).clamp(0.0, 1.0)
+		} else {
+			0.0
+		};
+
+		let point = evaluate_bezier(segment.points, w);
+		let width = evaluate_bezier(segment.widths, w);
+
+		if point.xy().norm_squared() > width * width / 4.0 || point.z <= 0.0 || point.z >= max_z {
+			return None;
+		}
+
+		Some((
+			point.z,
+			segment.u_range.0 + w * (segment.u_range.1 - segment.u_range.0),
+		))
+	}
+
+	// Number of splits needed for the segment to be within a fraction of its
+	// width of a straight line
+	fn subdivision_depth(segment: &Segment) -> i32
+	{
+		let points = &segment.points;
+		let curvature = f32::max(
+			(points[0] - points[1] * 2.0 + points[2]).norm(),
+			(points[1] - points[2] * 2.0 + points[3]).norm(),
+		);
+
+		let tolerance = segment.max_width() / 20.0;
+		if tolerance <= 0.0 || curvature <= 0.0 {
+			return 0;
+		}
+
+		let depth = (f32::consts::SQRT_2 * 6.0 * curvature / (8.0 * tolerance)).log(4.0);
+
+		(depth.ceil() as i32).clamp(0, MAX_SUBDIVISION_DEPTH)
+	}
+}
+
+impl Primitive for Curve
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = (transform * ray.origin()).xyz();
+		let direction = (transform * ray.point()).xyz() - origin;
+		let length = direction.norm();
+
+		if length == 0.0 {
+			return None;
+		}
+
+		// Ray space has the ray along +z, distances are kept so widths carry over
+		let z_axis = direction / length;
+		let helper = if z_axis.x.abs() > 0.9 {
+			Vector3::new(0.0, 1.0, 0.0)
+		} else {
+			Vector3::new(1.0, 0.0, 0.0)
+		};
+		let x_axis = helper.cross(&z_axis).normalize();
+		let y_axis = z_axis.cross(&x_axis);
+		let to_ray_space = |point: Vector3<f32>| {
+			let offset = point - origin;
+			Vector3::new(
+				offset.dot(&x_axis),
+				offset.dot(&y_axis),
+				offset.dot(&z_axis),
+			)
+		};
+
+		let mut closest: Option<(f32, f32, &Segment)> = None;
+
+		for segment in &self.segments {
+			let ray_segment = Segment {
+				points: [
+					to_ray_space(segment.points[0]),
+					to_ray_space(segment.points[1]),
+					to_ray_space(segment.points[2]),
+					to_ray_space(segment.points[3]),
+				],
+				..*segment
+			};
+
+			let max_z = closest.map_or(f32::INFINITY, |(z, _, _)| z);
+			let depth = Curve::subdivision_depth(&ray_segment);
+
+			if let Some((z, u)) = self.hit_segment(&ray_segment, depth, max_z) {
+				if math::far_from_zero_pos(z / length) {
+					closest = Some((z, u, segment));
+				}
+			}
+		}
+
+		let (z, u, segment) = closest?;
+		let intersect = z / length;
+
+		// Position and tangent on the segment at the hit
+		let t = (u - segment.u_range.0) / (segment.u_range.1 - segment.u_range.0);
+		let centre = evaluate_bezier(segment.points, t);
+		let width = evaluate_bezier(segment.widths, t);
+		let tangent = bezier_derivative(segment.points, t);
+		let tangent_direction = tangent.normalize();
+
+		// Facing the ray, perpendicular to the curve
+		let facing = -z_axis + tangent_direction * z_axis.dot(&tangent_direction);
+		let facing = if facing.norm_squared() > 0.0 {
+			facing.normalize()
+		} else {
+			-z_axis
+		};
+		let across = tangent_direction.cross(&facing);
+
+		let offset = (origin + direction * intersect - centre).dot(&across) / (width / 2.0);
+		let offset = offset.clamp(-1.0, 1.0);
+
+		let normal = match self.curve_type {
+			CurveType::Cylinder => facing * (1.0 - offset * offset).sqrt() + across * offset,
+			CurveType::Flat => facing,
+		};
+
+		Some(Hit::new(
+			intersect,
+			math::transform_normals(normal.insert_row(3, 0.0), transform),
+			(u, (offset + 1.0) / 2.0),
+			primitive::tangents_to_world(
+				Some((
+					tangent.insert_row(3, 0.0),
+					(across * width).insert_row(3, 0.0),
+				)),
+				transform,
+			),
+		))
+	}
+
+	// Curves are too thin to enclose anything
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		primitive::flat_interval(self.hit(ray, transform))
+	}
+
+	// The control points bound each segment, padded by the widest point
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let (lower, upper) = self.segments.iter().fold(
+			(
+				Vector3::repeat(f32::INFINITY),
+				Vector3::repeat(f32::NEG_INFINITY),
+			),
+			|(lower, upper), segment| {
+				let padding = Vector3::repeat(segment.max_width() / 2.0);
+
+				segment
+					.points
+					.iter()
+					.fold((lower, upper), |(lower, upper), point| {
+						(lower.inf(&(point - padding)), upper.sup(&(point + padding)))
+					})
+			},
+		);
+
+		(lower.insert_row(3, 1.0), upper.insert_row(3, 1.0))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn hits_curved_strand_across_its_width()
+	{
+		// Arc in the XY plane from (-1, 0) to (1, 0) peaking at y = 0.75
+		let points = vec![
+			Vector3::new(-1.0, 0.0, 0.0),
+			Vector3::new(-1.0, 1.0, 0.0),
+			Vector3::new(1.0, 1.0, 0.0),
+			Vector3::new(1.0, 0.0, 0.0),
+		];
+		let curve = Curve::new(points, vec![0.1; 4], CurveType::Cylinder);
+
+		let ray = Ray::new(
+			Vector4::new(0.0, 0.74, 5.0, 1.0),
+			Vector4::new(0.0, 0.74, 4.0, 1.0),
+		);
+		let hit = curve.hit(&ray, Matrix4::identity()).unwrap();
+
+		assert!((hit.intersect - 5.0).abs() < 0.001);
+		assert!((hit.uv.0 - 0.5).abs() < 0.01);
+
+		// Just below the centre line, so the normal leans down
+		assert!(hit.normal.y < 0.0 && hit.normal.z > 0.0);
+
+		let miss = Ray::new(
+			Vector4::new(0.0, 0.9, 5.0, 1.0),
+			Vector4::new(0.0, 0.9, 4.0, 1.0),
+		);
+		assert!(curve.hit(&miss, Matrix4::identity()).is_none());
+	}
+}
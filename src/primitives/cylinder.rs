@@ -0,0 +1,169 @@
+use std::f32;
+use std::fmt;
+
+use na::Matrix4;
+use na::Vector4;
+use primitives::primitive;
+use primitives::primitive::Crossing;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+// Cylinder around the Y axis centred on the origin, uncapped cylinders are
+// open tubes whose inside is visible
+#[derive(fmt::Debug)]
+pub struct Cylinder
+{
+	radius: f32,
+	height: f32,
+	capped: bool,
+}
+
+impl Cylinder
+{
+	pub fn new(radius: f32, height: f32, capped: bool) -> Self
+	{
+		Cylinder {
+			radius: radius,
+			height: height,
+			capped: capped,
+		}
+	}
+
+	// Every crossing of the surface along the ray, including those behind the origin
+	fn candidates(&self, origin: Vector4<f32>, direction: Vector4<f32>) -> Vec<Crossing>
+	{
+		let half_height = self.height / 2.0;
+		let mut candidates = Vec::with_capacity(4);
+
+		for t in side_intersections(origin, direction, self.radius) {
+			let contact_point = origin + (t * direction);
+
+			if contact_point.y.abs() <= half_height {
+				candidates.push((
+					t,
+					Vector4::new(contact_point.x, 0.0, contact_point.z, 0.0),
+					(
+						angle_around_y(contact_point),
+						(contact_point.y + half_height) / self.height,
+					),
+					Some((
+						tangent_around_y(contact_point),
+						Vector4::new(0.0, self.height, 0.0, 0.0),
+					)),
+				));
+			}
+		}
+
+		if self.capped {
+			candidates.extend(cap_intersection(
+				origin,
+				direction,
+				self.radius,
+				half_height,
+			));
+			candidates.extend(cap_intersection(
+				origin,
+				direction,
+				self.radius,
+				-half_height,
+			));
+		}
+
+		candidates
+	}
+}
+
+impl Primitive for Cylinder
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::closest_candidate(self.candidates(origin, direction), direction, transform)
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::candidate_intervals(self.candidates(origin, direction), transform)
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let half_height = self.height / 2.0;
+
+		(
+			Vector4::new(-self.radius, -half_height, -self.radius, 1.0),
+			Vector4::new(self.radius, half_height, self.radius, 1.0),
+		)
+	}
+}
+
+// Intersects a ray with the infinite cylinder of the given radius around the Y axis
+pub fn side_intersections(origin: Vector4<f32>, direction: Vector4<f32>, radius: f32) -> Vec<f32>
+{
+	let a = direction.x * direction.x + direction.z * direction.z;
+	let b = 2.0 * (origin.x * direction.x + origin.z * direction.z);
+	let c = origin.x * origin.x + origin.z * origin.z - radius * radius;
+
+	// Rays parallel to the axis never touch the side
+	if math::near_zero(a) {
+		return Vec::new();
+	}
+
+	match math::quadratic_roots(a, b, c) {
+		math::QuadRoots::Two(root_one, root_two) => vec![root_one, root_two],
+		math::QuadRoots::One(root) => vec![root],
+		math::QuadRoots::Zero => Vec::new(),
+	}
+}
+
+// Intersects a ray with a disk cap of the given radius at height y facing away
+// from the origin, UVs are planar over the cap's square bounds
+pub fn cap_intersection(
+	origin: Vector4<f32>,
+	direction: Vector4<f32>,
+	radius: f32,
+	y: f32,
+) -> Option<Crossing>
+{
+	if math::near_zero(direction.y) {
+		return None;
+	}
+
+	let t = (y - origin.y) / direction.y;
+	let contact_point = origin + (t * direction);
+
+	if contact_point.x * contact_point.x + contact_point.z * contact_point.z > radius * radius {
+		return None;
+	}
+
+	Some((
+		t,
+		Vector4::new(0.0, y.signum(), 0.0, 0.0),
+		(
+			(contact_point.x + radius) / (2.0 * radius),
+			(contact_point.z + radius) / (2.0 * radius),
+		),
+		None,
+	))
+}
+
+// Fraction of a full turn around the Y axis, used as the U coordinate of
+// surfaces of revolution
+pub fn angle_around_y(point: Vector4<f32>) -> f32
+{
+	0.5 + f32::atan2(point.x, point.z) / (2.0 * f32::consts::PI)
+}
+
+// Derivative of a point with respect to angle_around_y
+pub fn tangent_around_y(point: Vector4<f32>) -> Vector4<f32>
+{
+	Vector4::new(point.z, 0.0, -point.x, 0.0) * (2.0 * f32::consts::PI)
+}
@@ -0,0 +1,107 @@
+use std::f32;
+use std::fmt;
+
+use na::Matrix4;
+use na::Vector3;
+use na::Vector4;
+use primitives::plane;
+use primitives::primitive;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+// Disk lying in the X/Z plane facing +Y, UVs map the disk's square bounds
+// onto [0, 1] so an image is placed on it undistorted
+#[derive(fmt::Debug)]
+pub struct Disk
+{
+	position: Vector4<f32>,
+	radius: f32,
+}
+
+impl Disk
+{
+	pub fn new(position: Vector3<f32>, radius: f32) -> Self
+	{
+		Disk {
+			position: Vector4::new(position.x, position.y, position.z, 1.0),
+			radius: radius,
+		}
+	}
+}
+
+impl Primitive for Disk
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		// Move the disk to the origin so it can be treated as the Y = 0 plane
+		let local_transform = Matrix4::new_translation(&-self.position.xyz()) * transform;
+		let (intersect, contact_point, normal) = plane::hit_xz_plane(ray, local_transform)?;
+
+		let x = contact_point.x;
+		let z = contact_point.z;
+
+		if x * x + z * z > self.radius * self.radius {
+			return None;
+		}
+
+		let u = (x + self.radius) / (2.0 * self.radius);
+		let v = (z + self.radius) / (2.0 * self.radius);
+
+		Some(Hit::new(
+			intersect,
+			math::transform_normals(normal, transform),
+			(u, v),
+			primitive::tangents_to_world(
+				Some((
+					Vector4::new(2.0 * self.radius, 0.0, 0.0, 0.0),
+					Vector4::new(0.0, 0.0, 2.0 * self.radius, 0.0),
+				)),
+				transform,
+			),
+		))
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		primitive::flat_interval(self.hit(ray, transform))
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let offset = Vector4::new(self.radius, 0.0, self.radius, 0.0);
+
+		(self.position - offset, self.position + offset)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn hits_within_the_radius()
+	{
+		let disk = Disk::new(Vector3::new(0.0, 1.0, 0.0), 2.0);
+
+		let ray = Ray::new(
+			Vector4::new(1.0, 5.0, 0.0, 1.0),
+			Vector4::new(1.0, 4.0, 0.0, 1.0),
+		);
+		let hit = disk.hit(&ray, Matrix4::identity()).unwrap();
+
+		assert!((hit.intersect - 4.0).abs() < 0.001);
+		assert!((hit.normal - Vector4::new(0.0, 1.0, 0.0, 0.0)).norm() < 0.001);
+		assert!((hit.uv.0 - 0.75).abs() < 0.001 && (hit.uv.1 - 0.5).abs() < 0.001);
+
+		// Inside the square around the disk but past its edge
+		let ray = Ray::new(
+			Vector4::new(1.5, 5.0, 1.5, 1.0),
+			Vector4::new(1.5, 4.0, 1.5, 1.0),
+		);
+		assert!(disk.hit(&ray, Matrix4::identity()).is_none());
+	}
+}
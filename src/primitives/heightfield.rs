@@ -0,0 +1,472 @@
+use std::error;
+use std::f32;
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+
+use image;
+use na::Matrix4;
+use na::Vector3;
+use na::Vector4;
+use png;
+use png::HasParameters;
+use primitives::primitive;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+// Cells per side of the blocks whose height ranges are used to skip over
+// empty space before stepping through individual cells
+const BLOCK_SIZE: usize = 32;
+
+#[derive(fmt::Debug)]
+pub enum Error
+{
+	FileReadError(Box<dyn error::Error + Send + Sync>),
+	ImageFormatError(String),
+}
+
+impl Error
+{
+	fn read_error<E: error::Error + Send + Sync + 'static>(err: E) -> Self
+	{
+		Self::FileReadError(Box::new(err))
+	}
+}
+
+impl fmt::Display for Error
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error>
+	{
+		match self {
+			Self::FileReadError(err) => err.fmt(f),
+			Self::ImageFormatError(msg) => write!(f, "{}", msg),
+		}
+	}
+}
+
+impl error::Error for Error {}
+
+// Terrain defined by a grid of height samples, intersected directly rather
+// than tessellated so large tiles stay cheap to store. Samples are spaced
+// evenly over a footprint centred on the origin whose longer side is 1 unit,
+// with heights running from 0 to the given scale.
+#[derive(fmt::Debug)]
+pub struct Heightfield
+{
+	width: usize,
+	depth: usize,
+	heights: Vec<f32>,
+	cell_size: f32,
+	corner: (f32, f32),
+	blocks: Vec<(f32, f32)>,
+	blocks_wide: usize,
+	height_range: (f32, f32),
+}
+
+impl Heightfield
+{
+	// Builds a heightfield from rows of samples in [0, 1] running along the X
+	// axis, with successive rows moving along the Z axis
+	pub fn new(width: usize, depth: usize, samples: Vec<f32>, scale: f32) -> Self
+	{
+		assert!(width >= 2 && depth >= 2 && samples.len() == width * depth);
+
+		let heights: Vec<f32> = samples.into_iter().map(|sample| sample * scale).collect();
+
+		let cell_size = 1.0 / (width.max(depth) - 1) as f32;
+		let corner = (
+			-cell_size * (width - 1) as f32 / 2.0,
+			-cell_size * (depth - 1) as f32 / 2.0,
+		);
+
+		let cells_wide = width - 1;
+		let cells_deep = depth - 1;
+		let blocks_wide = cells_wide.div_ceil(BLOCK_SIZE);
+		let blocks_deep = cells_deep.div_ceil(BLOCK_SIZE);
+
+		let mut blocks = vec![(f32::INFINITY, f32::NEG_INFINITY); blocks_wide * blocks_deep];
+
+		// Every sample bounds the cells on both sides of it, so samples on a
+		// block edge count towards both blocks
+		for z in 0..depth {
+			for x in 0..width {
+				let height = heights[z * width + x];
+
+				let block_xs =
+					(x.saturating_sub(1) / BLOCK_SIZE)..=(x.min(cells_wide - 1) / BLOCK_SIZE);
+				for block_z in
+					(z.saturating_sub(1) / BLOCK_SIZE)..=(z.min(cells_deep - 1) / BLOCK_SIZE)
+				{
+					for block_x in block_xs.clone() {
+						let block = &mut blocks[block_z * blocks_wide + block_x];
+						block.0 = f32::min(block.0, height);
+						block.1 = f32::max(block.1, height);
+					}
+				}
+			}
+		}
+
+		let height_range = heights.iter().fold(
+			(f32::INFINITY, f32::NEG_INFINITY),
+			|(low, high), &height| (f32::min(low, height), f32::max(high, height)),
+		);
+
+		Heightfield {
+			width: width,
+			depth: depth,
+			heights: heights,
+			cell_size: cell_size,
+			corner: corner,
+			blocks: blocks,
+			blocks_wide: blocks_wide,
+			height_range: height_range,
+		}
+	}
+
+	// Loads a grayscale image, 16-bit PNGs keep their full precision while
+	// other images are read as 8-bit luminance
+	pub fn from_file(file_name: &str, scale: f32) -> Result<Self, Error>
+	{
+		let is_png = Path::new(file_name)
+			.extension()
+			.is_some_and(|extension| extension.to_string_lossy().eq_ignore_ascii_case("png"));
+
+		let sixteen_bit = if is_png {
+			read_png_16(file_name)?
+		} else {
+			None
+		};
+
+		let (width, depth, samples) = if let Some(sixteen_bit) = sixteen_bit {
+			sixteen_bit
+		} else {
+			let image = image::open(file_name).map_err(Error::read_error)?.to_luma();
+			let (width, depth) = image.dimensions();
+			let samples = image
+				.into_raw()
+				.into_iter()
+				.map(|value| f32::from(value) / 255.0)
+				.collect();
+
+			(width as usize, depth as usize, samples)
+		};
+
+		if width < 2 || depth < 2 {
+			return Err(Error::ImageFormatError(format!(
+				"Failed to read {}: heightfields need at least 2x2 samples",
+				file_name
+			)));
+		}
+
+		Ok(Heightfield::new(width, depth, samples, scale))
+	}
+
+	fn height(&self, x: usize, z: usize) -> f32
+	{
+		self.heights[z * self.width + x]
+	}
+
+	fn vertex(&self, x: usize, z: usize) -> Vector3<f32>
+	{
+		Vector3::new(
+			self.corner.0 + x as f32 * self.cell_size,
+			self.height(x, z),
+			self.corner.1 + z as f32 * self.cell_size,
+		)
+	}
+
+	// Vertex normal from central differences of the neighbouring heights
+	fn vertex_normal(&self, x: usize, z: usize) -> Vector3<f32>
+	{
+		let (left, right) = (x.saturating_sub(1), (x + 1).min(self.width - 1));
+		let (back, front) = (z.saturating_sub(1), (z + 1).min(self.depth - 1));
+
+		let slope_x = (self.height(right, z) - self.height(left, z))
+			/ ((right - left) as f32 * self.cell_size);
+		let slope_z = (self.height(x, front) - self.height(x, back))
+			/ ((front - back) as f32 * self.cell_size);
+
+		Vector3::new(-slope_x, 1.0, -slope_z).normalize()
+	}
+
+	// Intersects the two triangles of a cell, returning the intersect, the
+	// interpolated normal and the UV coordinates of the nearest hit in front of
+	// the ray
+	fn hit_cell(
+		&self,
+		x: usize,
+		z: usize,
+		origin: Vector3<f32>,
+		direction: Vector3<f32>,
+	) -> Option<(f32, Vector3<f32>, (f32, f32))>
+	{
+		let corners = [(x, z), (x + 1, z), (x + 1, z + 1), (x, z + 1)];
+		let triangles = [
+			[corners[0], corners[1], corners[2]],
+			[corners[0], corners[2], corners[3]],
+		];
+
+		let mut closest: Option<(f32, Vector3<f32>, (f32, f32))> = None;
+
+		for triangle in triangles.iter() {
+			let v0 = self.vertex(triangle[0].0, triangle[0].1);
+			let v1 = self.vertex(triangle[1].0, triangle[1].1);
+			let v2 = self.vertex(triangle[2].0, triangle[2].1);
+
+			let (t, u, v) = match math::ray_triangle_intersection(origin, direction, v0, v1, v2) {
+				Some(hit) => hit,
+				None => continue,
+			};
+
+			if !math::far_from_zero_pos(t)
+				|| closest.is_some_and(|(closest_t, _, _)| t >= closest_t)
+			{
+				continue;
+			}
+
+			let normal = self.vertex_normal(triangle[0].0, triangle[0].1) * (1.0 - u - v)
+				+ self.vertex_normal(triangle[1].0, triangle[1].1) * u
+				+ self.vertex_normal(triangle[2].0, triangle[2].1) * v;
+
+			let point = origin + t * direction;
+			let uv = (
+				(point.x - self.corner.0) / (self.cell_size * (self.width - 1) as f32),
+				(point.z - self.corner.1) / (self.cell_size * (self.depth - 1) as f32),
+			);
+
+			closest = Some((t, normal, uv));
+		}
+
+		closest
+	}
+
+	// Steps through the blocks under the ray, and through the cells of any block
+	// whose height range the ray passes through
+	fn march(
+		&self,
+		origin: Vector3<f32>,
+		direction: Vector3<f32>,
+	) -> Option<(f32, Vector3<f32>, (f32, f32))>
+	{
+		let (lower, upper) = self.get_extents();
+		let (t_enter, t_exit) =
+			math::ray_box_intersection(origin, direction, lower.xyz(), upper.xyz())?;
+		let t_enter = f32::max(t_enter, 0.0);
+
+		// Grid space has unit cells with the first sample at the origin
+		let grid_origin = (
+			(origin.x - self.corner.0) / self.cell_size,
+			(origin.z - self.corner.1) / self.cell_size,
+		);
+		let grid_direction = (direction.x / self.cell_size, direction.z / self.cell_size);
+
+		let cells = (self.width - 1, self.depth - 1);
+		let blocks = (self.blocks_wide, self.blocks.len() / self.blocks_wide);
+
+		let height_at = |t: f32| origin.y + t * direction.y;
+
+		traverse(
+			grid_origin,
+			grid_direction,
+			(t_enter, t_exit),
+			BLOCK_SIZE as f32,
+			((0, blocks.0), (0, blocks.1)),
+			|block_x, block_z, t_start, t_end| {
+				let (low, high) = self.blocks[block_z * self.blocks_wide + block_x];
+				let (y_start, y_end) = (height_at(t_start), height_at(t_end));
+
+				if f32::max(y_start, y_end) < low || f32::min(y_start, y_end) > high {
+					return None;
+				}
+
+				let range_x = (
+					block_x * BLOCK_SIZE,
+					((block_x + 1) * BLOCK_SIZE).min(cells.0),
+				);
+				let range_z = (
+					block_z * BLOCK_SIZE,
+					((block_z + 1) * BLOCK_SIZE).min(cells.1),
+				);
+
+				traverse(
+					grid_origin,
+					grid_direction,
+					(t_start, t_end),
+					1.0,
+					(range_x, range_z),
+					|x, z, _, _| self.hit_cell(x, z, origin, direction),
+				)
+			},
+		)
+	}
+}
+
+// Walks the cells of a 2D grid crossed by the ray between the given
+// intersects, calling visit with each cell and the span of the ray over it
+// until it reports a result. Cells are size units wide and only those in the
+// given index ranges are visited.
+fn traverse<T, F>(
+	origin: (f32, f32),
+	direction: (f32, f32),
+	(t_start, t_end): (f32, f32),
+	size: f32,
+	((x_low, x_high), (z_low, z_high)): ((usize, usize), (usize, usize)),
+	mut visit: F,
+) -> Option<T>
+where
+	F: FnMut(usize, usize, f32, f32) -> Option<T>,
+{
+	let setup = |origin: f32, direction: f32, low: usize, high: usize| {
+		let position = origin + direction * t_start;
+		let cell = ((position / size).floor().max(low as f32) as usize).min(high - 1);
+
+		if direction > 0.0 {
+			let t_next = ((cell + 1) as f32 * size - origin) / direction;
+			(cell, 1, t_next, size / direction)
+		} else if direction < 0.0 {
+			let t_next = (cell as f32 * size - origin) / direction;
+			(cell, -1, t_next, -size / direction)
+		} else {
+			(cell, 0, f32::INFINITY, f32::INFINITY)
+		}
+	};
+
+	let (mut x, step_x, mut t_next_x, t_delta_x) = setup(origin.0, direction.0, x_low, x_high);
+	let (mut z, step_z, mut t_next_z, t_delta_z) = setup(origin.1, direction.1, z_low, z_high);
+
+	let mut t = t_start;
+
+	loop {
+		let t_cell_end = f32::min(f32::min(t_next_x, t_next_z), t_end);
+
+		if let Some(result) = visit(x, z, t, t_cell_end) {
+			return Some(result);
+		}
+
+		if t_cell_end >= t_end {
+			return None;
+		}
+
+		if t_next_x < t_next_z {
+			if (step_x < 0 && x == x_low) || (step_x > 0 && x + 1 == x_high) {
+				return None;
+			}
+			x = (x as isize + step_x) as usize;
+			t_next_x += t_delta_x;
+		} else {
+			if (step_z < 0 && z == z_low) || (step_z > 0 && z + 1 == z_high) {
+				return None;
+			}
+			z = (z as isize + step_z) as usize;
+			t_next_z += t_delta_z;
+		}
+
+		t = t_cell_end;
+	}
+}
+
+// The image crate reduces 16-bit PNGs to 8 bits, so those are decoded
+// directly. Returns None for any other PNG, which the image crate handles.
+fn read_png_16(file_name: &str) -> Result<Option<(usize, usize, Vec<f32>)>, Error>
+{
+	let file = File::open(file_name).map_err(Error::read_error)?;
+
+	let mut decoder = png::Decoder::new(file);
+	decoder.set(png::Transformations::IDENTITY);
+
+	let (info, mut reader) = decoder.read_info().map_err(Error::read_error)?;
+
+	if info.bit_depth != png::BitDepth::Sixteen {
+		return Ok(None);
+	}
+
+	let mut data = vec![0; info.buffer_size()];
+	reader.next_frame(&mut data).map_err(Error::read_error)?;
+
+	let channels = match info.color_type {
+		png::ColorType::Grayscale => 1,
+		png::ColorType::GrayscaleAlpha => 2,
+		png::ColorType::RGB | png::ColorType::Indexed => 3,
+		png::ColorType::RGBA => 4,
+	};
+
+	let values: Vec<f32> = data
+		.chunks(2)
+		.map(|bytes| f32::from(u16::from(bytes[0]) << 8 | u16::from(bytes[1])) / 65535.0)
+		.collect();
+
+	// Colour images are reduced to their luminance, alpha is ignored
+	let samples = values
+		.chunks(channels)
+		.map(|pixel| match pixel.len() {
+			3 | 4 => 0.299 * pixel[0] + 0.587 * pixel[1] + 0.114 * pixel[2],
+			_ => pixel[0],
+		})
+		.collect();
+
+	Ok(Some((info.width as usize, info.height as usize, samples)))
+}
+
+impl Primitive for Heightfield
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		let candidates = self
+			.march(origin.xyz(), direction.xyz())
+			.map(|(t, normal, uv)| (t, normal.insert_row(3, 0.0), uv, None))
+			.into_iter()
+			.collect();
+
+		primitive::closest_candidate(candidates, direction, transform)
+	}
+
+	// Terrain is an open surface rather than a solid
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		primitive::flat_interval(self.hit(ray, transform))
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		(
+			Vector4::new(self.corner.0, self.height_range.0, self.corner.1, 1.0),
+			Vector4::new(-self.corner.0, self.height_range.1, -self.corner.1, 1.0),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn hits_interpolated_slope()
+	{
+		// A ramp rising along X, large enough to span several blocks
+		let size = 100;
+		let samples = (0..size * size)
+			.map(|index| (index % size) as f32 / (size - 1) as f32)
+			.collect();
+		let heightfield = Heightfield::new(size, size, samples, 1.0);
+
+		let ray = Ray::new(
+			Vector4::new(0.25, 5.0, 0.1, 1.0),
+			Vector4::new(0.25, 4.0, 0.1, 1.0),
+		);
+		let hit = heightfield.hit(&ray, Matrix4::identity()).unwrap();
+
+		// The ramp has height 0.75 at x = 0.25 and a normal tilted away from X
+		assert!((hit.intersect - 4.25).abs() < 0.001);
+
+		let expected_normal = Vector4::new(-1.0, 1.0, 0.0, 0.0).normalize();
+		assert!((hit.normal - expected_normal).norm() < 0.001);
+		assert!((hit.uv.0 - 0.75).abs() < 0.001);
+	}
+}
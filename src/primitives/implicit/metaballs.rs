@@ -0,0 +1,111 @@
+use std::f32;
+use std::fmt;
+
+use na::Vector3;
+
+use super::ScalarField;
+
+#[derive(fmt::Debug)]
+pub struct Ball
+{
+	center: Vector3<f32>,
+	radius: f32,
+}
+
+impl Ball
+{
+	pub fn new(center: Vector3<f32>, radius: f32) -> Self
+	{
+		Ball {
+			center: center,
+			radius: radius,
+		}
+	}
+}
+
+// Blobby surface where the summed influence of the balls reaches the
+// threshold. Each ball contributes (1 - r^2 / R^2)^3 within its radius R and
+// nothing outside it, so the field is bounded by the balls themselves.
+#[derive(fmt::Debug)]
+pub struct Metaballs
+{
+	balls: Vec<Ball>,
+	threshold: f32,
+}
+
+impl Metaballs
+{
+	pub fn new(balls: Vec<Ball>, threshold: f32) -> Self
+	{
+		Metaballs {
+			balls: balls,
+			threshold: threshold,
+		}
+	}
+}
+
+impl ScalarField for Metaballs
+{
+	fn value(&self, point: Vector3<f32>) -> f32
+	{
+		let influence: f32 = self
+			.balls
+			.iter()
+			.map(|ball| {
+				let q = (point - ball.center).norm_squared() / (ball.radius * ball.radius);
+
+				if q < 1.0 {
+					(1.0 - q).powi(3)
+				} else {
+					0.0
+				}
+			})
+			.sum();
+
+		self.threshold - influence
+	}
+
+	fn gradient(&self, point: Vector3<f32>) -> Vector3<f32>
+	{
+		self.balls.iter().fold(Vector3::zeros(), |gradient, ball| {
+			let offset = point - ball.center;
+			let radius_squared = ball.radius * ball.radius;
+			let q = offset.norm_squared() / radius_squared;
+
+			if q < 1.0 {
+				gradient + offset * (6.0 * (1.0 - q).powi(2) / radius_squared)
+			} else {
+				gradient
+			}
+		})
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		self.balls.iter().fold(
+			(
+				Vector3::repeat(f32::INFINITY),
+				Vector3::repeat(f32::NEG_INFINITY),
+			),
+			|(lower, upper), ball| {
+				let radius = Vector3::repeat(ball.radius);
+
+				(
+					lower.inf(&(ball.center - radius)),
+					upper.sup(&(ball.center + radius)),
+				)
+			},
+		)
+	}
+
+	fn step_size(&self) -> f32
+	{
+		let smallest_radius = self
+			.balls
+			.iter()
+			.map(|ball| ball.radius)
+			.fold(f32::INFINITY, f32::min);
+
+		smallest_radius / 8.0
+	}
+}
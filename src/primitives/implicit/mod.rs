@@ -0,0 +1,151 @@
+mod metaballs;
+mod sampled;
+
+use std::fmt;
+
+use na::Matrix4;
+use na::Vector3;
+use na::Vector4;
+use primitives::primitive;
+use primitives::primitive::Crossing;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+pub use self::metaballs::Ball;
+pub use self::metaballs::Metaballs;
+pub use self::sampled::SampledField;
+
+const BISECTION_STEPS: usize = 24;
+
+pub trait ScalarField: Send + Sync + fmt::Debug
+{
+	// Value of the field, the surface is the zero level set with negative
+	// values inside
+	fn value(&self, point: Vector3<f32>) -> f32;
+
+	fn gradient(&self, point: Vector3<f32>) -> Vector3<f32>;
+
+	// Bounds outside of which the field is positive
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>);
+
+	// Distance between samples when searching for a sign change, features
+	// thinner than this can be missed
+	fn step_size(&self) -> f32;
+}
+
+// Surface found by stepping along the ray until the field changes sign and
+// refining the bracketing interval by bisection. Unlike distance fields the
+// value gives no bound on how far away the surface is, so the steps are fixed.
+#[derive(fmt::Debug)]
+pub struct Implicit
+{
+	field: Box<dyn ScalarField>,
+}
+
+impl Implicit
+{
+	pub fn new(field: Box<dyn ScalarField>) -> Self
+	{
+		Implicit { field: field }
+	}
+
+	fn march(
+		&self,
+		origin: Vector4<f32>,
+		direction: Vector4<f32>,
+		first_only: bool,
+	) -> Vec<Crossing>
+	{
+		let mut crossings = Vec::new();
+
+		let (lower, upper) = self.field.get_extents();
+		let (t_near, t_far) =
+			match math::ray_box_intersection(origin.xyz(), direction.xyz(), lower, upper) {
+				Some(bounds) => bounds,
+				None => return crossings,
+			};
+
+		let t_start = if first_only {
+			f32::max(t_near, 0.0)
+		} else {
+			t_near
+		};
+
+		let origin = origin.xyz();
+		let direction = direction.xyz();
+		let step = self.field.step_size() / direction.norm();
+		let value_at = |t: f32| self.field.value(origin + t * direction);
+
+		let mut t_previous = t_start;
+		let mut value_previous = value_at(t_previous);
+
+		while t_previous < t_far {
+			let t = f32::min(t_previous + step, t_far);
+			let value = value_at(t);
+
+			if (value < 0.0) != (value_previous < 0.0) {
+				let (mut t_low, mut t_high) = (t_previous, t);
+
+				for _ in 0..BISECTION_STEPS {
+					let t_middle = (t_low + t_high) / 2.0;
+
+					if (value_at(t_middle) < 0.0) == (value_previous < 0.0) {
+						t_low = t_middle;
+					} else {
+						t_high = t_middle;
+					}
+				}
+
+				let t_root = (t_low + t_high) / 2.0;
+				let normal = self.field.gradient(origin + t_root * direction);
+
+				if normal.norm() > 0.0 && (!first_only || math::far_from_zero_pos(t_root)) {
+					crossings.push((
+						t_root,
+						normal.normalize().insert_row(3, 0.0),
+						(0.0, 0.0),
+						None,
+					));
+
+					if first_only {
+						break;
+					}
+				}
+			}
+
+			t_previous = t;
+			value_previous = value;
+		}
+
+		crossings
+	}
+}
+
+impl Primitive for Implicit
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::closest_candidate(self.march(origin, direction, true), direction, transform)
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::candidate_intervals(self.march(origin, direction, false), transform)
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let (lower, upper) = self.field.get_extents();
+
+		(lower.insert_row(3, 1.0), upper.insert_row(3, 1.0))
+	}
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+
+use na::Vector3;
+
+use super::ScalarField;
+
+// Field sampled on a regular grid and reconstructed with Catmull-Rom
+// interpolation, which keeps the gradient continuous across cells so
+// shading stays smooth. Sampling happens once ahead of rendering, which lets
+// fields defined by Lua functions be evaluated from the worker threads
+// without touching the Lua state.
+#[derive(fmt::Debug)]
+pub struct SampledField
+{
+	lower: Vector3<f32>,
+	upper: Vector3<f32>,
+	cell_size: Vector3<f32>,
+	samples: [usize; 3],
+	values: Vec<f32>,
+}
+
+impl SampledField
+{
+	// Samples the field at the corners of resolution^3 cells spanning the bounds
+	pub fn from_fn<F, E>(
+		lower: Vector3<f32>,
+		upper: Vector3<f32>,
+		resolution: usize,
+		mut field: F,
+	) -> Result<Self, E>
+	where
+		F: FnMut(Vector3<f32>) -> Result<f32, E>,
+	{
+		let resolution = resolution.max(1);
+		let samples = [resolution + 1; 3];
+		let cell_size = (upper - lower) / resolution as f32;
+
+		let mut values = Vec::with_capacity(samples[0] * samples[1] * samples[2]);
+
+		for z in 0..samples[2] {
+			for y in 0..samples[1] {
+				for x in 0..samples[0] {
+					let offset = Vector3::new(x as f32, y as f32, z as f32);
+					values.push(field(lower + cell_size.component_mul(&offset))?);
+				}
+			}
+		}
+
+		Ok(SampledField {
+			lower: lower,
+			upper: upper,
+			cell_size: cell_size,
+			samples: samples,
+			values: values,
+		})
+	}
+
+	fn sample(&self, x: isize, y: isize, z: isize) -> f32
+	{
+		let clamp =
+			|index: isize, axis: usize| index.max(0).min(self.samples[axis] as isize - 1) as usize;
+
+		let (x, y, z) = (clamp(x, 0), clamp(y, 1), clamp(z, 2));
+
+		self.values[(z * self.samples[1] + y) * self.samples[0] + x]
+	}
+
+	// Splits a point into the index of its cell and the position in the cell
+	fn locate(&self, point: Vector3<f32>) -> ([isize; 3], [f32; 3])
+	{
+		let grid = (point - self.lower).component_div(&self.cell_size);
+
+		let mut cell = [0; 3];
+		let mut fraction = [0.0; 3];
+
+		for axis in 0..3 {
+			let floor = grid[axis].floor();
+			cell[axis] = floor as isize;
+			fraction[axis] = grid[axis] - floor;
+		}
+
+		(cell, fraction)
+	}
+
+	// Sums the 4x4x4 neighbourhood of the cell with a set of weights per axis
+	fn convolve(&self, cell: [isize; 3], weights: [[f32; 4]; 3]) -> f32
+	{
+		let mut total = 0.0;
+
+		for (k, weight_z) in weights[2].iter().enumerate() {
+			for (j, weight_y) in weights[1].iter().enumerate() {
+				for (i, weight_x) in weights[0].iter().enumerate() {
+					let value = self.sample(
+						cell[0] + i as isize - 1,
+						cell[1] + j as isize - 1,
+						cell[2] + k as isize - 1,
+					);
+
+					total += weight_x * weight_y * weight_z * value;
+				}
+			}
+		}
+
+		total
+	}
+}
+
+fn catmull_rom_weights(t: f32) -> [f32; 4]
+{
+	let t2 = t * t;
+	let t3 = t2 * t;
+
+	[
+		(-t3 + 2.0 * t2 - t) / 2.0,
+		(3.0 * t3 - 5.0 * t2 + 2.0) / 2.0,
+		(-3.0 * t3 + 4.0 * t2 + t) / 2.0,
+		(t3 - t2) / 2.0,
+	]
+}
+
+fn catmull_rom_derivative_weights(t: f32) -> [f32; 4]
+{
+	let t2 = t * t;
+
+	[
+		(-3.0 * t2 + 4.0 * t - 1.0) / 2.0,
+		(9.0 * t2 - 10.0 * t) / 2.0,
+		(-9.0 * t2 + 8.0 * t + 1.0) / 2.0,
+		(3.0 * t2 - 2.0 * t) / 2.0,
+	]
+}
+
+impl ScalarField for SampledField
+{
+	fn value(&self, point: Vector3<f32>) -> f32
+	{
+		let (cell, fraction) = self.locate(point);
+
+		self.convolve(
+			cell,
+			[
+				catmull_rom_weights(fraction[0]),
+				catmull_rom_weights(fraction[1]),
+				catmull_rom_weights(fraction[2]),
+			],
+		)
+	}
+
+	fn gradient(&self, point: Vector3<f32>) -> Vector3<f32>
+	{
+		let (cell, fraction) = self.locate(point);
+		let weights = [
+			catmull_rom_weights(fraction[0]),
+			catmull_rom_weights(fraction[1]),
+			catmull_rom_weights(fraction[2]),
+		];
+		let derivatives = [
+			catmull_rom_derivative_weights(fraction[0]),
+			catmull_rom_derivative_weights(fraction[1]),
+			catmull_rom_derivative_weights(fraction[2]),
+		];
+
+		Vector3::new(
+			self.convolve(cell, [derivatives[0], weights[1], weights[2]]) / self.cell_size.x,
+			self.convolve(cell, [weights[0], derivatives[1], weights[2]]) / self.cell_size.y,
+			self.convolve(cell, [weights[0], weights[1], derivatives[2]]) / self.cell_size.z,
+		)
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		(self.lower, self.upper)
+	}
+
+	fn step_size(&self) -> f32
+	{
+		self.cell_size.min() / 2.0
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn reproduces_linear_fields()
+	{
+		let field = SampledField::from_fn::<_, ()>(
+			Vector3::repeat(-1.0),
+			Vector3::repeat(1.0),
+			8,
+			|point| Ok(point.x + 2.0 * point.y - point.z),
+		)
+		.unwrap();
+
+		let point = Vector3::new(0.3, -0.2, 0.45);
+		assert!((field.value(point) - (0.3 - 0.4 - 0.45)).abs() < 0.0001);
+		assert!((field.gradient(point) - Vector3::new(1.0, 2.0, -1.0)).norm() < 0.0001);
+	}
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+use std::sync::Arc;
+
+use na::Matrix4;
+use na::Vector4;
+use primitives::primitive;
+use primitives::Primitive;
+use shading::Material;
+use util::math;
+use Grid;
+use Hit;
+use Interval;
+use Ray;
+
+// Placement of a prototype, a group of objects with its own grid that is
+// shared by every instance. Rays are moved into the prototype's space and
+// traced through its grid, so each copy only costs one object in the scene.
+#[derive(fmt::Debug)]
+pub struct Instance
+{
+	prototype: Arc<Grid>,
+}
+
+impl Instance
+{
+	pub fn new(prototype: Arc<Grid>) -> Self
+	{
+		Instance {
+			prototype: prototype,
+		}
+	}
+}
+
+impl Primitive for Instance
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		self.hit_with_material(ray, transform).map(|(hit, _)| hit)
+	}
+
+	// Prototypes are groups of objects rather than solids, only their surfaces
+	// take part in CSG
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		primitive::flat_interval(self.hit(ray, transform))
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let (lower, upper) = self.prototype.get_extents();
+
+		(lower.insert_row(3, 1.0), upper.insert_row(3, 1.0))
+	}
+
+	fn is_bounded(&self) -> bool
+	{
+		self.prototype.is_bounded()
+	}
+
+	// The objects of the prototype keep their own materials
+	fn hit_with_material(
+		&self,
+		ray: &Ray,
+		transform: Matrix4<f32>,
+	) -> Option<(Hit, Option<&dyn Material>)>
+	{
+		// The intersect is unchanged since the ray keeps its parameterization
+		let prototype_ray = ray.transformed(transform);
+		let (hit, material) = self.prototype.check_hit(&prototype_ray)?;
+
+		let hit = Hit {
+			normal: math::transform_normals(hit.normal, transform),
+			tangents: primitive::tangents_to_world(hit.tangents, transform),
+			..hit
+		};
+
+		Some((hit, Some(material)))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use na::Vector3;
+	use primitives::Sphere;
+	use shading::Phong;
+	use Object;
+
+	use super::*;
+
+	#[test]
+	fn copies_share_prototype()
+	{
+		let sphere = Object::new(
+			String::from("sphere"),
+			Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, 2.0, 1.0)),
+			Arc::new(Sphere::new(Vector3::zeros(), 1.0)),
+			Arc::new(Phong::new(
+				Vector3::repeat(1.0).into(),
+				Vector3::zeros().into(),
+				1.0.into(),
+			)),
+		);
+		let prototype = Arc::new(Grid::new(vec![Arc::new(sphere)]));
+
+		let left = Instance::new(Arc::clone(&prototype));
+		let right = Instance::new(prototype);
+
+		// Objects are given world to model transforms
+		let left_transform = Matrix4::new_translation(&Vector3::new(5.0, 0.0, 0.0));
+		let right_transform = Matrix4::new_translation(&Vector3::new(-5.0, 0.0, 0.0));
+
+		// The same ray reaches the prototype's objects through both copies
+		let ray = Ray::new(
+			Vector4::new(-5.0, 1.0, 10.0, 1.0),
+			Vector4::new(-5.0, 1.0, 9.0, 1.0),
+		);
+		let hit = left.hit(&ray, left_transform).unwrap();
+
+		assert!((hit.intersect - (10.0 - f32::sqrt(0.75))).abs() < 0.001);
+		assert!(hit.normal.y > 0.0 && hit.normal.z > 0.0);
+		assert!(right.hit(&ray, right_transform).is_none());
+
+		let ray = Ray::new(
+			Vector4::new(5.0, 0.0, 10.0, 1.0),
+			Vector4::new(5.0, 0.0, 9.0, 1.0),
+		);
+		assert!((right.hit(&ray, right_transform).unwrap().intersect - 9.0).abs() < 0.001);
+	}
+}
@@ -82,6 +82,7 @@ pub fn read_file(path: &str) -> Result<Mesh, Error>
 		vertices: Vec::new(),
 		normals: Vec::new(),
 		texture_coordinates: Vec::new(),
+		tangents: Vec::new(),
 		faces: Vec::new(),
 	};
 
@@ -129,7 +130,7 @@ fn parse_line(line: String, mesh: &mut Mesh) -> Result<(), String>
 				mesh.normals.push(normal);
 			},
 			"vt" => {
-				let texture_coordinate = parse_vertex_data(&line_parts)?;
+				let texture_coordinate = parse_texture_coordinate_data(&line_parts)?;
 				mesh.texture_coordinates.push(texture_coordinate);
 			},
 			"f" => {
@@ -165,6 +166,25 @@ fn parse_vertex_data(parts: &Vec<&str>) -> Result<Vector4<f32>, String>
 	Ok(Vector4::new(x, y, z, 1.0))
 }
 
+// The third component is optional and unused, only U and V are needed
+fn parse_texture_coordinate_data(parts: &[&str]) -> Result<Vector4<f32>, String>
+{
+	if parts.len() < 3 {
+		return Err(String::from(
+			"Texture coordinates with fewer than 2 components are not supported",
+		));
+	} else if parts.len() > 4 {
+		return Err(String::from(
+			"Texture coordinates with more than 3 components are not supported",
+		));
+	}
+
+	let u = parse_vertex_component(parts[1])?;
+	let v = parse_vertex_component(parts[2])?;
+
+	Ok(Vector4::new(u, v, 0.0, 0.0))
+}
+
 fn parse_vertex_component(part: &str) -> Result<f32, String>
 {
 	match part.parse::<f32>() {
@@ -211,6 +231,7 @@ fn parse_face_data(parts: &Vec<&str>) -> Result<Triangle, String>
 		vertices: vertices,
 		normals: normals,
 		texture_coordinates: texture_coordinates,
+		tangents: None,
 	})
 }
 
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+// Half-edge adjacency for a triangle mesh. The half-edges of face f are 3f,
+// 3f + 1 and 3f + 2, each running from one corner of the face to the next.
+pub struct HalfEdge
+{
+	pub origin: usize,
+	pub next: usize,
+	// Half-edge running the other way along the same edge, None on boundaries
+	// and where more than two faces meet at an edge
+	pub twin: Option<usize>,
+}
+
+pub struct HalfEdgeMesh
+{
+	pub half_edges: Vec<HalfEdge>,
+}
+
+impl HalfEdgeMesh
+{
+	pub fn new(faces: &[(usize, usize, usize)]) -> Self
+	{
+		let mut half_edges = Vec::with_capacity(faces.len() * 3);
+
+		for (index, &(a, b, c)) in faces.iter().enumerate() {
+			for (offset, &origin) in [a, b, c].iter().enumerate() {
+				half_edges.push(HalfEdge {
+					origin: origin,
+					next: index * 3 + (offset + 1) % 3,
+					twin: None,
+				});
+			}
+		}
+
+		let mut mesh = HalfEdgeMesh {
+			half_edges: half_edges,
+		};
+
+		let mut directed_edges = HashMap::new();
+		for index in 0..mesh.half_edges.len() {
+			directed_edges
+				.entry((mesh.origin(index), mesh.destination(index)))
+				.or_insert(index);
+		}
+
+		// Only half-edges that are each other's sole match are paired, which
+		// leaves non-manifold edges as boundaries
+		for index in 0..mesh.half_edges.len() {
+			let (origin, destination) = (mesh.origin(index), mesh.destination(index));
+
+			if directed_edges[&(origin, destination)] != index {
+				continue;
+			}
+
+			if let Some(&twin) = directed_edges.get(&(destination, origin)) {
+				mesh.half_edges[index].twin = Some(twin);
+			}
+		}
+
+		mesh
+	}
+
+	pub fn face(&self, half_edge: usize) -> usize
+	{
+		half_edge / 3
+	}
+
+	pub fn origin(&self, half_edge: usize) -> usize
+	{
+		self.half_edges[half_edge].origin
+	}
+
+	pub fn destination(&self, half_edge: usize) -> usize
+	{
+		self.half_edges[self.half_edges[half_edge].next].origin
+	}
+
+	// Corner of the face across from the half-edge
+	pub fn opposite(&self, half_edge: usize) -> usize
+	{
+		let next = self.half_edges[half_edge].next;
+
+		self.destination(next)
+	}
+
+	pub fn twin(&self, half_edge: usize) -> Option<usize>
+	{
+		self.half_edges[half_edge].twin
+	}
+
+	// One half-edge for every edge of the mesh
+	pub fn edges<'a>(&'a self) -> impl Iterator<Item = usize> + 'a
+	{
+		(0..self.half_edges.len()).filter(move |&index| match self.twin(index) {
+			Some(twin) => index < twin || self.twin(twin) != Some(index),
+			None => true,
+		})
+	}
+}
@@ -1,15 +1,25 @@
 mod file;
+mod half_edge;
+mod sampling;
+mod subdivision;
+mod tangents;
 
 use std::error;
 use std::f32;
 use std::fmt;
 use std::path::Path;
 
+use area_light;
 use na::Matrix4;
 use na::Vector4;
+use primitives::primitive;
+use primitives::primitive::Crossing;
+use primitives::primitive::Tangents;
 use primitives::Primitive;
+use rand::RngCore;
 use util::math;
 use Hit;
+use Interval;
 use Ray;
 
 use self::file::obj;
@@ -51,10 +61,12 @@ impl error::Error for Error {}
 #[derive(fmt::Debug)]
 pub struct Mesh
 {
-    vertices: Vec<Vector4<f32>>,
-    normals: Vec<Vector4<f32>>,
-    texture_coordinates: Vec<Vector4<f32>>,
-    faces: Vec<Triangle>,
+	vertices: Vec<Vector4<f32>>,
+	normals: Vec<Vector4<f32>>,
+	texture_coordinates: Vec<Vector4<f32>>,
+	// Averaged per face corner, see generate_tangents
+	tangents: Vec<Tangents>,
+	faces: Vec<Triangle>,
 }
 
 #[derive(fmt::Debug)]
@@ -63,6 +75,7 @@ struct Triangle
 	pub vertices: (usize, usize, usize),
 	pub normals: Option<(usize, usize, usize)>,
 	pub texture_coordinates: Option<(usize, usize, usize)>,
+	pub tangents: Option<(usize, usize, usize)>,
 }
 
 impl Mesh
@@ -76,7 +89,10 @@ impl Mesh
 
 			match file_extension.as_ref() {
 				obj::FILE_EXTENSION => {
-					return obj::read_file(file_name).map_err(Error::read_error);
+					let mut mesh = obj::read_file(file_name).map_err(Error::read_error)?;
+					mesh.generate_tangents();
+
+					return Ok(mesh);
 				},
 				_ => {
 					// No-op, error returned later
@@ -89,87 +105,162 @@ impl Mesh
 			file_name
 		)))
 	}
-}
 
-impl Primitive for Mesh
-{
-	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	// Where the ray crosses the face, as the intersect and the barycentric
+	// coordinates of the second and third vertices. Moller-Trumbore, kept in
+	// four dimensions as the mesh's vertices are.
+	#[inline(always)]
+	fn crossing(
+		&self,
+		face: &Triangle,
+		origin: Vector4<f32>,
+		vector: Vector4<f32>,
+	) -> Option<(f32, f32, f32)>
 	{
-		let point = transform * ray.point();
-		let origin = transform * ray.origin();
-		let vector = point - origin;
+		let v1 = &self.vertices[face.vertices.0];
+		let v2 = &self.vertices[face.vertices.1];
+		let v3 = &self.vertices[face.vertices.2];
 
-		let mut intersect = f32::INFINITY;
-		let mut normal = Vector4::new(0.0, 0.0, 0.0, 0.0);
+		let edge1 = v2 - v1;
+		let edge2 = v3 - v1;
 
-		for face in &self.faces {
-			// Moller-Trombore intersection algorithm
+		let h = math::cross_4d(vector, edge2);
+		let a = edge1.dot(&h);
 
-			let v1 = &self.vertices[face.vertices.0];
-			let v2 = &self.vertices[face.vertices.1];
-			let v3 = &self.vertices[face.vertices.2];
+		if math::near_zero(a) {
+			return None;
+		}
 
-			let edge1 = v2 - v1;
-			let edge2 = v3 - v1;
+		let f = 1.0 / a;
+		let s = origin - v1;
+		let u = f * s.dot(&h);
 
-			let h = math::cross_4d(vector, edge2);
-			let a = edge1.dot(&h);
+		if !(0.0..=1.0).contains(&u) {
+			return None;
+		}
 
-			if math::near_zero(a) {
-				continue;
-			}
+		let q = math::cross_4d(s, edge1);
+		let v = f * vector.dot(&q);
 
-			let f = 1.0 / a;
-			let s = origin - v1;
-			let u = f * s.dot(&h);
+		if v < 0.0 || u + v > 1.0 {
+			return None;
+		}
 
-			if u < 0.0 || u > 1.0 {
-				continue;
-			}
+		Some((f * edge2.dot(&q), u, v))
+	}
 
-			let q = math::cross_4d(s, edge1);
-			let v = f * vector.dot(&q);
+	// Interpolated vertex normal, or the face's own normal following its
+	// winding when it has none
+	fn normal(&self, face: &Triangle, u: f32, v: f32) -> Vector4<f32>
+	{
+		match face.normals {
+			Some(normals) => {
+				let n1 = &self.normals[normals.0];
+				let n2 = &self.normals[normals.1];
+				let n3 = &self.normals[normals.2];
+
+				(n1 * (1.0 - u - v)) + (n2 * u) + (n3 * v)
+			},
+			None => {
+				let v1 = self.vertices[face.vertices.0];
+				let v2 = self.vertices[face.vertices.1];
+				let v3 = self.vertices[face.vertices.2];
+
+				math::cross_4d(v2 - v1, v3 - v1)
+			},
+		}
+	}
 
-			if v < 0.0 || u + v > 1.0 {
-				continue;
-			}
+	// Every face the ray crosses, including those behind the origin. Normals
+	// follow the winding of the face so closed meshes pair up into intervals.
+	fn candidates(&self, origin: Vector4<f32>, vector: Vector4<f32>) -> Vec<Crossing>
+	{
+		self.faces
+			.iter()
+			.filter_map(|face| {
+				let (t, u, v) = self.crossing(face, origin, vector)?;
+				let (uv, tangents) = self.surface_coordinates(face, u, v);
+
+				Some((t, self.normal(face, u, v), uv, tangents))
+			})
+			.collect()
+	}
+
+	// Texture coordinates and tangents interpolated across the face at
+	// barycentric u and v. Faces without texture coordinates, or whose
+	// coordinates collapse to a line, have no tangents.
+	fn surface_coordinates(&self, face: &Triangle, u: f32, v: f32)
+		-> ((f32, f32), Option<Tangents>)
+	{
+		let indices = match face.texture_coordinates {
+			Some(indices) => indices,
+			None => return ((0.0, 0.0), None),
+		};
 
-			let t = f * edge2.dot(&q);
+		let uv1 = &self.texture_coordinates[indices.0];
+		let uv2 = &self.texture_coordinates[indices.1];
+		let uv3 = &self.texture_coordinates[indices.2];
 
-			if !math::far_from_zero_pos(t) {
-				continue;
-			}
+		let uv = (uv1 * (1.0 - u - v)) + (uv2 * u) + (uv3 * v);
 
-			if t < intersect {
-				intersect = t;
+		let tangents = face.tangents.map(|tangents| {
+			let t1 = self.tangents[tangents.0];
+			let t2 = self.tangents[tangents.1];
+			let t3 = self.tangents[tangents.2];
 
-				if let Some(normals) = face.normals {
-					let n1 = &self.normals[normals.0];
-					let n2 = &self.normals[normals.1];
-					let n3 = &self.normals[normals.2];
+			(
+				(t1.0 * (1.0 - u - v)) + (t2.0 * u) + (t3.0 * v),
+				(t1.1 * (1.0 - u - v)) + (t2.1 * u) + (t3.1 * v),
+			)
+		});
+
+		((uv.x, uv.y), tangents)
+	}
+}
 
-					normal = ((n1 * (1.0 - u - v)) + (n2 * u) + (n3 * v)).normalize();
-				} else {
-					normal = math::cross_4d(v2 - v1, v3 - v1).normalize();
+impl Primitive for Mesh
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let vector = transform * ray.point() - origin;
+
+		// Only the closest face needs its surface coordinates worked out
+		let mut intersect = f32::INFINITY;
+		let mut closest = None;
+
+		for face in &self.faces {
+			if let Some((t, u, v)) = self.crossing(face, origin, vector) {
+				if t < intersect && math::far_from_zero_pos(t) {
+					intersect = t;
+					closest = Some((face, u, v));
 				}
 			}
 		}
 
-		if intersect < f32::INFINITY {
-			if vector.dot(&normal) > 0.0 {
-				normal = -normal;
-			}
-
-			normal = math::transform_normals(normal, transform);
+		let (face, u, v) = closest?;
 
-			Some(Hit {
-				normal: normal,
-				intersect: intersect,
-				uv: (0.0, 0.0),
-			})
-		} else {
-			None
+		let mut normal = self.normal(face, u, v);
+		if vector.dot(&normal) > 0.0 {
+			normal = -normal;
 		}
+
+		let (uv, tangents) = self.surface_coordinates(face, u, v);
+
+		Some(Hit::new(
+			intersect,
+			math::transform_normals(normal, transform),
+			uv,
+			primitive::tangents_to_world(tangents, transform),
+		))
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let vector = transform * ray.point() - origin;
+
+		primitive::candidate_intervals(self.candidates(origin, vector), transform)
 	}
 
 	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
@@ -189,4 +280,30 @@ impl Primitive for Mesh
 
 		(min, max)
 	}
+
+	fn sample_surface(
+		&self,
+		count: usize,
+		rng: &mut dyn RngCore,
+	) -> Option<Vec<(Vector4<f32>, Vector4<f32>)>>
+	{
+		Some(self.sample_faces(count, rng))
+	}
+
+	fn emitting_surface(&self) -> Option<area_light::Surface>
+	{
+		let triangles = self
+			.faces
+			.iter()
+			.map(|face| {
+				[
+					self.vertices[face.vertices.0],
+					self.vertices[face.vertices.1],
+					self.vertices[face.vertices.2],
+				]
+			})
+			.collect();
+
+		Some(area_light::Surface::Triangles(triangles))
+	}
 }
@@ -0,0 +1,70 @@
+use na::Vector4;
+use rand::Rng;
+use rand::RngCore;
+use util::math;
+
+use super::Mesh;
+
+impl Mesh
+{
+	// Faces are picked in proportion to their area and points spread evenly
+	// over each face, so the points are uniform over the whole surface
+	pub(super) fn sample_faces(
+		&self,
+		count: usize,
+		rng: &mut dyn RngCore,
+	) -> Vec<(Vector4<f32>, Vector4<f32>)>
+	{
+		// Running total of face areas
+		let mut cumulative_areas = Vec::with_capacity(self.faces.len());
+		let mut total_area = 0.0;
+
+		for face in &self.faces {
+			let v1 = self.vertices[face.vertices.0];
+			let v2 = self.vertices[face.vertices.1];
+			let v3 = self.vertices[face.vertices.2];
+
+			total_area += math::cross_4d(v2 - v1, v3 - v1).norm() / 2.0;
+			cumulative_areas.push(total_area);
+		}
+
+		if total_area <= 0.0 {
+			return Vec::new();
+		}
+
+		(0..count)
+			.map(|_| {
+				let target = rng.gen::<f32>() * total_area;
+				let index = usize::min(
+					cumulative_areas.partition_point(|&area| area < target),
+					self.faces.len() - 1,
+				);
+				let face = &self.faces[index];
+
+				// Uniform barycentric coordinates, folding the square onto the triangle
+				let (mut u, mut v) = (rng.gen::<f32>(), rng.gen::<f32>());
+				if u + v > 1.0 {
+					u = 1.0 - u;
+					v = 1.0 - v;
+				}
+
+				let v1 = self.vertices[face.vertices.0];
+				let v2 = self.vertices[face.vertices.1];
+				let v3 = self.vertices[face.vertices.2];
+
+				let position = v1 * (1.0 - u - v) + v2 * u + v3 * v;
+
+				let normal = match face.normals {
+					Some(normals) => {
+						self.normals[normals.0] * (1.0 - u - v)
+							+ self.normals[normals.1] * u
+							+ self.normals[normals.2] * v
+					},
+					None => math::cross_4d(v2 - v1, v3 - v1),
+				};
+
+				(position, normal.normalize())
+			})
+			.collect()
+	}
+}
@@ -0,0 +1,310 @@
+use std::collections::HashSet;
+
+use na::Vector4;
+use util::math;
+
+use super::half_edge::HalfEdgeMesh;
+use super::Mesh;
+use super::Triangle;
+
+// Loop subdivision of a triangle mesh. Boundary edges and creases follow the
+// crease rules so they stay sharp, every other edge and vertex is smoothed.
+// Creases are the edges whose faces meet at more than the crease angle (in
+// degrees), or none if no angle is given.
+impl Mesh
+{
+	pub fn subdivide(&mut self, levels: u32, crease_angle: Option<f32>)
+	{
+		if levels == 0 {
+			return;
+		}
+
+		let crease_cosine = crease_angle.map(|angle| angle.to_radians().cos());
+
+		let mut creases = HashSet::new();
+		if let Some(crease_cosine) = crease_cosine {
+			let topology = HalfEdgeMesh::new(&self.face_vertices());
+			let face_normals = self.face_normals();
+
+			for edge in topology.edges() {
+				if let Some(twin) = topology.twin(edge) {
+					let normal = face_normals[topology.face(edge)].normalize();
+					let twin_normal = face_normals[topology.face(twin)].normalize();
+
+					if normal.dot(&twin_normal) < crease_cosine {
+						creases.insert(edge_key(topology.origin(edge), topology.destination(edge)));
+					}
+				}
+			}
+		}
+
+		for _ in 0..levels {
+			creases = self.subdivide_once(&creases);
+		}
+
+		self.compute_normals(crease_cosine);
+		self.generate_tangents();
+	}
+
+	fn face_vertices(&self) -> Vec<(usize, usize, usize)>
+	{
+		self.faces.iter().map(|face| face.vertices).collect()
+	}
+
+	// Unnormalized so larger faces carry more weight when averaged
+	fn face_normals(&self) -> Vec<Vector4<f32>>
+	{
+		self.faces
+			.iter()
+			.map(|face| {
+				let (a, b, c) = face.vertices;
+				math::cross_4d(
+					self.vertices[b] - self.vertices[a],
+					self.vertices[c] - self.vertices[a],
+				)
+			})
+			.collect()
+	}
+
+	// Splits every face into four and returns the creases of the new mesh
+	fn subdivide_once(&mut self, creases: &HashSet<(usize, usize)>) -> HashSet<(usize, usize)>
+	{
+		let faces = self.face_vertices();
+		let topology = HalfEdgeMesh::new(&faces);
+		let vertex_count = self.vertices.len();
+
+		let mut vertices = self.vertices.clone();
+		let mut edge_vertices = vec![0; topology.half_edges.len()];
+		let mut neighbours = vec![Vec::new(); vertex_count];
+		let mut crease_neighbours = vec![Vec::new(); vertex_count];
+		let mut new_creases = HashSet::new();
+
+		for edge in topology.edges() {
+			let (a, b) = (topology.origin(edge), topology.destination(edge));
+			let twin = topology.twin(edge);
+			let is_crease = twin.is_none() || creases.contains(&edge_key(a, b));
+
+			let point = match twin {
+				Some(twin) if !is_crease => {
+					(self.vertices[a] + self.vertices[b]) * (3.0 / 8.0)
+						+ (self.vertices[topology.opposite(edge)]
+							+ self.vertices[topology.opposite(twin)])
+							* (1.0 / 8.0)
+				},
+				_ => (self.vertices[a] + self.vertices[b]) * 0.5,
+			};
+
+			let index = vertices.len();
+			vertices.push(point);
+
+			edge_vertices[edge] = index;
+			if let Some(twin) = twin {
+				edge_vertices[twin] = index;
+			}
+
+			neighbours[a].push(b);
+			neighbours[b].push(a);
+
+			if is_crease {
+				crease_neighbours[a].push(b);
+				crease_neighbours[b].push(a);
+
+				new_creases.insert(edge_key(a, index));
+				new_creases.insert(edge_key(index, b));
+			}
+		}
+
+		for vertex in 0..vertex_count {
+			let position = self.vertices[vertex];
+			let sum_of = |indices: &[usize]| {
+				indices
+					.iter()
+					.fold(Vector4::zeros(), |sum, &index| sum + self.vertices[index])
+			};
+
+			vertices[vertex] = match crease_neighbours[vertex].len() {
+				// Smooth vertices, and darts where a crease fades out
+				0 | 1 if !neighbours[vertex].is_empty() => {
+					let valence = neighbours[vertex].len() as f32;
+					let beta = if valence > 3.0 {
+						3.0 / (8.0 * valence)
+					} else {
+						3.0 / 16.0
+					};
+
+					position * (1.0 - valence * beta) + sum_of(&neighbours[vertex]) * beta
+				},
+				2 => position * 0.75 + sum_of(&crease_neighbours[vertex]) * 0.125,
+				// Corners where three or more creases meet stay put
+				_ => position,
+			};
+		}
+
+		let mut new_faces = Vec::with_capacity(self.faces.len() * 4);
+		let texture_coordinates = &mut self.texture_coordinates;
+
+		for (index, face) in self.faces.iter().enumerate() {
+			let (a, b, c) = face.vertices;
+			let ab = edge_vertices[index * 3];
+			let bc = edge_vertices[index * 3 + 1];
+			let ca = edge_vertices[index * 3 + 2];
+
+			// Texture coordinates are split linearly, each face gets its own
+			// midpoints so seams are kept
+			let uvs = face.texture_coordinates.map(|(ua, ub, uc)| {
+				let mut midpoint = |first: usize, second: usize| {
+					let uv = (texture_coordinates[first] + texture_coordinates[second]) * 0.5;
+					texture_coordinates.push(uv);
+					texture_coordinates.len() - 1
+				};
+
+				(
+					ua,
+					ub,
+					uc,
+					midpoint(ua, ub),
+					midpoint(ub, uc),
+					midpoint(uc, ua),
+				)
+			});
+
+			let corners = [
+				(
+					(a, ab, ca),
+					uvs.map(|(ua, _, _, uab, _, uca)| (ua, uab, uca)),
+				),
+				(
+					(ab, b, bc),
+					uvs.map(|(_, ub, _, uab, ubc, _)| (uab, ub, ubc)),
+				),
+				(
+					(ca, bc, c),
+					uvs.map(|(_, _, uc, _, ubc, uca)| (uca, ubc, uc)),
+				),
+				(
+					(ab, bc, ca),
+					uvs.map(|(_, _, _, uab, ubc, uca)| (uab, ubc, uca)),
+				),
+			];
+
+			for &(vertices, texture_coordinates) in corners.iter() {
+				new_faces.push(Triangle {
+					vertices: vertices,
+					normals: None,
+					texture_coordinates: texture_coordinates,
+					tangents: None,
+				});
+			}
+		}
+
+		self.vertices = vertices;
+		self.faces = new_faces;
+
+		new_creases
+	}
+
+	// Gives every face corner the average normal of the faces around its
+	// vertex, leaving out faces across a crease so creases shade sharp
+	fn compute_normals(&mut self, crease_cosine: Option<f32>)
+	{
+		let face_normals = self.face_normals();
+
+		let mut vertex_faces = vec![Vec::new(); self.vertices.len()];
+		for (index, face) in self.faces.iter().enumerate() {
+			let (a, b, c) = face.vertices;
+			vertex_faces[a].push(index);
+			vertex_faces[b].push(index);
+			vertex_faces[c].push(index);
+		}
+
+		self.normals.clear();
+
+		for index in 0..self.faces.len() {
+			let (a, b, c) = self.faces[index].vertices;
+			let face_normal = face_normals[index].normalize();
+
+			let mut corner_normal = |vertex: usize| {
+				let normal = vertex_faces[vertex]
+					.iter()
+					.filter(|&&other| match crease_cosine {
+						Some(crease_cosine) => {
+							face_normals[other].normalize().dot(&face_normal) >= crease_cosine
+						},
+						None => true,
+					})
+					.fold(Vector4::zeros(), |sum, &other| sum + face_normals[other]);
+
+				self.normals.push(normal.normalize());
+				self.normals.len() - 1
+			};
+
+			let normals = (corner_normal(a), corner_normal(b), corner_normal(c));
+			self.faces[index].normals = Some(normals);
+		}
+	}
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize)
+{
+	(a.min(b), a.max(b))
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn tetrahedron() -> Mesh
+	{
+		let vertices = vec![
+			Vector4::new(1.0, 1.0, 1.0, 1.0),
+			Vector4::new(1.0, -1.0, -1.0, 1.0),
+			Vector4::new(-1.0, 1.0, -1.0, 1.0),
+			Vector4::new(-1.0, -1.0, 1.0, 1.0),
+		];
+
+		let faces = [(0, 1, 2), (0, 3, 1), (0, 2, 3), (1, 3, 2)];
+
+		Mesh {
+			vertices: vertices,
+			normals: Vec::new(),
+			texture_coordinates: Vec::new(),
+			tangents: Vec::new(),
+			faces: faces
+				.iter()
+				.map(|&vertices| Triangle {
+					vertices: vertices,
+					normals: None,
+					texture_coordinates: None,
+					tangents: None,
+				})
+				.collect(),
+		}
+	}
+
+	#[test]
+	fn smooth_subdivision_shrinks_towards_limit_surface()
+	{
+		let mut mesh = tetrahedron();
+		mesh.subdivide(2, None);
+
+		// Each level splits faces in four and adds a vertex per edge
+		assert_eq!(mesh.faces.len(), 4 * 16);
+		assert_eq!(mesh.vertices.len(), 4 + 6 + 24);
+
+		// Smoothing pulls the corners in, the mesh stays symmetric
+		let corner = mesh.vertices[0];
+		assert!(corner.x < 1.0 && (corner.x - corner.y).abs() < 0.0001);
+		assert_eq!(mesh.normals.len(), mesh.faces.len() * 3);
+	}
+
+	#[test]
+	fn creased_corners_stay_fixed()
+	{
+		let mut mesh = tetrahedron();
+		mesh.subdivide(1, Some(30.0));
+
+		// Every edge of a tetrahedron is a crease, so its corners do not move
+		assert_eq!(mesh.vertices[0], Vector4::new(1.0, 1.0, 1.0, 1.0));
+	}
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use na::Vector4;
+use primitives::primitive::Tangents;
+
+use super::Mesh;
+
+impl Mesh
+{
+	// Gives each face corner the average tangents of the faces that share its
+	// vertex, normal and texture coordinate, so a normal map follows the
+	// smooth shading rather than the facets. Faces whose tangents can't be
+	// found from their texture coordinates are left out.
+	pub(super) fn generate_tangents(&mut self)
+	{
+		self.tangents.clear();
+
+		let mut corners: HashMap<(usize, Option<usize>, usize), usize> = HashMap::new();
+		let mut counts = Vec::new();
+
+		for index in 0..self.faces.len() {
+			let face = &self.faces[index];
+
+			let (texture_coordinates, (dpdu, dpdv)) =
+				match (face.texture_coordinates, self.face_tangents(index)) {
+					(Some(texture_coordinates), Some(tangents)) => (texture_coordinates, tangents),
+					_ => continue,
+				};
+
+			let normals = face.normals;
+			let keys = [
+				(
+					face.vertices.0,
+					normals.map(|normals| normals.0),
+					texture_coordinates.0,
+				),
+				(
+					face.vertices.1,
+					normals.map(|normals| normals.1),
+					texture_coordinates.1,
+				),
+				(
+					face.vertices.2,
+					normals.map(|normals| normals.2),
+					texture_coordinates.2,
+				),
+			];
+
+			let mut indices = [0; 3];
+
+			for (corner, key) in keys.iter().enumerate() {
+				let tangents = &mut self.tangents;
+				let index = *corners.entry(*key).or_insert_with(|| {
+					tangents.push((Vector4::zeros(), Vector4::zeros()));
+					counts.push(0.0);
+					tangents.len() - 1
+				});
+
+				self.tangents[index].0 += dpdu;
+				self.tangents[index].1 += dpdv;
+				counts[index] += 1.0;
+
+				indices[corner] = index;
+			}
+
+			self.faces[index].tangents = Some((indices[0], indices[1], indices[2]));
+		}
+
+		// Averaged rather than normalized, the lengths are needed for filtering
+		for (tangents, count) in self.tangents.iter_mut().zip(counts) {
+			tangents.0 /= count;
+			tangents.1 /= count;
+		}
+	}
+
+	// Derivatives of the position along U and V over a face, from how its
+	// texture coordinates change along its edges
+	fn face_tangents(&self, index: usize) -> Option<Tangents>
+	{
+		let face = &self.faces[index];
+		let indices = face.texture_coordinates?;
+
+		let uv1 = &self.texture_coordinates[indices.0];
+		let uv2 = &self.texture_coordinates[indices.1];
+		let uv3 = &self.texture_coordinates[indices.2];
+
+		let (du1, dv1) = (uv2.x - uv1.x, uv2.y - uv1.y);
+		let (du2, dv2) = (uv3.x - uv1.x, uv3.y - uv1.y);
+		let determinant = du1 * dv2 - dv1 * du2;
+
+		if determinant.abs() < 1e-12 {
+			return None;
+		}
+
+		let edge1 = self.vertices[face.vertices.1] - self.vertices[face.vertices.0];
+		let edge2 = self.vertices[face.vertices.2] - self.vertices[face.vertices.0];
+
+		let dpdu = (edge1 * dv2 - edge2 * dv1) / determinant;
+		let dpdv = (edge2 * du1 - edge1 * du2) / determinant;
+
+		Some((dpdu, dpdv))
+	}
+}
@@ -1,11 +1,39 @@
+pub use self::bezier::BezierPatch;
 pub use self::bounding_box::BoundingBox;
+pub use self::capsule::Capsule;
+pub use self::cone::Cone;
+pub use self::csg::Csg;
 pub use self::cube::Cube;
+pub use self::curve::Curve;
+pub use self::cylinder::Cylinder;
+pub use self::disk::Disk;
+pub use self::heightfield::Heightfield;
+pub use self::implicit::Implicit;
+pub use self::instance::Instance;
 pub use self::mesh::Mesh;
+pub use self::plane::Plane;
 pub use self::primitive::Primitive;
+pub use self::rectangle::Rectangle;
+pub use self::sdf::Sdf;
 pub use self::sphere::Sphere;
+pub use self::torus::Torus;
 
+pub mod bezier;
 pub mod bounding_box;
+pub mod capsule;
+pub mod cone;
+pub mod csg;
 pub mod cube;
+pub mod curve;
+pub mod cylinder;
+pub mod disk;
+pub mod heightfield;
+pub mod implicit;
+pub mod instance;
 pub mod mesh;
+pub mod plane;
 pub mod primitive;
+pub mod rectangle;
+pub mod sdf;
 pub mod sphere;
+pub mod torus;
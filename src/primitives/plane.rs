@@ -0,0 +1,214 @@
+use std::f32;
+use std::fmt;
+
+use na::Matrix4;
+use na::Vector4;
+use primitives::primitive;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+// Infinite plane through the origin with a +Y normal, UVs are the X/Z model
+// space coordinates so textures tile once per unit. In CSG the plane bounds
+// the half-space below it.
+#[derive(fmt::Debug)]
+pub struct Plane;
+
+impl Plane
+{
+	pub fn new() -> Self
+	{
+		Plane
+	}
+}
+
+impl Default for Plane
+{
+	fn default() -> Self
+	{
+		Plane::new()
+	}
+}
+
+impl Primitive for Plane
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let (intersect, contact_point, normal) = hit_xz_plane(ray, transform)?;
+
+		Some(Hit::new(
+			intersect,
+			math::transform_normals(normal, transform),
+			(contact_point.x, contact_point.z),
+			primitive::tangents_to_world(Some((Vector4::x(), Vector4::z())), transform),
+		))
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		let infinite_hit = |intersect: f32| {
+			Hit::new(
+				intersect,
+				Vector4::new(0.0, 0.0, 0.0, 0.0),
+				(0.0, 0.0),
+				None,
+			)
+		};
+
+		// Rays parallel to the plane are either always or never below it
+		if math::near_zero(direction.y) {
+			return if origin.y < 0.0 {
+				vec![Interval::new(
+					infinite_hit(f32::NEG_INFINITY),
+					infinite_hit(f32::INFINITY),
+				)]
+			} else {
+				Vec::new()
+			};
+		}
+
+		let t = -origin.y / direction.y;
+		let contact_point = origin + (t * direction);
+
+		let surface_hit = Hit::new(
+			t,
+			math::transform_normals(Vector4::new(0.0, 1.0, 0.0, 0.0), transform),
+			(contact_point.x, contact_point.z),
+			primitive::tangents_to_world(Some((Vector4::x(), Vector4::z())), transform),
+		);
+
+		if direction.y < 0.0 {
+			vec![Interval::new(surface_hit, infinite_hit(f32::INFINITY))]
+		} else {
+			vec![Interval::new(infinite_hit(f32::NEG_INFINITY), surface_hit)]
+		}
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		(
+			Vector4::new(f32::NEG_INFINITY, 0.0, f32::NEG_INFINITY, 1.0),
+			Vector4::new(f32::INFINITY, 0.0, f32::INFINITY, 1.0),
+		)
+	}
+
+	fn is_bounded(&self) -> bool
+	{
+		false
+	}
+}
+
+// Intersects a ray with the model space Y = 0 plane, returning the intersect,
+// the model space contact point and a normal facing back towards the ray
+pub fn hit_xz_plane(ray: &Ray, transform: Matrix4<f32>)
+	-> Option<(f32, Vector4<f32>, Vector4<f32>)>
+{
+	let point = transform * ray.point();
+	let origin = transform * ray.origin();
+	let direction = point - origin;
+
+	if math::near_zero(direction.y) {
+		return None;
+	}
+
+	let t = -origin.y / direction.y;
+
+	if !math::far_from_zero_pos(t) {
+		return None;
+	}
+
+	let contact_point = origin + (t * direction);
+
+	let normal = if direction.y > 0.0 {
+		Vector4::new(0.0, -1.0, 0.0, 0.0)
+	} else {
+		Vector4::new(0.0, 1.0, 0.0, 0.0)
+	};
+
+	Some((t, contact_point, normal))
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::sync::Arc;
+
+	use na::Vector3;
+	use primitives::Sphere;
+	use shading::Phong;
+	use Grid;
+	use Object;
+
+	use super::*;
+
+	#[test]
+	fn uvs_are_model_space_coordinates()
+	{
+		let ray = Ray::new(
+			Vector4::new(2.5, 4.0, -3.0, 1.0),
+			Vector4::new(2.5, 3.0, -3.0, 1.0),
+		);
+		let hit = Plane::new().hit(&ray, Matrix4::identity()).unwrap();
+
+		assert!((hit.intersect - 4.0).abs() < 0.001);
+		assert!((hit.uv.0 - 2.5).abs() < 0.001 && (hit.uv.1 + 3.0).abs() < 0.001);
+
+		// Rays running along the plane never reach it
+		let ray = Ray::new(
+			Vector4::new(0.0, 1.0, 0.0, 1.0),
+			Vector4::new(1.0, 1.0, 0.0, 1.0),
+		);
+		assert!(Plane::new().hit(&ray, Matrix4::identity()).is_none());
+	}
+
+	#[test]
+	fn found_outside_the_grid()
+	{
+		let material = || {
+			Arc::new(Phong::new(
+				Vector3::repeat(1.0).into(),
+				Vector3::zeros().into(),
+				1.0.into(),
+			))
+		};
+
+		// The plane lies at Y = -1, below the sphere
+		let sphere = Object::new(
+			String::from("sphere"),
+			Matrix4::identity(),
+			Arc::new(Sphere::new(Vector3::zeros(), 1.0)),
+			material(),
+		);
+		let plane = Object::new(
+			String::from("plane"),
+			Matrix4::new_translation(&Vector3::new(0.0, -1.0, 0.0)),
+			Arc::new(Plane::new()),
+			material(),
+		);
+		assert!(!plane.is_bounded());
+
+		let grid = Grid::new(vec![Arc::new(sphere), Arc::new(plane)]);
+		assert!(!grid.is_bounded());
+
+		// Far from the sphere, so the ray never enters the grid's cells
+		let ray = Ray::new(
+			Vector4::new(50.0, 4.0, 50.0, 1.0),
+			Vector4::new(50.0, 3.0, 50.0, 1.0),
+		);
+		let (hit, _) = grid.check_hit(&ray).unwrap();
+		assert!((hit.intersect - 5.0).abs() < 0.001);
+
+		// The sphere is still found in front of the plane
+		let ray = Ray::new(
+			Vector4::new(0.0, 4.0, 0.0, 1.0),
+			Vector4::new(0.0, 3.0, 0.0, 1.0),
+		);
+		let (hit, _) = grid.check_hit(&ray).unwrap();
+		assert!((hit.intersect - 3.0).abs() < 0.001);
+	}
+}
@@ -1,12 +1,137 @@
 use std::fmt;
 
+use area_light;
 use na::Matrix4;
 use na::Vector4;
+use rand::RngCore;
+use shading::Material;
+use util::math;
 use Hit;
+use Interval;
 use Ray;
 
+// Derivatives of the position on a surface with respect to U and V
+pub type Tangents = (Vector4<f32>, Vector4<f32>);
+
+// Point where a ray crosses a surface: the intersect, the model space normal
+// pointing out of the surface, the UV coordinates and, when known, the model
+// space tangents
+pub type Crossing = (f32, Vector4<f32>, (f32, f32), Option<Tangents>);
+
 pub trait Primitive: Send + Sync + fmt::Debug
 {
 	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>;
+
+	// Every span of the ray inside the primitive in ascending order, including
+	// spans behind the ray origin, used to combine primitives with CSG
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>;
+
 	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>);
+
+	// Unbounded primitives (e.g. infinite planes) are kept out of the grid and
+	// tested against every ray instead
+	fn is_bounded(&self) -> bool
+	{
+		true
+	}
+
+	// Primitives built from other primitives can carry their own materials,
+	// None means the material of the object is used
+	fn hit_with_material(
+		&self,
+		ray: &Ray,
+		transform: Matrix4<f32>,
+	) -> Option<(Hit, Option<&dyn Material>)>
+	{
+		self.hit(ray, transform).map(|hit| (hit, None))
+	}
+
+	// Points spread uniformly over the surface in model space, each with the
+	// surface normal there, used to scatter objects over the primitive. None
+	// for primitives that can't be sampled.
+	fn sample_surface(
+		&self,
+		_count: usize,
+		_rng: &mut dyn RngCore,
+	) -> Option<Vec<(Vector4<f32>, Vector4<f32>)>>
+	{
+		None
+	}
+
+	// Surface sampled for points to light the scene from when the primitive's
+	// material gives off light, in model space. None for primitives that only
+	// glow where they are seen.
+	fn emitting_surface(&self) -> Option<area_light::Surface>
+	{
+		None
+	}
+}
+
+// Picks the nearest crossing in front of the ray and builds a Hit from it,
+// normals are flipped to face the ray so open surfaces shade from both sides
+pub fn closest_candidate(
+	candidates: Vec<Crossing>,
+	direction: Vector4<f32>,
+	transform: Matrix4<f32>,
+) -> Option<Hit>
+{
+	let (intersect, mut normal, uv, tangents) = candidates
+		.into_iter()
+		.filter(|candidate| math::far_from_zero_pos(candidate.0))
+		.min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())?;
+
+	if normal.dot(&direction) > 0.0 {
+		normal = -normal;
+	}
+
+	Some(Hit::new(
+		intersect,
+		math::transform_normals(normal, transform),
+		uv,
+		tangents_to_world(tangents, transform),
+	))
+}
+
+// Pairs up the crossings of a closed surface into entry and exit intervals
+pub fn candidate_intervals<'a>(
+	mut candidates: Vec<Crossing>,
+	transform: Matrix4<f32>,
+) -> Vec<Interval<'a>>
+{
+	candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+	let to_hit = |(intersect, normal, uv, tangents): Crossing| {
+		Hit::new(
+			intersect,
+			math::transform_normals(normal, transform),
+			uv,
+			tangents_to_world(tangents, transform),
+		)
+	};
+
+	candidates
+		.chunks(2)
+		.filter(|pair| pair.len() == 2)
+		.map(|pair| Interval::new(to_hit(pair[0]), to_hit(pair[1])))
+		.collect()
+}
+
+// Tangents follow the surface, so unlike normals they go from model to world
+// space with the inverse of the world to model transform
+pub fn tangents_to_world(tangents: Option<Tangents>, transform: Matrix4<f32>) -> Option<Tangents>
+{
+	let (dpdu, dpdv) = tangents?;
+	let model_to_world = transform.try_inverse()?;
+
+	Some((model_to_world * dpdu, model_to_world * dpdv))
+}
+
+// Flat primitives enclose no volume, so a hit is reported as an empty
+// interval that enters and leaves the surface at the same point
+pub fn flat_interval<'a>(hit: Option<Hit>) -> Vec<Interval<'a>>
+{
+	match hit {
+		Some(hit) => vec![Interval::new(hit, hit.flipped())],
+		None => Vec::new(),
+	}
 }
@@ -0,0 +1,130 @@
+use std::f32;
+use std::fmt;
+
+use area_light;
+use na::Matrix4;
+use na::Vector3;
+use na::Vector4;
+use primitives::plane;
+use primitives::primitive;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+// Rectangle centred on its position in the X/Z plane facing +Y, width runs
+// along X and height along Z, UVs span [0, 1] over the rectangle
+#[derive(fmt::Debug)]
+pub struct Rectangle
+{
+	position: Vector4<f32>,
+	width: f32,
+	height: f32,
+}
+
+impl Rectangle
+{
+	pub fn unit_rectangle() -> Self
+	{
+		Rectangle {
+			position: Vector4::new(0.0, 0.0, 0.0, 1.0),
+			width: 1.0,
+			height: 1.0,
+		}
+	}
+
+	pub fn new(position: Vector3<f32>, width: f32, height: f32) -> Self
+	{
+		Rectangle {
+			position: Vector4::new(position.x, position.y, position.z, 1.0),
+			width: width,
+			height: height,
+		}
+	}
+}
+
+impl Primitive for Rectangle
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		// Move the rectangle to the origin so it can be treated as the Y = 0 plane
+		let local_transform = Matrix4::new_translation(&-self.position.xyz()) * transform;
+		let (intersect, contact_point, normal) = plane::hit_xz_plane(ray, local_transform)?;
+
+		let u = contact_point.x / self.width + 0.5;
+		let v = contact_point.z / self.height + 0.5;
+
+		if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+			return None;
+		}
+
+		Some(Hit::new(
+			intersect,
+			math::transform_normals(normal, transform),
+			(u, v),
+			primitive::tangents_to_world(
+				Some((
+					Vector4::new(self.width, 0.0, 0.0, 0.0),
+					Vector4::new(0.0, 0.0, self.height, 0.0),
+				)),
+				transform,
+			),
+		))
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		primitive::flat_interval(self.hit(ray, transform))
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let offset = Vector4::new(self.width / 2.0, 0.0, self.height / 2.0, 0.0);
+
+		(self.position - offset, self.position + offset)
+	}
+
+	fn emitting_surface(&self) -> Option<area_light::Surface>
+	{
+		let corner = |x: f32, z: f32| {
+			self.position + Vector4::new(x * self.width / 2.0, 0.0, z * self.height / 2.0, 0.0)
+		};
+
+		// Wound to face +Y
+		Some(area_light::Surface::Triangles(vec![
+			[corner(-1.0, -1.0), corner(-1.0, 1.0), corner(1.0, 1.0)],
+			[corner(-1.0, -1.0), corner(1.0, 1.0), corner(1.0, -1.0)],
+		]))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn hits_within_its_sides()
+	{
+		let rectangle = Rectangle::new(Vector3::zeros(), 4.0, 2.0);
+
+		// Seen from below the normal faces back down the ray
+		let ray = Ray::new(
+			Vector4::new(1.0, -3.0, -0.5, 1.0),
+			Vector4::new(1.0, -2.0, -0.5, 1.0),
+		);
+		let hit = rectangle.hit(&ray, Matrix4::identity()).unwrap();
+
+		assert!((hit.intersect - 3.0).abs() < 0.001);
+		assert!((hit.normal - Vector4::new(0.0, -1.0, 0.0, 0.0)).norm() < 0.001);
+		assert!((hit.uv.0 - 0.75).abs() < 0.001 && (hit.uv.1 - 0.25).abs() < 0.001);
+
+		// Within the width but past the height
+		let ray = Ray::new(
+			Vector4::new(1.0, 3.0, 1.5, 1.0),
+			Vector4::new(1.0, 2.0, 1.5, 1.0),
+		);
+		assert!(rectangle.hit(&ray, Matrix4::identity()).is_none());
+	}
+}
@@ -0,0 +1,390 @@
+use std::f32;
+use std::fmt;
+use std::sync::Arc;
+
+use na::Vector3;
+
+use super::DistanceField;
+
+#[derive(fmt::Debug)]
+pub struct SphereField
+{
+	radius: f32,
+}
+
+impl SphereField
+{
+	pub fn new(radius: f32) -> Self
+	{
+		SphereField { radius: radius }
+	}
+}
+
+impl DistanceField for SphereField
+{
+	fn distance(&self, point: Vector3<f32>) -> f32
+	{
+		point.norm() - self.radius
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		(Vector3::repeat(-self.radius), Vector3::repeat(self.radius))
+	}
+}
+
+// Box centred on the origin with the given side lengths
+#[derive(fmt::Debug)]
+pub struct BoxField
+{
+	half_size: Vector3<f32>,
+}
+
+impl BoxField
+{
+	pub fn new(size: Vector3<f32>) -> Self
+	{
+		BoxField {
+			half_size: size / 2.0,
+		}
+	}
+}
+
+impl DistanceField for BoxField
+{
+	fn distance(&self, point: Vector3<f32>) -> f32
+	{
+		let q = point.abs() - self.half_size;
+		let outside = q.sup(&Vector3::repeat(0.0)).norm();
+		let inside = f32::min(f32::max(q.x, f32::max(q.y, q.z)), 0.0);
+
+		outside + inside
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		(-self.half_size, self.half_size)
+	}
+}
+
+// Torus lying in the X/Z plane around the Y axis
+#[derive(fmt::Debug)]
+pub struct TorusField
+{
+	major_radius: f32,
+	minor_radius: f32,
+}
+
+impl TorusField
+{
+	pub fn new(major_radius: f32, minor_radius: f32) -> Self
+	{
+		TorusField {
+			major_radius: major_radius,
+			minor_radius: minor_radius,
+		}
+	}
+}
+
+impl DistanceField for TorusField
+{
+	fn distance(&self, point: Vector3<f32>) -> f32
+	{
+		let ring_distance = point.x.hypot(point.z) - self.major_radius;
+
+		ring_distance.hypot(point.y) - self.minor_radius
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		let outer_radius = self.major_radius + self.minor_radius;
+		let extent = Vector3::new(outer_radius, self.minor_radius, outer_radius);
+
+		(-extent, extent)
+	}
+}
+
+#[derive(fmt::Debug)]
+pub struct Translation
+{
+	field: Arc<dyn DistanceField>,
+	offset: Vector3<f32>,
+}
+
+impl Translation
+{
+	pub fn new(field: Arc<dyn DistanceField>, offset: Vector3<f32>) -> Self
+	{
+		Translation {
+			field: field,
+			offset: offset,
+		}
+	}
+}
+
+impl DistanceField for Translation
+{
+	fn distance(&self, point: Vector3<f32>) -> f32
+	{
+		self.field.distance(point - self.offset)
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		let (lower, upper) = self.field.get_extents();
+
+		(lower + self.offset, upper + self.offset)
+	}
+
+	fn lipschitz(&self) -> f32
+	{
+		self.field.lipschitz()
+	}
+}
+
+// Polynomial smooth minimum of two fields, k is the radius of the blend
+#[derive(fmt::Debug)]
+pub struct SmoothUnion
+{
+	first: Arc<dyn DistanceField>,
+	second: Arc<dyn DistanceField>,
+	k: f32,
+}
+
+impl SmoothUnion
+{
+	pub fn new(first: Arc<dyn DistanceField>, second: Arc<dyn DistanceField>, k: f32) -> Self
+	{
+		SmoothUnion {
+			first: first,
+			second: second,
+			k: k,
+		}
+	}
+}
+
+impl DistanceField for SmoothUnion
+{
+	fn distance(&self, point: Vector3<f32>) -> f32
+	{
+		let a = self.first.distance(point);
+		let b = self.second.distance(point);
+
+		if self.k <= 0.0 {
+			return f32::min(a, b);
+		}
+
+		let h = f32::max(self.k - (a - b).abs(), 0.0) / self.k;
+
+		f32::min(a, b) - h * h * self.k / 4.0
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		// The blend can pull the surface out by at most k / 4 from either field
+		let (first_lower, first_upper) = self.first.get_extents();
+		let (second_lower, second_upper) = self.second.get_extents();
+		let padding = Vector3::repeat(f32::max(self.k, 0.0) / 4.0);
+
+		(
+			first_lower.inf(&second_lower) - padding,
+			first_upper.sup(&second_upper) + padding,
+		)
+	}
+
+	fn lipschitz(&self) -> f32
+	{
+		f32::max(self.first.lipschitz(), self.second.lipschitz())
+	}
+}
+
+// Repeats a field on a grid with the given spacing, limited to count copies
+// along each axis so the result stays bounded
+#[derive(fmt::Debug)]
+pub struct Repetition
+{
+	field: Arc<dyn DistanceField>,
+	spacing: Vector3<f32>,
+	count: Vector3<f32>,
+}
+
+impl Repetition
+{
+	pub fn new(field: Arc<dyn DistanceField>, spacing: Vector3<f32>, count: Vector3<u32>) -> Self
+	{
+		Repetition {
+			field: field,
+			spacing: spacing,
+			count: count.map(|c| c.max(1) as f32),
+		}
+	}
+
+	// Copies are centred on the origin, so the index of the outermost copy is
+	// half the count either side
+	fn half_span(&self) -> Vector3<f32>
+	{
+		(self.count - Vector3::repeat(1.0)) / 2.0
+	}
+}
+
+impl DistanceField for Repetition
+{
+	fn distance(&self, point: Vector3<f32>) -> f32
+	{
+		let half_span = self.half_span();
+
+		let cell = Vector3::new(
+			(point.x / self.spacing.x + half_span.x).round() - half_span.x,
+			(point.y / self.spacing.y + half_span.y).round() - half_span.y,
+			(point.z / self.spacing.z + half_span.z).round() - half_span.z,
+		);
+
+		let cell = cell.zip_map(&half_span, |c, h| c.clamp(-h, h));
+
+		self.field
+			.distance(point - self.spacing.component_mul(&cell))
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		let (lower, upper) = self.field.get_extents();
+		let offset = self.spacing.component_mul(&self.half_span()).abs();
+
+		(lower - offset, upper + offset)
+	}
+
+	fn lipschitz(&self) -> f32
+	{
+		self.field.lipschitz()
+	}
+}
+
+// Twists a field around the Y axis by the given angle per unit of height
+#[derive(fmt::Debug)]
+pub struct Twist
+{
+	field: Arc<dyn DistanceField>,
+	rate: f32,
+}
+
+impl Twist
+{
+	pub fn new(field: Arc<dyn DistanceField>, degrees_per_unit: f32) -> Self
+	{
+		Twist {
+			field: field,
+			rate: degrees_per_unit.to_radians(),
+		}
+	}
+
+	fn radius(&self) -> f32
+	{
+		let (lower, upper) = self.field.get_extents();
+		let x = f32::max(lower.x.abs(), upper.x.abs());
+		let z = f32::max(lower.z.abs(), upper.z.abs());
+
+		x.hypot(z)
+	}
+}
+
+impl DistanceField for Twist
+{
+	fn distance(&self, point: Vector3<f32>) -> f32
+	{
+		let (sin, cos) = (self.rate * point.y).sin_cos();
+
+		let twisted = Vector3::new(
+			cos * point.x - sin * point.z,
+			point.y,
+			sin * point.x + cos * point.z,
+		);
+
+		self.field.distance(twisted)
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		// Any point of the field can be rotated to any angle around Y
+		let (lower, upper) = self.field.get_extents();
+		let radius = self.radius();
+
+		(
+			Vector3::new(-radius, lower.y, -radius),
+			Vector3::new(radius, upper.y, radius),
+		)
+	}
+
+	fn lipschitz(&self) -> f32
+	{
+		// The rotation shears points further from the axis by up to rate * radius
+		self.field.lipschitz() * f32::sqrt(1.0 + (self.rate * self.radius()).powi(2))
+	}
+}
+
+// Distance estimate of the power-n Mandelbulb fractal, for powers above 1
+#[derive(fmt::Debug)]
+pub struct Mandelbulb
+{
+	power: f32,
+	iterations: u32,
+}
+
+impl Mandelbulb
+{
+	pub fn new(power: f32, iterations: u32) -> Self
+	{
+		Mandelbulb {
+			power: power,
+			iterations: iterations,
+		}
+	}
+}
+
+impl DistanceField for Mandelbulb
+{
+	fn distance(&self, point: Vector3<f32>) -> f32
+	{
+		const BAILOUT: f32 = 2.0;
+
+		let mut z = point;
+		let mut dr = 1.0;
+		let mut r = z.norm();
+
+		for _ in 0..self.iterations {
+			if r > BAILOUT {
+				break;
+			}
+
+			// Raise z to the given power in spherical coordinates and add the point
+			let theta = (z.z / r).acos() * self.power;
+			let phi = z.y.atan2(z.x) * self.power;
+			let zr = r.powf(self.power);
+
+			dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+
+			z = zr
+				* Vector3::new(
+					theta.sin() * phi.cos(),
+					phi.sin() * theta.sin(),
+					theta.cos(),
+				) + point;
+
+			r = z.norm();
+		}
+
+		if r == 0.0 {
+			return 0.0;
+		}
+
+		0.5 * r.ln() * r / dr
+	}
+
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>)
+	{
+		// Orbits starting further out than 2^(1 / (power - 1)) grow every step and
+		// escape, padded as the traced surface lies just outside the set
+		let radius = f32::powf(2.0, 1.0 / (self.power - 1.0)) + 0.1;
+
+		(Vector3::repeat(-radius), Vector3::repeat(radius))
+	}
+}
@@ -0,0 +1,215 @@
+mod fields;
+
+use std::f32;
+use std::fmt;
+use std::sync::Arc;
+
+use na::Matrix4;
+use na::Vector3;
+use na::Vector4;
+use primitives::primitive;
+use primitives::primitive::Crossing;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+pub use self::fields::BoxField;
+pub use self::fields::Mandelbulb;
+pub use self::fields::Repetition;
+pub use self::fields::SmoothUnion;
+pub use self::fields::SphereField;
+pub use self::fields::TorusField;
+pub use self::fields::Translation;
+pub use self::fields::Twist;
+
+const MAX_STEPS: usize = 512;
+const SURFACE_EPSILON: f32 = 0.0001;
+const NORMAL_EPSILON: f32 = 0.0001;
+
+pub trait DistanceField: Send + Sync + fmt::Debug
+{
+	// Signed distance to the surface, negative inside
+	fn distance(&self, point: Vector3<f32>) -> f32;
+
+	// Conservative bounds of the surface
+	fn get_extents(&self) -> (Vector3<f32>, Vector3<f32>);
+
+	// Upper bound on how fast the distance can change, fields that distort
+	// space (e.g. twisting) overestimate distances and need smaller steps
+	fn lipschitz(&self) -> f32
+	{
+		1.0
+	}
+}
+
+// Surface rendered by sphere tracing the zero level set of a distance field
+#[derive(fmt::Debug)]
+pub struct Sdf
+{
+	field: Arc<dyn DistanceField>,
+}
+
+impl Sdf
+{
+	pub fn new(field: Arc<dyn DistanceField>) -> Self
+	{
+		Sdf { field: field }
+	}
+
+	// Marches along the ray inside the field's bounds and records every surface
+	// crossing, stopping after the first one in front of the ray if requested
+	fn march(
+		&self,
+		origin: Vector4<f32>,
+		direction: Vector4<f32>,
+		first_only: bool,
+	) -> Vec<Crossing>
+	{
+		let mut crossings = Vec::new();
+
+		let (t_near, t_far) = match self.bounds_intersection(origin, direction) {
+			Some(bounds) => bounds,
+			None => return crossings,
+		};
+
+		// March in unit steps along the normalized direction, intersects are
+		// converted back to the parameterization of the ray when recorded
+		let length = direction.xyz().norm();
+		let unit_direction = direction.xyz() / length;
+		let origin = origin.xyz();
+		let lipschitz = self.field.lipschitz();
+
+		let s_far = t_far * length;
+		let mut s = if first_only {
+			f32::max(t_near, 0.0) * length
+		} else {
+			t_near * length
+		};
+
+		let mut inside = self.field.distance(origin + s * unit_direction) < 0.0;
+
+		for _ in 0..MAX_STEPS {
+			if s > s_far {
+				break;
+			}
+
+			let point = origin + s * unit_direction;
+			let distance = self.field.distance(point);
+
+			if (distance < 0.0) != inside || distance.abs() < SURFACE_EPSILON {
+				let t = s / length;
+
+				if !first_only || math::far_from_zero_pos(t) {
+					crossings.push((t, self.gradient(point).insert_row(3, 0.0), (0.0, 0.0), None));
+
+					if first_only {
+						break;
+					}
+				}
+
+				// Step through the surface before continuing on the other side
+				inside = !inside;
+				s += 2.0 * SURFACE_EPSILON;
+				continue;
+			}
+
+			s += f32::max(distance.abs() / lipschitz, SURFACE_EPSILON);
+		}
+
+		crossings
+	}
+
+	fn gradient(&self, point: Vector3<f32>) -> Vector3<f32>
+	{
+		let dx = Vector3::new(NORMAL_EPSILON, 0.0, 0.0);
+		let dy = Vector3::new(0.0, NORMAL_EPSILON, 0.0);
+		let dz = Vector3::new(0.0, 0.0, NORMAL_EPSILON);
+
+		Vector3::new(
+			self.field.distance(point + dx) - self.field.distance(point - dx),
+			self.field.distance(point + dy) - self.field.distance(point - dy),
+			self.field.distance(point + dz) - self.field.distance(point - dz),
+		)
+		.normalize()
+	}
+
+	fn bounds_intersection(
+		&self,
+		origin: Vector4<f32>,
+		direction: Vector4<f32>,
+	) -> Option<(f32, f32)>
+	{
+		let (lower, upper) = self.field.get_extents();
+
+		// Pad the bounds slightly so surfaces touching them are still found
+		math::ray_box_intersection(
+			origin.xyz(),
+			direction.xyz(),
+			lower.add_scalar(-SURFACE_EPSILON),
+			upper.add_scalar(SURFACE_EPSILON),
+		)
+	}
+}
+
+impl Primitive for Sdf
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::closest_candidate(self.march(origin, direction, true), direction, transform)
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::candidate_intervals(self.march(origin, direction, false), transform)
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let (lower, upper) = self.field.get_extents();
+
+		(lower.insert_row(3, 1.0), upper.insert_row(3, 1.0))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn sphere_field_matches_analytic_sphere()
+	{
+		let sdf = Sdf::new(Arc::new(SphereField::new(1.0)));
+		let ray = Ray::new(
+			Vector4::new(0.0, 0.0, 5.0, 1.0),
+			Vector4::new(0.0, 0.0, 3.0, 1.0),
+		);
+
+		let hit = sdf.hit(&ray, Matrix4::identity()).unwrap();
+		assert!((hit.intersect - 2.0).abs() < 0.001);
+		assert!((hit.normal - Vector4::new(0.0, 0.0, 1.0, 0.0)).norm() < 0.01);
+
+		let intervals = sdf.hit_intervals(&ray, Matrix4::identity());
+		assert_eq!(intervals.len(), 1);
+		assert!((intervals[0].exit.intersect - 3.0).abs() < 0.001);
+	}
+
+	#[test]
+	fn mandelbulb_extents_grow_for_low_powers()
+	{
+		// Lower powers escape more slowly, so the set can reach further out
+		let (lower, upper) = fields::Mandelbulb::new(2.0, 20).get_extents();
+		assert!(lower.x <= -2.0 && upper.x >= 2.0);
+
+		let (_, upper) = fields::Mandelbulb::new(8.0, 10).get_extents();
+		assert!((upper.x - 1.2).abs() < 0.01);
+	}
+}
@@ -1,12 +1,17 @@
 use std::f32;
 use std::fmt;
 
+use area_light;
 use na::Matrix4;
 use na::Vector3;
 use na::Vector4;
+use primitives::cylinder;
+use primitives::primitive;
+use primitives::primitive::Crossing;
 use primitives::Primitive;
 use util::math;
 use Hit;
+use Interval;
 use Ray;
 
 #[derive(fmt::Debug)]
@@ -33,59 +38,67 @@ impl Sphere
 			radius: radius,
 		}
 	}
+
+	// Every crossing of the surface along the ray, including those behind the origin
+	fn candidates(&self, origin: Vector4<f32>, direction: Vector4<f32>) -> Vec<Crossing>
+	{
+		let oc = origin - self.position;
+
+		let a = direction.dot(&direction);
+		let b = direction.dot(&oc) * 2.0;
+		let c = oc.dot(&oc) - (self.radius * self.radius);
+
+		match math::quadratic_roots(a, b, c) {
+			math::QuadRoots::Zero | math::QuadRoots::One(_) => Vec::new(),
+			math::QuadRoots::Two(root_one, root_two) => [root_one, root_two]
+				.iter()
+				.map(|&t| {
+					let offset = (origin + (t * direction)) - self.position;
+					let (uv, tangents) = self.surface_coordinates(offset);
+
+					(t, offset, uv, Some(tangents))
+				})
+				.collect(),
+		}
+	}
+
+	// Longitude around Y as U and latitude from the bottom pole as V, along with
+	// their derivatives, for a point given relative to the centre
+	fn surface_coordinates(
+		&self,
+		offset: Vector4<f32>,
+	) -> ((f32, f32), (Vector4<f32>, Vector4<f32>))
+	{
+		let u = cylinder::angle_around_y(offset);
+		let v = f32::acos((-offset.y / self.radius).clamp(-1.0, 1.0)) / f32::consts::PI;
+
+		// The direction away from the axis is undefined at the poles
+		let distance_from_axis = f32::hypot(offset.x, offset.z).max(f32::EPSILON);
+		let outward = Vector4::new(offset.x, 0.0, offset.z, 0.0) / distance_from_axis;
+
+		let dpdv = (Vector4::new(0.0, distance_from_axis, 0.0, 0.0) - outward * offset.y)
+			* f32::consts::PI;
+
+		((u, v), (cylinder::tangent_around_y(offset), dpdv))
+	}
 }
 
 impl Primitive for Sphere
 {
 	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
 	{
-		let point = transform * ray.point();
 		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
 
-		let po = point - origin;
-		let oc = origin - self.position;
+		primitive::closest_candidate(self.candidates(origin, direction), direction, transform)
+	}
 
-		let a = po.dot(&po);
-		let b = po.dot(&oc) * 2.0;
-		let c = oc.dot(&oc) - (self.radius * self.radius);
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
 
-		match math::quadratic_roots(a, b, c) {
-			math::QuadRoots::Zero | math::QuadRoots::One(_) => None,
-			math::QuadRoots::Two(root_one, root_two) => {
-				if !math::far_from_zero_pos(root_one) && !math::far_from_zero_pos(root_two) {
-					None
-				} else {
-					let t = if root_one <= root_two {
-						if math::far_from_zero_pos(root_one) {
-							root_one
-						} else {
-							root_two
-						}
-					} else {
-						if math::far_from_zero_pos(root_two) {
-							root_two
-						} else {
-							root_one
-						}
-					};
-
-					let mut n = (origin + (t * po)) - self.position;
-
-					// Invert normal if inside sphere
-					if n.dot(&(origin - point)) < 0.0 {
-						n = -n;
-					}
-
-					n = math::transform_normals(n, transform);
-
-					Some(Hit {
-						normal: n,
-						intersect: t,
-						uv: (0.0, 0.0),
-					})
-				}
-			},
-		}
+		primitive::candidate_intervals(self.candidates(origin, direction), transform)
 	}
 
 	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
@@ -95,4 +108,44 @@ impl Primitive for Sphere
 			self.position.add_scalar(self.radius),
 		)
 	}
+
+	fn emitting_surface(&self) -> Option<area_light::Surface>
+	{
+		Some(area_light::Surface::Sphere(self.position, self.radius))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn surface_coordinates_follow_the_surface()
+	{
+		// A sphere of radius 2 in the world, objects are given world to model transforms
+		let sphere = Sphere::unit_sphere();
+		let transform = Matrix4::new_scaling(0.5);
+
+		let ray = Ray::new(
+			Vector4::new(0.0, 0.0, 10.0, 1.0),
+			Vector4::new(0.0, 0.0, 9.0, 1.0),
+		);
+		let hit = sphere.hit(&ray, transform).unwrap();
+
+		assert!((hit.uv.0 - 0.5).abs() < 0.001 && (hit.uv.1 - 0.5).abs() < 0.001);
+
+		let (dpdu, dpdv) = hit.tangents.unwrap();
+		let pi = f32::consts::PI;
+
+		assert!((dpdu - Vector4::new(4.0 * pi, 0.0, 0.0, 0.0)).norm() < 0.001);
+		assert!((dpdv - Vector4::new(0.0, 2.0 * pi, 0.0, 0.0)).norm() < 0.001);
+
+		// Higher up the sphere is further along V
+		let ray = Ray::new(
+			Vector4::new(0.0, 1.0, 10.0, 1.0),
+			Vector4::new(0.0, 1.0, 9.0, 1.0),
+		);
+		assert!(sphere.hit(&ray, transform).unwrap().uv.1 > 0.5);
+	}
 }
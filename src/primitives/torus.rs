@@ -0,0 +1,125 @@
+use std::f32;
+use std::fmt;
+
+use na::Matrix4;
+use na::Vector4;
+use primitives::cylinder;
+use primitives::primitive;
+use primitives::primitive::Crossing;
+use primitives::Primitive;
+use util::math;
+use Hit;
+use Interval;
+use Ray;
+
+// Torus lying in the X/Z plane around the Y axis, the major radius is the
+// distance from the centre to the middle of the tube and the minor radius is
+// the radius of the tube itself
+#[derive(fmt::Debug)]
+pub struct Torus
+{
+	major_radius: f32,
+	minor_radius: f32,
+}
+
+impl Torus
+{
+	pub fn new(major_radius: f32, minor_radius: f32) -> Self
+	{
+		Torus {
+			major_radius: major_radius,
+			minor_radius: minor_radius,
+		}
+	}
+
+	// Every crossing of the surface along the ray, including those behind the origin
+	fn candidates(&self, origin: Vector4<f32>, direction: Vector4<f32>) -> Vec<Crossing>
+	{
+		// The quartic is badly conditioned, so the coefficients are built in f64
+		let (ox, oy, oz) = (origin.x as f64, origin.y as f64, origin.z as f64);
+		let (dx, dy, dz) = (direction.x as f64, direction.y as f64, direction.z as f64);
+		let major2 = (self.major_radius as f64).powi(2);
+		let minor2 = (self.minor_radius as f64).powi(2);
+
+		let d_dot_d = dx * dx + dy * dy + dz * dz;
+		let o_dot_d = ox * dx + oy * dy + oz * dz;
+		let e = ox * ox + oy * oy + oz * oz - major2 - minor2;
+		let four_major2 = 4.0 * major2;
+
+		let roots = math::quartic_roots(
+			d_dot_d * d_dot_d,
+			4.0 * d_dot_d * o_dot_d,
+			2.0 * d_dot_d * e + 4.0 * o_dot_d * o_dot_d + four_major2 * dy * dy,
+			4.0 * o_dot_d * e + 2.0 * four_major2 * oy * dy,
+			e * e - four_major2 * (minor2 - oy * oy),
+		);
+
+		roots
+			.into_iter()
+			.map(|t| {
+				let t = t as f32;
+				let contact_point = origin + (t * direction);
+
+				// Gradient of the implicit torus equation
+				let sum_squared = contact_point.xyz().norm_squared();
+				let radii_squared = self.major_radius.powi(2) + self.minor_radius.powi(2);
+				let normal = Vector4::new(
+					contact_point.x * (sum_squared - radii_squared),
+					contact_point.y
+						* (sum_squared - radii_squared + 2.0 * self.major_radius.powi(2)),
+					contact_point.z * (sum_squared - radii_squared),
+					0.0,
+				);
+
+				// V runs around the tube, starting from the outer equator
+				let distance_from_axis = contact_point.x.hypot(contact_point.z);
+				let tube_angle =
+					f32::atan2(contact_point.y, distance_from_axis - self.major_radius);
+				let v = 0.5 + tube_angle / (2.0 * f32::consts::PI);
+
+				// Around the tube in the plane through the axis
+				let outward = Vector4::new(contact_point.x, 0.0, contact_point.z, 0.0)
+					/ distance_from_axis.max(f32::EPSILON);
+				let dpdv = (Vector4::new(0.0, distance_from_axis - self.major_radius, 0.0, 0.0)
+					- outward * contact_point.y)
+					* (2.0 * f32::consts::PI);
+
+				(
+					t,
+					normal,
+					(cylinder::angle_around_y(contact_point), v),
+					Some((cylinder::tangent_around_y(contact_point), dpdv)),
+				)
+			})
+			.collect()
+	}
+}
+
+impl Primitive for Torus
+{
+	fn hit(&self, ray: &Ray, transform: Matrix4<f32>) -> Option<Hit>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::closest_candidate(self.candidates(origin, direction), direction, transform)
+	}
+
+	fn hit_intervals(&self, ray: &Ray, transform: Matrix4<f32>) -> Vec<Interval<'_>>
+	{
+		let origin = transform * ray.origin();
+		let direction = transform * ray.point() - origin;
+
+		primitive::candidate_intervals(self.candidates(origin, direction), transform)
+	}
+
+	fn get_extents(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let outer_radius = self.major_radius + self.minor_radius;
+
+		(
+			Vector4::new(-outer_radius, -self.minor_radius, -outer_radius, 1.0),
+			Vector4::new(outer_radius, self.minor_radius, outer_radius, 1.0),
+		)
+	}
+}
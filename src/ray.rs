@@ -1,7 +1,11 @@
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 
+use na::Matrix4;
+use na::Vector3;
 use na::Vector4;
+use shading::Material;
+use util::math;
 
 static NEXT_RAY_ID: AtomicU64 = AtomicU64::new(0);
 
@@ -10,6 +14,16 @@ pub struct Ray
 	id: u64,
 	point: Vector4<f32>,
 	origin: Vector4<f32>,
+	differentials: Option<Differentials>,
+}
+
+// Rays through the neighbouring pixels in X and Y, each as an origin and a
+// point like the ray itself, used to find how much of a surface a pixel covers
+#[derive(Clone, Copy)]
+pub struct Differentials
+{
+	pub x: (Vector4<f32>, Vector4<f32>),
+	pub y: (Vector4<f32>, Vector4<f32>),
 }
 
 impl Ray
@@ -20,6 +34,35 @@ impl Ray
 			id: NEXT_RAY_ID.fetch_add(1, Ordering::Relaxed),
 			point: point,
 			origin: origin,
+			differentials: None,
+		}
+	}
+
+	pub fn with_differentials(
+		origin: Vector4<f32>,
+		point: Vector4<f32>,
+		differentials: Differentials,
+	) -> Self
+	{
+		Ray {
+			differentials: Some(differentials),
+			..Ray::new(origin, point)
+		}
+	}
+
+	// The same ray, along with its differentials, in another space. It counts
+	// as a new ray so objects seen by the original are tested again.
+	pub fn transformed(&self, transform: Matrix4<f32>) -> Self
+	{
+		let transform_pair =
+			|(origin, point): (Vector4<f32>, Vector4<f32>)| (transform * origin, transform * point);
+
+		Ray {
+			differentials: self.differentials.map(|differentials| Differentials {
+				x: transform_pair(differentials.x),
+				y: transform_pair(differentials.y),
+			}),
+			..Ray::new(transform * self.origin, transform * self.point)
 		}
 	}
 
@@ -37,11 +80,209 @@ impl Ray
 	{
 		self.origin
 	}
+
+	// Change in UV from this pixel to the next in X and in Y, found by crossing
+	// the neighbouring rays with the plane tangent to the surface at the hit
+	pub fn uv_derivatives(&self, hit: &Hit) -> Option<UvDerivatives>
+	{
+		let differentials = self.differentials?;
+		let (dpdu, dpdv) = hit.tangents?;
+
+		let normal = hit.normal;
+		let contact_point = self.origin + hit.intersect * (self.point - self.origin);
+
+		let offset = |(origin, point): (Vector4<f32>, Vector4<f32>)| {
+			let direction = point - origin;
+			let denominator = normal.dot(&direction);
+
+			if denominator.abs() < f32::EPSILON {
+				return None;
+			}
+
+			let t = normal.dot(&(contact_point - origin)) / denominator;
+
+			Some(origin + t * direction - contact_point)
+		};
+
+		let dpdx = offset(differentials.x)?;
+		let dpdy = offset(differentials.y)?;
+
+		// Least squares solution of dpdx = dpdu * du + dpdv * dv, and likewise
+		// for dpdy, since the offsets need not lie exactly in the tangent plane
+		let uu = dpdu.dot(&dpdu);
+		let uv = dpdu.dot(&dpdv);
+		let vv = dpdv.dot(&dpdv);
+		let determinant = uu * vv - uv * uv;
+
+		if determinant.abs() < f32::EPSILON {
+			return None;
+		}
+
+		let solve = |offset: Vector4<f32>| {
+			let along_u = dpdu.dot(&offset);
+			let along_v = dpdv.dot(&offset);
+
+			(
+				(vv * along_u - uv * along_v) / determinant,
+				(uu * along_v - uv * along_u) / determinant,
+			)
+		};
+
+		Some((solve(dpdx), solve(dpdy)))
+	}
 }
 
+#[derive(Clone, Copy)]
 pub struct Hit
 {
 	pub intersect: f32,
 	pub normal: Vector4<f32>,
 	pub uv: (f32, f32),
+	// World space derivatives of the surface position with respect to u and
+	// v, for primitives that know them exactly
+	pub tangents: Option<(Vector4<f32>, Vector4<f32>)>,
+	// Set on the hit being shaded when the ray carries differentials
+	pub uv_derivatives: Option<UvDerivatives>,
+	// Hit point in the model space of the object that was hit, so solid
+	// textures move with the object. Set by the object rather than the primitive.
+	pub local_point: Option<Vector4<f32>>,
+	// Tangents in the same model space as the local point
+	pub local_tangents: Option<(Vector4<f32>, Vector4<f32>)>,
+	// Unit vector from the hit back towards the eye, set on the hit being shaded
+	pub view: Option<Vector4<f32>>,
+	// Wavelengths in nanometres carried by the three colour channels when
+	// rendering spectrally, set on the hit being shaded
+	pub wavelengths: Option<Vector3<f32>>,
+}
+
+// Change in UV across a pixel, (du/dx, dv/dx) and (du/dy, dv/dy)
+pub type UvDerivatives = ((f32, f32), (f32, f32));
+
+impl Hit
+{
+	// Hit as found by a primitive, the rest is filled in while shading
+	pub fn new(
+		intersect: f32,
+		normal: Vector4<f32>,
+		uv: (f32, f32),
+		tangents: Option<(Vector4<f32>, Vector4<f32>)>,
+	) -> Self
+	{
+		Hit {
+			intersect: intersect,
+			normal: normal,
+			uv: uv,
+			tangents: tangents,
+			uv_derivatives: None,
+			local_point: None,
+			local_tangents: None,
+			view: None,
+			wavelengths: None,
+		}
+	}
+
+	// Direction the hit is seen from. Hits that aren't being shaded, such as
+	// points on lights, don't know it and are taken as seen head on.
+	pub fn view_or_normal(&self) -> Vector4<f32>
+	{
+		self.view.unwrap_or(self.normal)
+	}
+
+	pub fn flipped(&self) -> Self
+	{
+		Hit {
+			normal: -self.normal,
+			..*self
+		}
+	}
+
+	// Unit tangent along U and bitangent completing a frame around the normal,
+	// any frame will do on surfaces without tangents
+	pub fn tangent_frame(&self) -> (Vector4<f32>, Vector4<f32>)
+	{
+		let normal = self.normal;
+
+		let tangent = self
+			.tangents
+			.and_then(|(dpdu, _)| (dpdu - normal * normal.dot(&dpdu)).try_normalize(f32::EPSILON))
+			.unwrap_or_else(|| {
+				let other = if normal.x.abs() > 0.9 {
+					Vector4::new(0.0, 1.0, 0.0, 0.0)
+				} else {
+					Vector4::new(1.0, 0.0, 0.0, 0.0)
+				};
+
+				math::cross_4d(other, normal).normalize()
+			});
+
+		(tangent, math::cross_4d(normal, tangent))
+	}
+}
+
+// Span of a ray inside a solid, normals at both ends point out of the solid.
+// Materials are only set by primitives built from other primitives (CSG), a
+// missing material falls back to the material of the object that was hit.
+pub struct Interval<'a>
+{
+	pub entry: Hit,
+	pub exit: Hit,
+	pub entry_material: Option<&'a dyn Material>,
+	pub exit_material: Option<&'a dyn Material>,
+}
+
+impl<'a> Interval<'a>
+{
+	pub fn new(entry: Hit, exit: Hit) -> Self
+	{
+		Interval {
+			entry: entry,
+			exit: exit,
+			entry_material: None,
+			exit_material: None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn uv_derivatives_follow_the_neighbouring_rays()
+	{
+		// A plane facing +Z with U running half as fast as X and V a quarter as
+		// fast as Y, hit head on at the origin
+		let hit = Hit::new(
+			5.0,
+			Vector4::new(0.0, 0.0, 1.0, 0.0),
+			(0.0, 0.0),
+			Some((
+				Vector4::new(2.0, 0.0, 0.0, 0.0),
+				Vector4::new(0.0, 4.0, 0.0, 0.0),
+			)),
+		);
+
+		let shifted = |x: f32, y: f32| (Vector4::new(x, y, 5.0, 1.0), Vector4::new(x, y, 4.0, 1.0));
+		let ray = Ray::with_differentials(
+			Vector4::new(0.0, 0.0, 5.0, 1.0),
+			Vector4::new(0.0, 0.0, 4.0, 1.0),
+			Differentials {
+				x: shifted(0.1, 0.0),
+				y: shifted(0.0, 0.2),
+			},
+		);
+
+		let ((dudx, dvdx), (dudy, dvdy)) = ray.uv_derivatives(&hit).unwrap();
+		assert!((dudx - 0.05).abs() < 0.0001);
+		assert!((dvdy - 0.05).abs() < 0.0001);
+		assert!(dvdx.abs() < 0.0001 && dudy.abs() < 0.0001);
+
+		// Rays without differentials have no footprint
+		let ray = Ray::new(
+			Vector4::new(0.0, 0.0, 5.0, 1.0),
+			Vector4::new(0.0, 0.0, 4.0, 1.0),
+		);
+		assert!(ray.uv_derivatives(&hit).is_none());
+	}
 }
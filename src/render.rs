@@ -1,11 +1,15 @@
+use std::collections::HashMap;
 use std::f32;
 use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
 use std::sync::mpsc;
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
 
+use area_light;
 use image::png;
 use image::ColorType;
 use image::ImageBuffer;
@@ -14,11 +18,22 @@ use image::Rgb;
 use na::Matrix4;
 use na::Vector3;
 use na::Vector4;
+use output::y4m;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use ray::Differentials;
+use shading::Material;
+use spectrum;
+use Hit;
 use Light;
 use Object;
 use Ray;
 use Scene;
 
+// Open Y4M streams keyed by file name, render calls to the same file append frames
+pub type VideoStreams = Arc<Mutex<HashMap<String, y4m::Encoder<BufWriter<File>>>>>;
+
 pub struct Parameters
 {
 	pub objects: Vec<Arc<Object>>,
@@ -31,6 +46,9 @@ pub struct Parameters
 	pub vertical_fov: f32,
 	pub ambient_light: Vector3<f32>,
 	pub single_pixel: Option<(u32, u32)>,
+	pub frame_rate: (u32, u32),
+	pub video_streams: VideoStreams,
+	pub spectral: bool,
 }
 
 pub fn render(parameters: Parameters)
@@ -86,7 +104,12 @@ pub fn render(parameters: Parameters)
 
 	let frame_sections = Arc::new(Mutex::new(divide_frame(image_width, image_height)));
 
-	let scene = Arc::new(Scene::new(objects, lights, ambient_light));
+	let tracer = Arc::new(Tracer {
+		stw: stw,
+		eye: eye_4d,
+		scene: Scene::new(objects, lights, ambient_light),
+		spectral: parameters.spectral,
+	});
 
 	let mut image = ImageBuffer::<Rgb<u8>, Vec<u8>>::from_pixel(
 		image_width,
@@ -95,7 +118,7 @@ pub fn render(parameters: Parameters)
 	);
 
 	if let Some(p) = &parameters.single_pixel {
-		let rgb = trace_pixel(p.0, p.1, stw, eye_4d, scene.as_ref());
+		let rgb = tracer.trace_pixel(p.0, p.1);
 		image.put_pixel(p.0, p.1, *Rgb::from_slice(&rgb));
 	} else {
 		let rx = {
@@ -104,10 +127,10 @@ pub fn render(parameters: Parameters)
 			for _ in 0..num_cpus::get() {
 				let frame_sections = Arc::clone(&frame_sections);
 				let tx = mpsc::Sender::clone(&tx);
-				let scene = Arc::clone(&scene);
+				let tracer = Arc::clone(&tracer);
 
 				thread::spawn(move || {
-					trace_worker(stw, eye_4d, scene.as_ref(), frame_sections, tx);
+					trace_worker(tracer.as_ref(), frame_sections, tx);
 				});
 			}
 
@@ -137,15 +160,29 @@ pub fn render(parameters: Parameters)
 		println!("Done");
 	}
 
-	match File::create(&output_file) {
+	let is_video = Path::new(&output_file)
+		.extension()
+		.is_some_and(|extension| extension == y4m::FILE_EXTENSION);
+
+	if is_video {
+		write_y4m_frame(
+			&parameters.video_streams,
+			&output_file,
+			&image.into_raw(),
+			(image_width, image_height),
+			parameters.frame_rate,
+		);
+	} else {
+		write_png(&output_file, &image.into_raw(), (image_width, image_height));
+	}
+}
+
+fn write_png(output_file: &str, image: &[u8], resolution: (u32, u32))
+{
+	match File::create(output_file) {
 		Ok(file) => {
 			let encoder = png::PNGEncoder::new(file);
-			match encoder.encode(
-				&image.into_raw(),
-				image_width,
-				image_height,
-				ColorType::RGB(8),
-			) {
+			match encoder.encode(image, resolution.0, resolution.1, ColorType::RGB(8)) {
 				Ok(_) => (),
 				Err(e) => println!("ERROR: Unable to encode image: {}", e),
 			}
@@ -154,10 +191,57 @@ pub fn render(parameters: Parameters)
 	}
 }
 
-fn trace_worker(
+fn write_y4m_frame(
+	video_streams: &VideoStreams,
+	output_file: &str,
+	image: &[u8],
+	resolution: (u32, u32),
+	frame_rate: (u32, u32),
+)
+{
+	let mut video_streams = video_streams.lock().unwrap();
+
+	if !video_streams.contains_key(output_file) {
+		let encoder = File::create(output_file)
+			.map_err(y4m::Error::from)
+			.and_then(|file| {
+				y4m::Encoder::new(BufWriter::new(file), resolution.0, resolution.1, frame_rate)
+			});
+
+		match encoder {
+			Ok(encoder) => {
+				video_streams.insert(String::from(output_file), encoder);
+			},
+			Err(e) => {
+				println!("ERROR: Unable to write to file {}: {}", output_file, e);
+				return;
+			},
+		}
+	}
+
+	let encoder = video_streams.get_mut(output_file).unwrap();
+
+	match encoder.encode_frame(image, resolution.0, resolution.1) {
+		Ok(_) => println!(
+			"Wrote frame {} to {}",
+			encoder.frames_written(),
+			output_file
+		),
+		Err(e) => println!("ERROR: Unable to encode frame: {}", e),
+	}
+}
+
+// Everything the workers share to trace pixels of the frame
+struct Tracer
+{
 	stw: Matrix4<f32>,
 	eye: Vector4<f32>,
-	scene: &Scene,
+	scene: Scene,
+	spectral: bool,
+}
+
+fn trace_worker(
+	tracer: &Tracer,
 	frame_sections: Arc<Mutex<Vec<FrameSection>>>,
 	tx: Sender<PixelColour>,
 )
@@ -174,7 +258,7 @@ fn trace_worker(
 
 		for x in frame_section.x..frame_section.x + frame_section.width {
 			for y in frame_section.y..frame_section.y + frame_section.height {
-				let rgb = trace_pixel(x, y, stw, eye, &scene);
+				let rgb = tracer.trace_pixel(x, y);
 
 				tx.send(PixelColour {
 					x: x,
@@ -187,55 +271,299 @@ fn trace_worker(
 	}
 }
 
-fn trace_pixel(x: u32, y: u32, stw: Matrix4<f32>, eye: Vector4<f32>, scene: &Scene) -> [u8; 3]
+// Fraction of the distance to a point on an area light that a shadow ray can
+// stop short of it and still count as reaching the light
+const AREA_LIGHT_EPSILON: f32 = 0.001;
+
+// Surfaces a ray is refracted through before the light beyond is taken as
+// dark
+const MAX_REFRACTIONS: u32 = 8;
+
+// Light reaching the point being shaded from one direction, in RGB, along
+// with the weight it is shaded with
+struct Incoming
 {
-	let pworld = stw * Vector4::new(x as f32, y as f32, 0.0, 1.0);
-	let ray = Ray::new(eye, pworld);
-
-	let colour_vec = match scene.check_hit(&ray) {
-		Some((hit, material)) => {
-			let contact_point = ray.origin() + (hit.intersect * (ray.point() - ray.origin()));
-			let view_vector = (eye - contact_point).normalize();
-			let normal = hit.normal.normalize();
-
-			let ac = scene
-				.get_ambient()
-				.component_mul(&material.ambient_component());
-			let mut dc = Vector3::new(0.0, 0.0, 0.0);
-			let mut sc = Vector3::new(0.0, 0.0, 0.0);
-
-			for light in scene.get_lights().iter() {
-				let shadow_ray = Ray::new(contact_point, light.get_position());
-
-				if let Some((shadow_hit, _)) = scene.check_hit(&shadow_ray) {
-					if shadow_hit.intersect <= 1.0 {
-						continue;
-					}
+	direction: Vector4<f32>,
+	radiance: Vector3<f32>,
+	weight: f32,
+}
+
+impl Tracer
+{
+	fn trace_pixel(&self, x: u32, y: u32) -> [u8; 3]
+	{
+		let stw = self.stw;
+		let eye = self.eye;
+
+		let pworld = stw * Vector4::new(x as f32, y as f32, 0.0, 1.0);
+		let differentials = Differentials {
+			x: (eye, stw * Vector4::new(x as f32 + 1.0, y as f32, 0.0, 1.0)),
+			y: (eye, stw * Vector4::new(x as f32, y as f32 + 1.0, 0.0, 1.0)),
+		};
+		let ray = Ray::with_differentials(eye, pworld, differentials);
+
+		// Each pixel draws its own random numbers, so images come out the same
+		// however the frame is split between threads
+		let mut rng = StdRng::seed_from_u64(u64::from(x) << 32 | u64::from(y));
+
+		let colour_vec = match self.scene.check_hit(&ray) {
+			Some((hit, material)) => {
+				let contact_point = ray.origin() + (hit.intersect * (ray.point() - ray.origin()));
+				let hit = material.detail(&Hit {
+					normal: hit.normal.normalize(),
+					uv_derivatives: ray.uv_derivatives(&hit),
+					view: Some((eye - contact_point).normalize()),
+					..hit
+				});
+
+				// Which lights can be seen doesn't depend on wavelength, so
+				// light is gathered once and shaded for each set of wavelengths
+				let incoming = self.gather_light(contact_point, &hit, material, &mut rng);
+
+				if self.spectral {
+					// Wavelengths are drawn in sets of three, stratified over the visible range
+					(0..spectrum::SAMPLES).fold(Vector3::zeros(), |colour, sample| {
+						let offset = (sample as f32 + rng.gen::<f32>()) / spectrum::SAMPLES as f32;
+						let wavelengths = spectrum::hero_wavelengths(offset);
+						let hit = Hit {
+							wavelengths: Some(wavelengths),
+							..hit
+						};
+
+						let radiance = self.shade(&hit, material, &incoming)
+							+ self.refracted(contact_point, &hit, material, None, 0, &mut rng);
+
+						colour + spectrum::to_rgb(radiance, wavelengths) / spectrum::SAMPLES as f32
+					})
+				} else {
+					self.shade(&hit, material, &incoming)
+						+ self.refracted(contact_point, &hit, material, None, 0, &mut rng)
+				}
+			},
+			None => Vector3::new(0.0, 0.0, 0.0),
+		};
+
+		let r = (255.0 * colour_vec[0].min(1.0)) as u8;
+		let g = (255.0 * colour_vec[1].min(1.0)) as u8;
+		let b = (255.0 * colour_vec[2].min(1.0)) as u8;
+
+		[r, g, b]
+	}
+
+	// Light arriving along a ray cast from a surface rather than from the eye,
+	// at the given wavelengths in spectral mode, for the one channel the ray
+	// carries if it has been split from the others
+	fn trace_ray(
+		&self,
+		ray: &Ray,
+		wavelengths: Option<Vector3<f32>>,
+		channel: Option<usize>,
+		depth: u32,
+		rng: &mut StdRng,
+	) -> Vector3<f32>
+	{
+		let (hit, material) = match self.scene.check_hit(ray) {
+			Some(hit) => hit,
+			None => return Vector3::zeros(),
+		};
+
+		let contact_point = ray.origin() + (hit.intersect * (ray.point() - ray.origin()));
+		let hit = material.detail(&Hit {
+			normal: hit.normal.normalize(),
+			view: Some((ray.origin() - contact_point).normalize()),
+			wavelengths: wavelengths,
+			..hit
+		});
+
+		let incoming = self.gather_light(contact_point, &hit, material, rng);
+
+		self.shade(&hit, material, &incoming)
+			+ self.refracted(contact_point, &hit, material, channel, depth, rng)
+	}
+
+	// Light refracted towards the view through a transmissive surface, traced
+	// on from the other side. Only the given channel is followed once light
+	// has been split into its colours, the others are left as they come.
+	fn refracted(
+		&self,
+		contact_point: Vector4<f32>,
+		hit: &Hit,
+		material: &dyn Material,
+		channel: Option<usize>,
+		depth: u32,
+		rng: &mut StdRng,
+	) -> Vector3<f32>
+	{
+		let transmission = match material.transmission(hit) {
+			Some(transmission) if depth < MAX_REFRACTIONS => transmission,
+			_ => return Vector3::zeros(),
+		};
+
+		let view_vector = hit.view_or_normal();
+		let mut trace = |refractive_index: f32, channel: Option<usize>| match refract(
+			view_vector,
+			hit.normal,
+			refractive_index,
+		) {
+			Some(direction) => {
+				let ray = Ray::new(contact_point, contact_point + direction);
+
+				self.trace_ray(&ray, hit.wavelengths, channel, depth + 1, rng)
+			},
+			None => Vector3::zeros(),
+		};
+
+		// Each channel is bent by its own index, so where the indices differ
+		// light splits into its colours and each channel follows a ray of its
+		// own from then on
+		let indices = transmission.refractive_index;
+		let radiance = match channel {
+			Some(channel) => trace(indices[channel], Some(channel)),
+			None if indices.x == indices.y && indices.y == indices.z => trace(indices.x, None),
+			None => {
+				let mut radiance = Vector3::zeros();
+
+				for channel in 0..3 {
+					radiance[channel] = trace(indices[channel], Some(channel))[channel];
 				}
 
-				let light_vector = light.get_position() - contact_point;
-				let distance = light_vector.dot(&light_vector).sqrt();
+				radiance
+			},
+		};
 
-				let light_vector = light_vector.normalize();
+		transmission.tint.component_mul(&radiance)
+	}
 
-				sc += light
-					.attenuate(distance)
-					.component_mul(&material.specular_component(view_vector, light_vector, normal));
-				dc += light
-					.attenuate(distance)
-					.component_mul(&material.diffuse_component(light_vector, normal));
+	// Light from the point lights and area lights that reaches the contact
+	// point unblocked
+	fn gather_light(
+		&self,
+		contact_point: Vector4<f32>,
+		hit: &Hit,
+		material: &dyn Material,
+		rng: &mut StdRng,
+	) -> Vec<Incoming>
+	{
+		let scene = &self.scene;
+		let view_vector = hit.view_or_normal();
+		let mut incoming = Vec::new();
+
+		for light in scene.get_lights().iter() {
+			let shadow_ray = Ray::new(contact_point, light.get_position());
+
+			if let Some((shadow_hit, _)) = scene.check_hit(&shadow_ray) {
+				if shadow_hit.intersect <= 1.0 {
+					continue;
+				}
 			}
 
-			ac + dc + sc
-		},
-		None => Vector3::new(0.0, 0.0, 0.0),
-	};
+			let light_vector = light.get_position() - contact_point;
+			let distance = light_vector.dot(&light_vector).sqrt();
+
+			incoming.push(Incoming {
+				direction: light_vector.normalize(),
+				radiance: light.attenuate(distance),
+				weight: 1.0,
+			});
+		}
+
+		// Area lights are lit both from points drawn on the lights and from
+		// directions drawn from the material, each weighted by how well it
+		// samples the light arriving from that direction. Materials return
+		// their reflectance times pi, as point lights expect, so the pi is
+		// taken back out of light gathered over solid angle.
+		for light in scene.get_area_lights() {
+			for _ in 0..area_light::SAMPLES {
+				let sample = light.sample(contact_point, rng);
+
+				// The shadow ray ends on the light, which it may hit just short of the sample
+				let shadow_ray = Ray::new(contact_point, sample.point);
+
+				let occluded = scene
+					.check_hit(&shadow_ray)
+					.is_some_and(|(shadow_hit, _)| shadow_hit.intersect < 1.0 - AREA_LIGHT_EPSILON);
+
+				if !occluded {
+					let light_vector = sample.point - contact_point;
+					let distance_squared = light_vector.dot(&light_vector);
+
+					let light_vector = light_vector.normalize();
+
+					// Both sides of a light give off light, a sphere's far side
+					// is hidden behind its near side
+					let cosine = sample.normal.dot(&light_vector).abs();
+					let light_density = distance_squared / (cosine * sample.area);
+					let material_density =
+						material.direction_density(view_vector, light_vector, hit);
+
+					incoming.push(Incoming {
+						direction: light_vector,
+						radiance: sample.radiance,
+						weight: power_heuristic(light_density, material_density)
+							/ (f32::consts::PI * light_density * area_light::SAMPLES as f32),
+					});
+				}
+
+				let (direction, material_density) =
+					match material.sample_direction(view_vector, hit, rng) {
+						Some(direction) => direction,
+						None => continue,
+					};
+
+				let light_ray = Ray::new(contact_point, contact_point + direction);
 
-	let r = (255.0 * colour_vec[0].min(1.0)) as u8;
-	let g = (255.0 * colour_vec[1].min(1.0)) as u8;
-	let b = (255.0 * colour_vec[2].min(1.0)) as u8;
+				let (light_hit, radiance) = match light.hit(&light_ray) {
+					Some(light_hit) => light_hit,
+					None => continue,
+				};
 
-	[r, g, b]
+				let occluded = scene.check_hit(&light_ray).is_some_and(|(shadow_hit, _)| {
+					shadow_hit.intersect < light_hit.intersect * (1.0 - AREA_LIGHT_EPSILON)
+				});
+
+				if occluded {
+					continue;
+				}
+
+				// Directions are unit length, so the intersect is the distance
+				let cosine = light_hit.normal.dot(&direction).abs();
+				let light_density = light.density(light_hit.normal, contact_point)
+					* light_hit.intersect
+					* light_hit.intersect
+					/ cosine;
+
+				incoming.push(Incoming {
+					direction: direction,
+					radiance: radiance,
+					weight: power_heuristic(material_density, light_density)
+						/ (f32::consts::PI * material_density * area_light::SAMPLES as f32),
+				});
+			}
+		}
+
+		incoming
+	}
+
+	// Light leaving the hit towards the eye, in RGB or, when the hit carries
+	// wavelengths, at the wavelength each channel carries
+	fn shade(&self, hit: &Hit, material: &dyn Material, incoming: &[Incoming]) -> Vector3<f32>
+	{
+		let view_vector = hit.view_or_normal();
+
+		let ac = spectrum::colour(self.scene.get_ambient(), hit)
+			.component_mul(&material.ambient_component(hit));
+
+		let lit = incoming.iter().fold(Vector3::zeros(), |lit, light| {
+			let intensity = spectrum::colour(light.radiance, hit) * light.weight;
+
+			lit + intensity.component_mul(
+				&(material.diffuse_component(light.direction, hit)
+					+ material.specular_component(view_vector, light.direction, hit)),
+			)
+		});
+
+		material.emission(hit) + ac + lit
+	}
 }
 
 fn create_screen_to_world_matrix(
@@ -270,6 +598,43 @@ fn create_screen_to_world_matrix(
 	t4 * r3 * s2 * t1
 }
 
+// Weight of a sample drawn with one density when it could also have been
+// drawn with the other, favouring whichever strategy suits the direction
+fn power_heuristic(density: f32, other_density: f32) -> f32
+{
+	let squared = density * density;
+	let total = squared + other_density * other_density;
+
+	if total > 0.0 && total.is_finite() {
+		squared / total
+	} else {
+		1.0
+	}
+}
+
+// Direction light seen from the view arrives from through a surface with the
+// given index of refraction, None when light leaving the material at that
+// angle is reflected back inside it
+fn refract(view: Vector4<f32>, normal: Vector4<f32>, refractive_index: f32)
+	-> Option<Vector4<f32>>
+{
+	// Seen from inside, light passes out of the material
+	let (normal, ratio) = if view.dot(&normal) >= 0.0 {
+		(normal, 1.0 / refractive_index)
+	} else {
+		(-normal, refractive_index)
+	};
+
+	let cosine = view.dot(&normal);
+	let sin_squared = ratio * ratio * (1.0 - cosine * cosine);
+
+	if sin_squared >= 1.0 {
+		return None;
+	}
+
+	Some(normal * (ratio * cosine - f32::sqrt(1.0 - sin_squared)) - view * ratio)
+}
+
 const BLOCK_SIZE: u32 = 64;
 fn divide_frame(width: u32, height: u32) -> Vec<FrameSection>
 {
@@ -341,3 +706,169 @@ struct PixelColour
 	pub y: u32,
 	pub rgb: [u8; 3],
 }
+
+#[cfg(test)]
+mod tests
+{
+	use primitives::Sphere;
+	use shading::microfacet::dielectric_fresnel;
+	use shading::principled;
+	use shading::Emissive;
+	use shading::Phong;
+	use shading::Principled;
+
+	use super::*;
+
+	#[test]
+	fn white_surfaces_match_the_light_around_them()
+	{
+		// A white ball inside an emissive sphere that lights it evenly from
+		// every side reflects exactly the light it is surrounded by
+		let sky = Object::new(
+			String::from("sky"),
+			Matrix4::identity(),
+			Arc::new(Sphere::new(Vector3::zeros(), 10.0)),
+			Arc::new(Emissive::new(Vector3::repeat(1.0).into(), 0.2, None)),
+		);
+		let ball = Object::new(
+			String::from("ball"),
+			Matrix4::identity(),
+			Arc::new(Sphere::new(Vector3::zeros(), 1.0)),
+			Arc::new(Phong::new(
+				Vector3::repeat(1.0).into(),
+				Vector3::zeros().into(),
+				1.0.into(),
+			)),
+		);
+
+		// Every pixel looks straight down -Z at the same point on the ball, each
+		// drawing its own light samples
+		let tracer = Tracer {
+			stw: Matrix4::new_translation(&Vector3::new(0.0, 0.0, 4.0))
+				* Matrix4::new_nonuniform_scaling(&Vector3::new(0.0, 0.0, 1.0)),
+			eye: Vector4::new(0.0, 0.0, 5.0, 1.0),
+			scene: Scene::new(
+				vec![Arc::new(sky), Arc::new(ball)],
+				Vec::new(),
+				Vector3::zeros(),
+			),
+			spectral: false,
+		};
+
+		let pixels = 400;
+		let total = (0..pixels).fold(Vector3::zeros(), |total: Vector3<f32>, x| {
+			total + Vector3::from(tracer.trace_pixel(x, 0)).map(f32::from)
+		});
+
+		// Pixels are rounded down, so about half a level is lost on average
+		let level = total / pixels as f32 + Vector3::repeat(0.5);
+		let sky_level = 255.0 * 0.2;
+		assert!(
+			(level - Vector3::repeat(sky_level)).amax() < 2.0,
+			"{:?}",
+			level
+		);
+	}
+
+	#[test]
+	fn refraction_follows_snells_law()
+	{
+		let normal = Vector4::new(0.0, 0.0, 1.0, 0.0);
+		let view = Vector4::new(0.6, 0.0, 0.8, 0.0);
+
+		// Bent towards the normal going in, away from it coming back out
+		let inside = refract(view, normal, 1.5).unwrap();
+		assert!((inside.norm() - 1.0).abs() < 1e-5);
+		assert!((inside.x * -1.5 - 0.6).abs() < 1e-5, "{:?}", inside);
+		assert!(inside.z < 0.0);
+
+		// Looking back out along the same path
+		let outside = refract(inside, normal, 1.5).unwrap();
+		assert!((outside - view).norm() < 1e-5, "{:?}", outside);
+
+		// Head on light passes straight through
+		assert!((refract(normal, normal, 1.5).unwrap() + normal).norm() < 1e-5);
+
+		// Past the critical angle light can't leave the material
+		assert!(refract(Vector4::new(0.8, 0.0, -0.6, 0.0), normal, 1.5).is_none());
+	}
+
+	// Every pixel looks down -Z from the eye, offset from the centre of a
+	// ball made of the given material, at a lamp further back
+	fn looking_through(material: Principled, offset: f32, lamp: Vector3<f32>) -> Tracer
+	{
+		let lamp = Object::new(
+			String::from("lamp"),
+			Matrix4::new_translation(&lamp),
+			Arc::new(Sphere::new(Vector3::zeros(), 0.3)),
+			Arc::new(Emissive::new(Vector3::repeat(1.0).into(), 1.0, None)),
+		);
+		let ball = Object::new(
+			String::from("ball"),
+			Matrix4::identity(),
+			Arc::new(Sphere::new(Vector3::zeros(), 1.0)),
+			Arc::new(material),
+		);
+
+		Tracer {
+			stw: Matrix4::new_translation(&Vector3::new(offset, 0.0, 4.0))
+				* Matrix4::new_nonuniform_scaling(&Vector3::new(0.0, 0.0, 1.0)),
+			eye: Vector4::new(offset, 0.0, 5.0, 1.0),
+			scene: Scene::new(
+				vec![Arc::new(lamp), Arc::new(ball)],
+				Vec::new(),
+				Vector3::zeros(),
+			),
+			spectral: false,
+		}
+	}
+
+	fn white(inputs: principled::Inputs) -> Principled
+	{
+		Principled::new(principled::Inputs {
+			base_colour: Vector3::repeat(1.0).into(),
+			..inputs
+		})
+	}
+
+	#[test]
+	fn glass_shows_what_is_behind_it()
+	{
+		let lamp = Vector3::new(0.0, 0.0, -5.0);
+		let glass = white(principled::Inputs {
+			transmission: 1.0.into(),
+			..principled::Inputs::default()
+		});
+
+		// Light loses what is reflected going in and coming back out
+		let through = 255.0 * (1.0 - dielectric_fresnel(1.0, 1.5)).powi(2);
+		let seen = looking_through(glass, 0.0, lamp).trace_pixel(0, 0);
+		let seen = Vector3::from(seen).map(f32::from);
+		assert!((seen - Vector3::repeat(through)).amax() < 2.0, "{:?}", seen);
+
+		// The front of an opaque ball lit from behind is dark
+		let opaque = white(principled::Inputs::default());
+		let seen = looking_through(opaque, 0.0, lamp).trace_pixel(0, 0);
+		assert!(seen.iter().all(|&level| level < 5), "{:?}", seen);
+	}
+
+	#[test]
+	fn glass_splits_light_into_its_colours()
+	{
+		// An index of 1 for red, rising steeply towards blue, so only red passes
+		// straight through to a lamp seen off the middle of the ball
+		let red = 0.61f32;
+		let dispersion = 0.2;
+		let prism = white(principled::Inputs {
+			transmission: 1.0.into(),
+			refractive_index: spectrum::RefractiveIndex::Cauchy(
+				1.0 - dispersion / (red * red),
+				dispersion,
+			),
+			..principled::Inputs::default()
+		});
+
+		let seen = looking_through(prism, 0.8, Vector3::new(0.8, 0.0, -5.0)).trace_pixel(0, 0);
+		assert!(seen[0] > 250 && seen[1] < 5 && seen[2] < 5, "{:?}", seen);
+	}
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use na::Vector3;
 use shading::Material;
+use AreaLight;
 use Grid;
 use Hit;
 use Light;
@@ -12,6 +13,8 @@ pub struct Scene
 {
 	grid: Grid,
 	lights: Vec<Light>,
+	// Objects giving off light that can be sampled
+	area_lights: Vec<AreaLight>,
 	ambient: Vector3<f32>,
 }
 
@@ -19,9 +22,15 @@ impl Scene
 {
 	pub fn new(objects: Vec<Arc<Object>>, lights: Vec<Light>, ambient: Vector3<f32>) -> Self
 	{
+		let area_lights = objects
+			.iter()
+			.filter_map(|object| AreaLight::from_object(object))
+			.collect();
+
 		Scene {
 			grid: Grid::new(objects),
 			lights: lights,
+			area_lights: area_lights,
 			ambient: ambient,
 		}
 	}
@@ -36,6 +45,11 @@ impl Scene
 		&self.lights
 	}
 
+	pub fn get_area_lights(&self) -> &[AreaLight]
+	{
+		&self.area_lights
+	}
+
 	pub fn get_ambient(&self) -> Vector3<f32>
 	{
 		self.ambient
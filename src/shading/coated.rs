@@ -0,0 +1,249 @@
+use std::f32;
+use std::fmt;
+use std::sync::Arc;
+
+use na::Vector3;
+use na::Vector4;
+use rand::Rng;
+use rand::RngCore;
+use shading::microfacet::dielectric_fresnel;
+use shading::microfacet::ggx;
+use shading::microfacet::schlick_weight;
+use shading::microfacet::smith_ggx;
+use shading::Material;
+use shading::Parameter;
+use Hit;
+
+// Clear dielectric layer, such as varnish or lacquer, over another material.
+// The coat reflects light by Fresnel, more of it at grazing angles, and only
+// what it lets through on the way in and again on the way out reaches the
+// material beneath, tinted by the coat's colour. The coat stays smooth over
+// any surface detail of the material beneath.
+#[derive(fmt::Debug)]
+pub struct Coated
+{
+	material: Arc<dyn Material>,
+	// From 0 for a mirror finish to 1, squared to give the width of the lobe
+	roughness: Parameter,
+	refractive_index: f32,
+	tint: Parameter,
+}
+
+impl Coated
+{
+	pub fn new(
+		material: Arc<dyn Material>,
+		roughness: Parameter,
+		refractive_index: f32,
+		tint: Parameter,
+	) -> Self
+	{
+		Coated {
+			material: material,
+			roughness: roughness,
+			refractive_index: refractive_index,
+			tint: tint,
+		}
+	}
+
+	fn alpha(&self, hit: &Hit) -> f32
+	{
+		self.roughness.value(hit).clamp(0.0, 1.0).powi(2).max(0.001)
+	}
+
+	// Share of light coming in from the light direction and leaving towards
+	// the view that passes through the coat both ways
+	fn transmitted(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
+	{
+		let normal = hit.normal;
+		let transmitted = |direction: Vector4<f32>| {
+			1.0 - dielectric_fresnel(normal.dot(&direction), self.refractive_index)
+		};
+
+		self.tint.colour(hit) * (transmitted(light) * transmitted(view))
+	}
+
+	// Chance of sampling the coat rather than the material beneath, rising
+	// towards grazing angles where the coat reflects most
+	fn coat_chance(&self, view: Vector4<f32>, hit: &Hit) -> f32
+	{
+		0.25 + 0.75 * schlick_weight(hit.normal.dot(&view))
+	}
+
+	fn coat_density(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit) -> f32
+	{
+		let normal = hit.normal;
+
+		if normal.dot(&light) <= 0.0 || normal.dot(&view) <= 0.0 {
+			return 0.0;
+		}
+
+		let half = (light + view).normalize();
+		let n_dot_h = normal.dot(&half);
+		let l_dot_h = light.dot(&half).max(f32::EPSILON);
+
+		ggx(n_dot_h, self.alpha(hit)) * n_dot_h / (4.0 * l_dot_h)
+	}
+}
+
+impl Material for Coated
+{
+	fn emission(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let view = hit.view_or_normal();
+		let through = 1.0 - dielectric_fresnel(hit.normal.dot(&view), self.refractive_index);
+
+		self.material
+			.emission(&self.material.detail(hit))
+			.component_mul(&self.tint.colour(hit))
+			* through
+	}
+
+	fn is_emissive(&self) -> bool
+	{
+		self.material.is_emissive()
+	}
+
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let reflected = dielectric_fresnel(1.0, self.refractive_index);
+
+		self.material
+			.ambient_component(&self.material.detail(hit))
+			.component_mul(&self.tint.colour(hit))
+			* (1.0 - reflected).powi(2)
+			+ Vector3::repeat(reflected)
+	}
+
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
+	{
+		let view = hit.view_or_normal();
+
+		self.material
+			.diffuse_component(light, &self.material.detail(hit))
+			.component_mul(&self.transmitted(view, light, hit))
+	}
+
+	fn specular_component(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit)
+		-> Vector3<f32>
+	{
+		let beneath = self
+			.material
+			.specular_component(view, light, &self.material.detail(hit))
+			.component_mul(&self.transmitted(view, light, hit));
+
+		let normal = hit.normal;
+		let n_dot_l = normal.dot(&light);
+		let n_dot_v = normal.dot(&view);
+
+		if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+			return beneath;
+		}
+
+		let half = (light + view).normalize();
+		let alpha = self.alpha(hit);
+
+		let coat = dielectric_fresnel(light.dot(&half), self.refractive_index)
+			* ggx(normal.dot(&half), alpha)
+			* smith_ggx(n_dot_l, alpha)
+			* smith_ggx(n_dot_v, alpha);
+
+		beneath + Vector3::repeat(coat * n_dot_l * f32::consts::PI)
+	}
+
+	fn sample_direction(
+		&self,
+		view: Vector4<f32>,
+		hit: &Hit,
+		rng: &mut dyn RngCore,
+	) -> Option<(Vector4<f32>, f32)>
+	{
+		let normal = hit.normal;
+
+		if normal.dot(&view) <= 0.0 {
+			return None;
+		}
+
+		let light = if rng.gen::<f32>() < self.coat_chance(view, hit) {
+			// A microfacet normal drawn by its slope, for the view to reflect about
+			let (tangent, bitangent) = hit.tangent_frame();
+			let (u1, u2) = (rng.gen::<f32>(), rng.gen::<f32>());
+			let slope = self.alpha(hit) * f32::sqrt(u1 / (1.0 - u1));
+			let angle = 2.0 * f32::consts::PI * u2;
+
+			let half =
+				(tangent * (slope * angle.cos()) + bitangent * (slope * angle.sin()) + normal)
+					.try_normalize(f32::EPSILON)?;
+
+			half * (2.0 * view.dot(&half)) - view
+		} else {
+			self.material
+				.sample_direction(view, &self.material.detail(hit), rng)?
+				.0
+		};
+
+		let density = self.direction_density(view, light, hit);
+
+		if density > 0.0 {
+			Some((light, density))
+		} else {
+			None
+		}
+	}
+
+	fn direction_density(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit) -> f32
+	{
+		let coat_chance = self.coat_chance(view, hit);
+		let beneath = self
+			.material
+			.direction_density(view, light, &self.material.detail(hit));
+
+		coat_chance * self.coat_density(view, light, hit) + (1.0 - coat_chance) * beneath
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use shading::principled;
+	use shading::testing;
+	use shading::Principled;
+
+	use super::*;
+
+	#[test]
+	fn coat_reflects_more_at_grazing_angles_without_adding_light()
+	{
+		let hit = testing::hit();
+		let normal = hit.normal;
+
+		let white: Arc<dyn Material> = Arc::new(Principled::new(principled::Inputs {
+			base_colour: Vector3::repeat(1.0).into(),
+			..principled::Inputs::default()
+		}));
+		let coated = Coated::new(white, 0.1.into(), 1.5, Vector3::repeat(1.0).into());
+
+		for &view in &[normal, Vector4::new(0.6, 0.0, 0.8, 0.0)] {
+			testing::assert_albedo_within(testing::albedo(&coated, view, &hit), 0.6);
+		}
+
+		// Mirror reflections off the coat alone
+		let mirror = |cosine: f32| {
+			let sine = f32::sqrt(1.0 - cosine * cosine);
+			let view = Vector4::new(sine, 0.0, cosine, 0.0);
+			let light = Vector4::new(-sine, 0.0, cosine, 0.0);
+
+			let black: Arc<dyn Material> = Arc::new(Principled::new(principled::Inputs {
+				base_colour: Vector3::zeros().into(),
+				specular: 0.0.into(),
+				..principled::Inputs::default()
+			}));
+
+			Coated::new(black, 0.1.into(), 1.5, Vector3::repeat(1.0).into())
+				.specular_component(view, light, &hit)
+				.x
+		};
+
+		assert!(mirror(0.2) > 2.0 * mirror(0.9));
+	}
+}
@@ -3,66 +3,135 @@ use std::fmt;
 
 use na::Vector3;
 use na::Vector4;
+use shading::microfacet::ggx_anisotropic;
+use shading::microfacet::smith_ggx_anisotropic;
 use shading::Material;
+use shading::Parameter;
+use spectrum::RefractiveIndex;
 use util::math;
+use Hit;
 
 #[derive(fmt::Debug)]
 pub struct CookTorrance
 {
-	diffuse_colour: Vector3<f32>,
-	specular_colour: Vector3<f32>,
-	diffuse_fraction: f32,
-	roughness: f32,
-	refractive_index: f32,
-	extinction_coefficient: f32,
+	diffuse_colour: Parameter,
+	// Reflectance head on, which metals without one take from their index
+	specular_colour: Option<Parameter>,
+	diffuse_fraction: Parameter,
+	roughness: Parameter,
+	// Raises roughness along the tangent and lowers it across, from 0 to 1,
+	// stretching highlights as on brushed metal
+	anisotropy: Parameter,
+	// Turns the direction of anisotropy about the normal, a full turn at 1
+	rotation: Option<Parameter>,
+	refractive_index: RefractiveIndex,
+	extinction_coefficient: RefractiveIndex,
 }
 
 impl CookTorrance
 {
 	pub fn new(
-		diffuse_colour: Vector3<f32>,
-		specular_colour: Vector3<f32>,
-		diffuse_fraction: f32,
-		roughness: f32,
-		refractive_index: f32,
-		extinction_coefficient: f32,
+		diffuse_colour: Parameter,
+		specular_colour: Parameter,
+		diffuse_fraction: Parameter,
+		roughness: Parameter,
+		refractive_index: RefractiveIndex,
+		extinction_coefficient: RefractiveIndex,
 	) -> Self
 	{
 		CookTorrance {
 			diffuse_colour: diffuse_colour,
-			specular_colour: specular_colour,
+			specular_colour: Some(specular_colour),
 			diffuse_fraction: diffuse_fraction,
 			roughness: roughness,
+			anisotropy: 0.0.into(),
+			rotation: None,
 			refractive_index: refractive_index,
 			extinction_coefficient: extinction_coefficient,
 		}
 	}
+
+	// Bare metal coloured only by the Fresnel reflectance of its complex
+	// refractive index
+	pub fn metal(
+		roughness: Parameter,
+		refractive_index: RefractiveIndex,
+		extinction_coefficient: RefractiveIndex,
+	) -> Self
+	{
+		CookTorrance {
+			specular_colour: None,
+			..CookTorrance::new(
+				Vector3::zeros().into(),
+				Vector3::zeros().into(),
+				0.0.into(),
+				roughness,
+				refractive_index,
+				extinction_coefficient,
+			)
+		}
+	}
+
+	// Roughness that differs along and across the surface tangent, following U
+	// on surfaces with UVs. Surfaces without tangents pick a direction.
+	pub fn with_anisotropy(self, anisotropy: Parameter, rotation: Option<Parameter>) -> Self
+	{
+		CookTorrance {
+			anisotropy: anisotropy,
+			rotation: rotation,
+			..self
+		}
+	}
+
+	// Roughness along the tangent and bitangent, and those directions
+	fn roughness_frame(&self, hit: &Hit) -> ((f32, f32), (Vector4<f32>, Vector4<f32>))
+	{
+		let roughness = self.roughness.value(hit);
+		let anisotropy = self.anisotropy.value(hit).clamp(0.0, 1.0);
+		let aspect = f32::sqrt(1.0 - 0.9 * anisotropy);
+
+		let (tangent, bitangent) = hit.tangent_frame();
+		let frame = match &self.rotation {
+			Some(rotation) => {
+				let angle = 2.0 * f32::consts::PI * rotation.value(hit);
+				let (sin, cos) = angle.sin_cos();
+
+				(
+					tangent * cos + bitangent * sin,
+					bitangent * cos - tangent * sin,
+				)
+			},
+			None => (tangent, bitangent),
+		};
+
+		((roughness / aspect, roughness * aspect), frame)
+	}
 }
 
 impl Material for CookTorrance
 {
-	fn ambient_component(&self) -> Vector3<f32>
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>
 	{
-		self.diffuse_colour * f32::consts::PI
+		self.diffuse_colour.colour(hit) * f32::consts::PI
 	}
 
-	fn diffuse_component(&self, light: Vector4<f32>, normal: Vector4<f32>) -> Vector3<f32>
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
 	{
-		if math::near_zero(self.diffuse_fraction) {
+		let diffuse_fraction = self.diffuse_fraction.value(hit);
+
+		if math::near_zero(diffuse_fraction) {
 			return Vector3::new(0.0, 0.0, 0.0);
 		}
 
-		self.diffuse_colour * f32::max(light.dot(&normal), 0.0) * self.diffuse_fraction
+		self.diffuse_colour.colour(hit) * f32::max(light.dot(&hit.normal), 0.0) * diffuse_fraction
 	}
 
-	fn specular_component(
-		&self,
-		view: Vector4<f32>,
-		light: Vector4<f32>,
-		normal: Vector4<f32>,
-	) -> Vector3<f32>
+	fn specular_component(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit)
+		-> Vector3<f32>
 	{
-		let specular_fraction = 1.0 - self.diffuse_fraction;
+		let normal = hit.normal;
+
+		let specular_fraction = 1.0 - self.diffuse_fraction.value(hit);
 
 		if math::near_zero(specular_fraction) {
 			return Vector3::new(0.0, 0.0, 0.0);
@@ -71,83 +140,65 @@ impl Material for CookTorrance
 		let half = (view + light).normalize();
 
 		let nv = normal.dot(&view);
+		let nl = normal.dot(&light);
+		let nh = normal.dot(&half);
 
-		if math::near_zero(nv) {
+		if nv <= 0.0 || nl <= 0.0 || nh <= 0.0 {
 			return Vector3::new(0.0, 0.0, 0.0);
 		}
 
-		let fresnel_n =
-			fresnel_from_refractive_index(1.0, self.refractive_index, self.extinction_coefficient);
+		let refractive_indices = self.refractive_index.channels(hit);
+		let extinction_coefficients = self.extinction_coefficient.channels(hit);
+		let specular_colour = self
+			.specular_colour
+			.as_ref()
+			.map(|colour| colour.colour(hit));
 
-		let fresnel_vh = fresnel_from_refractive_index(
-			view.dot(&half),
-			self.refractive_index,
-			self.extinction_coefficient,
-		);
-
-		let fresnel_red = fresnel_approximation(fresnel_vh, fresnel_n, self.specular_colour.x);
-		let fresnel_green = fresnel_approximation(fresnel_vh, fresnel_n, self.specular_colour.y);
-		let fresnel_blue = fresnel_approximation(fresnel_vh, fresnel_n, self.specular_colour.z);
+		// Each channel has its own index when the index varies with wavelength
+		let fresnel = Vector3::from_fn(|channel, _| {
+			let refractive_index = refractive_indices[channel];
+			let extinction_coefficient = extinction_coefficients[channel];
 
-		let d = ggx_distribution(half, normal, self.roughness);
-		let g = ggx_geometry(view, light, half, normal, self.roughness);
+			let fresnel_n =
+				fresnel_from_refractive_index(1.0, refractive_index, extinction_coefficient);
 
-		let specular_partial = (d * g) / (4.0 * nv);
+			let fresnel_vh = fresnel_from_refractive_index(
+				view.dot(&half),
+				refractive_index,
+				extinction_coefficient,
+			);
 
-		let specular_colour = Vector3::new(
-			fresnel_red * specular_partial,
-			fresnel_green * specular_partial,
-			fresnel_blue * specular_partial,
-		);
+			match specular_colour {
+				Some(colour) => fresnel_approximation(fresnel_vh, fresnel_n, colour[channel]),
+				None => fresnel_vh,
+			}
+		});
 
-		specular_colour * specular_fraction
-	}
-}
+		let ((alpha_x, alpha_y), (tangent, bitangent)) = self.roughness_frame(hit);
 
-fn chi(a: f32) -> f32
-{
-	if a > 0.0 {
-		1.0
-	} else {
-		0.0
-	}
-}
+		let d = ggx_anisotropic(
+			nh,
+			half.dot(&tangent),
+			half.dot(&bitangent),
+			alpha_x,
+			alpha_y,
+		);
 
-fn ggx_distribution(half: Vector4<f32>, normal: Vector4<f32>, alpha: f32) -> f32
-{
-	let a2 = alpha.powi(2);
-	let hn = half.dot(&normal);
-	let hn2 = hn.powi(2);
+		// Masking carries the 1 / (4 n.l n.v), leaving the cosine at the light
+		let masking = |direction: Vector4<f32>, cosine: f32| {
+			smith_ggx_anisotropic(
+				cosine,
+				direction.dot(&tangent),
+				direction.dot(&bitangent),
+				alpha_x,
+				alpha_y,
+			)
+		};
 
-	(chi(hn) * a2) / (f32::consts::PI * ((hn2 * a2) + (1.0 - hn2)).powi(2))
-}
+		let specular_partial = d * masking(view, nv) * masking(light, nl) * nl;
 
-fn ggx_geometry(
-	view: Vector4<f32>,
-	light: Vector4<f32>,
-	half: Vector4<f32>,
-	normal: Vector4<f32>,
-	alpha: f32,
-) -> f32
-{
-	ggx_geometry_partial(view, half, normal, alpha)
-		* ggx_geometry_partial(light, half, normal, alpha)
-}
-
-fn ggx_geometry_partial(
-	direction: Vector4<f32>,
-	half: Vector4<f32>,
-	normal: Vector4<f32>,
-	alpha: f32,
-) -> f32
-{
-	let a2 = alpha.powi(2);
-	let dh = direction.dot(&half);
-	let dn = direction.dot(&normal);
-	let dn2 = dn.powi(2);
-	let tan2 = (1.0 - dn2) / dn2;
-
-	(chi(dh / dn) * 2.0) / (1.0 + (1.0 + (a2 * tan2)).sqrt())
+		fresnel * (specular_partial * specular_fraction)
+	}
 }
 
 fn fresnel_from_refractive_index(
@@ -173,3 +224,50 @@ fn fresnel_approximation(fresnel: f32, fresnel_normal: f32, reflectance: f32) ->
 
 	r + (1.0 - r) * ((f - nf) / (1.0 - nf))
 }
+
+#[cfg(test)]
+mod tests
+{
+	use shading::testing;
+
+	use super::*;
+
+	#[test]
+	fn anisotropy_stretches_highlights_along_the_tangent()
+	{
+		let hit = testing::hit();
+		let normal = hit.normal;
+		let metal = || {
+			CookTorrance::new(
+				Vector3::zeros().into(),
+				Vector3::repeat(0.9).into(),
+				0.0.into(),
+				0.2.into(),
+				1.5.into(),
+				1.0.into(),
+			)
+		};
+
+		// Lights tilted off the mirror direction along and across U
+		let view = normal;
+		let along = Vector4::new(0.5, 0.0, 1.0, 0.0).normalize();
+		let across = Vector4::new(0.0, 0.5, 1.0, 0.0).normalize();
+
+		let isotropic = metal();
+		let brushed = metal().with_anisotropy(1.0.into(), None);
+		let turned = metal().with_anisotropy(1.0.into(), Some(0.25.into()));
+
+		let highlight =
+			|material: &CookTorrance, light| material.specular_component(view, light, &hit).x;
+
+		assert!((highlight(&isotropic, along) - highlight(&isotropic, across)).abs() < 0.001);
+		assert!(highlight(&brushed, along) > 2.0 * highlight(&brushed, across));
+
+		// A quarter turn swaps the directions
+		assert!((highlight(&turned, along) - highlight(&brushed, across)).abs() < 0.001);
+
+		// No anisotropy leaves the highlight as it was
+		let unchanged = metal().with_anisotropy(0.0.into(), Some(0.1.into()));
+		assert!((highlight(&unchanged, along) - highlight(&isotropic, along)).abs() < 0.001);
+	}
+}
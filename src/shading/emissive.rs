@@ -0,0 +1,111 @@
+use std::fmt;
+use std::sync::Arc;
+
+use na::Vector3;
+use na::Vector4;
+use rand::RngCore;
+use shading::Material;
+use shading::Parameter;
+use shading::Transmission;
+use Hit;
+
+// Surface that gives off light of its own, on top of another material it is
+// shaded with, or black. Emissive meshes and spheres light the rest of the
+// scene as well as being seen to glow.
+#[derive(fmt::Debug)]
+pub struct Emissive
+{
+	colour: Parameter,
+	strength: f32,
+	material: Option<Arc<dyn Material>>,
+}
+
+impl Emissive
+{
+	pub fn new(colour: Parameter, strength: f32, material: Option<Arc<dyn Material>>) -> Self
+	{
+		Emissive {
+			colour: colour,
+			strength: strength,
+			material: material,
+		}
+	}
+}
+
+impl Material for Emissive
+{
+	fn detail(&self, hit: &Hit) -> Hit
+	{
+		match &self.material {
+			Some(material) => material.detail(hit),
+			None => *hit,
+		}
+	}
+
+	fn emission(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let own = self.colour.colour(hit) * self.strength;
+
+		match &self.material {
+			Some(material) => own + material.emission(hit),
+			None => own,
+		}
+	}
+
+	fn is_emissive(&self) -> bool
+	{
+		self.strength > 0.0
+	}
+
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>
+	{
+		self.material
+			.as_ref()
+			.map_or_else(Vector3::zeros, |material| material.ambient_component(hit))
+	}
+
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
+	{
+		self.material
+			.as_ref()
+			.map_or_else(Vector3::zeros, |material| {
+				material.diffuse_component(light, hit)
+			})
+	}
+
+	fn specular_component(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit)
+		-> Vector3<f32>
+	{
+		self.material
+			.as_ref()
+			.map_or_else(Vector3::zeros, |material| {
+				material.specular_component(view, light, hit)
+			})
+	}
+
+	fn sample_direction(
+		&self,
+		view: Vector4<f32>,
+		hit: &Hit,
+		rng: &mut dyn RngCore,
+	) -> Option<(Vector4<f32>, f32)>
+	{
+		self.material
+			.as_ref()
+			.and_then(|material| material.sample_direction(view, hit, rng))
+	}
+
+	fn direction_density(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit) -> f32
+	{
+		self.material
+			.as_ref()
+			.map_or(0.0, |material| material.direction_density(view, light, hit))
+	}
+
+	fn transmission(&self, hit: &Hit) -> Option<Transmission>
+	{
+		self.material
+			.as_ref()
+			.and_then(|material| material.transmission(hit))
+	}
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+use na::Vector3;
+use na::Vector4;
+use shading::Material;
+use spectrum;
+use Hit;
+
+// Kajiya-Kay hair model, shades a strand by the angle between its tangent and
+// the light instead of the normal. Surfaces without tangents are shaded as
+// if the strand ran perpendicular to the normal, i.e. as Lambert and Phong.
+#[derive(fmt::Debug)]
+pub struct KajiyaKay
+{
+	diffuse: Vector3<f32>,
+	specular: Vector3<f32>,
+	shininess: f32,
+}
+
+impl KajiyaKay
+{
+	pub fn new(diffuse: Vector3<f32>, specular: Vector3<f32>, shininess: f32) -> Self
+	{
+		KajiyaKay {
+			diffuse: diffuse,
+			specular: specular,
+			shininess: shininess,
+		}
+	}
+
+	fn tangent(hit: &Hit) -> Option<Vector4<f32>>
+	{
+		hit.tangents
+			.map(|(tangent, _)| tangent)
+			.filter(|tangent| tangent.norm_squared() > 0.0)
+			.map(|tangent| tangent.normalize())
+	}
+}
+
+impl Material for KajiyaKay
+{
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>
+	{
+		spectrum::colour(self.diffuse, hit)
+	}
+
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
+	{
+		let intensity = match KajiyaKay::tangent(hit) {
+			Some(tangent) => (1.0 - tangent.dot(&light).powi(2)).max(0.0).sqrt(),
+			None => light.dot(&hit.normal).max(0.0),
+		};
+
+		spectrum::colour(self.diffuse, hit) * intensity
+	}
+
+	fn specular_component(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit)
+		-> Vector3<f32>
+	{
+		let tangent = match KajiyaKay::tangent(hit) {
+			Some(tangent) => tangent,
+			None => {
+				let t = light.dot(&hit.normal) * 2.0;
+				let r = hit.normal * t - light;
+
+				return spectrum::colour(self.specular, hit)
+					* f32::max(r.dot(&view), 0.0).powf(self.shininess);
+			},
+		};
+
+		// Strongest where the view lies on the cone of reflections around the strand
+		let cos_light = tangent.dot(&light);
+		let cos_view = tangent.dot(&view);
+		let sin_light = (1.0 - cos_light * cos_light).max(0.0).sqrt();
+		let sin_view = (1.0 - cos_view * cos_view).max(0.0).sqrt();
+
+		// Lights behind the strand are blocked by it
+		if light.dot(&hit.normal) <= 0.0 {
+			return Vector3::zeros();
+		}
+
+		spectrum::colour(self.specular, hit)
+			* f32::max(sin_light * sin_view - cos_light * cos_view, 0.0).powf(self.shininess)
+	}
+}
@@ -2,15 +2,77 @@ use std::fmt;
 
 use na::Vector3;
 use na::Vector4;
+use rand::RngCore;
+use Hit;
+
+// Light let through the surface towards the view, as a tint on the light
+// refracted from the other side and the index of refraction of the material
+// behind the surface, both per colour channel
+#[derive(Clone, Copy, fmt::Debug)]
+pub struct Transmission
+{
+	pub tint: Vector3<f32>,
+	pub refractive_index: Vector3<f32>,
+}
 
 pub trait Material: Send + Sync + fmt::Debug
 {
-	fn ambient_component(&self) -> Vector3<f32>;
-	fn diffuse_component(&self, light: Vector4<f32>, normal: Vector4<f32>) -> Vector3<f32>;
+	// Adjusts the hit before it is shaded, materials with surface detail bend
+	// the normal here
+	fn detail(&self, hit: &Hit) -> Hit
+	{
+		*hit
+	}
+
+	// Light given off by the surface itself towards hit.view
+	fn emission(&self, _hit: &Hit) -> Vector3<f32>
+	{
+		Vector3::zeros()
+	}
+
+	// Emissive surfaces are also sampled as lights, so only materials that can
+	// give off light should say they do
+	fn is_emissive(&self) -> bool
+	{
+		false
+	}
+
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>;
+
+	// The hit describes the surface being shaded, its normal is normalized
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>;
 	fn specular_component(
 		&self,
 		view: Vector4<f32>,
 		light: Vector4<f32>,
-		normal: Vector4<f32>,
+		hit: &Hit,
 	) -> Vector3<f32>;
+
+	// A direction to gather light from, drawn in proportion to how much light
+	// the material reflects from it towards the view, with its probability
+	// density over solid angle. None for materials that can't be sampled,
+	// area lights are then lit from points drawn on the lights alone.
+	fn sample_direction(
+		&self,
+		_view: Vector4<f32>,
+		_hit: &Hit,
+		_rng: &mut dyn RngCore,
+	) -> Option<(Vector4<f32>, f32)>
+	{
+		None
+	}
+
+	// Density sample_direction draws the light direction with
+	fn direction_density(&self, _view: Vector4<f32>, _light: Vector4<f32>, _hit: &Hit) -> f32
+	{
+		0.0
+	}
+
+	// Refracted light for materials that let light through, None for opaque
+	// ones. The hit is seen from hit.view, from inside when it faces away
+	// from the normal.
+	fn transmission(&self, _hit: &Hit) -> Option<Transmission>
+	{
+		None
+	}
 }
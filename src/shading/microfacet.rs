@@ -0,0 +1,87 @@
+use std::f32;
+
+// Terms shared by the materials built from microfacets, tiny mirrors whose
+// orientations are spread around the surface normal by its roughness
+
+// Fresnel reflectance of a dielectric with the given index of refraction for
+// unpolarized light arriving at the given cosine to the normal, from outside
+pub fn dielectric_fresnel(cosine: f32, refractive_index: f32) -> f32
+{
+	let cosine = cosine.clamp(0.0, 1.0);
+	let sin_squared = (1.0 - cosine * cosine) / (refractive_index * refractive_index);
+
+	// Total internal reflection can't happen going into a denser medium, but
+	// guard against indices below one
+	if sin_squared >= 1.0 {
+		return 1.0;
+	}
+
+	let cos_transmitted = f32::sqrt(1.0 - sin_squared);
+	let parallel = (refractive_index * cosine - cos_transmitted)
+		/ (refractive_index * cosine + cos_transmitted);
+	let perpendicular = (cosine - refractive_index * cos_transmitted)
+		/ (cosine + refractive_index * cos_transmitted);
+
+	0.5 * (parallel * parallel + perpendicular * perpendicular)
+}
+
+// Weight of Schlick's approximation to Fresnel, 0 head on and 1 at grazing
+pub fn schlick_weight(cosine: f32) -> f32
+{
+	(1.0 - cosine).clamp(0.0, 1.0).powi(5)
+}
+
+// Generalized Trowbridge-Reitz with a long tail, for the clearcoat
+pub fn gtr1(n_dot_h: f32, alpha: f32) -> f32
+{
+	if alpha >= 1.0 {
+		return 1.0 / f32::consts::PI;
+	}
+
+	let alpha_squared = alpha * alpha;
+	let t = 1.0 + (alpha_squared - 1.0) * n_dot_h * n_dot_h;
+
+	(alpha_squared - 1.0) / (f32::consts::PI * alpha_squared.ln() * t)
+}
+
+// Trowbridge-Reitz distribution of microfacet normals
+pub fn ggx(n_dot_h: f32, alpha: f32) -> f32
+{
+	let sin = f32::sqrt((1.0 - n_dot_h * n_dot_h).max(0.0));
+
+	ggx_anisotropic(n_dot_h, sin, 0.0, alpha, alpha)
+}
+
+// The same stretched to different widths along the tangent and bitangent
+pub fn ggx_anisotropic(n_dot_h: f32, h_dot_x: f32, h_dot_y: f32, alpha_x: f32, alpha_y: f32)
+	-> f32
+{
+	let x = h_dot_x / alpha_x;
+	let y = h_dot_y / alpha_y;
+	let t = x * x + y * y + n_dot_h * n_dot_h;
+
+	1.0 / (f32::consts::PI * alpha_x * alpha_y * t * t)
+}
+
+// Smith masking for one direction, divided by 2 n.v so the product for both
+// directions carries the 1 / (4 n.l n.v) of the microfacet model
+pub fn smith_ggx(n_dot_v: f32, alpha: f32) -> f32
+{
+	let alpha_squared = alpha * alpha;
+	let cos_squared = n_dot_v * n_dot_v;
+
+	1.0 / (n_dot_v + f32::sqrt(alpha_squared + cos_squared - alpha_squared * cos_squared))
+}
+
+pub fn smith_ggx_anisotropic(
+	n_dot_v: f32,
+	v_dot_x: f32,
+	v_dot_y: f32,
+	alpha_x: f32,
+	alpha_y: f32,
+) -> f32
+{
+	let stretched = (v_dot_x * alpha_x).powi(2) + (v_dot_y * alpha_y).powi(2);
+
+	1.0 / (n_dot_v + f32::sqrt(stretched + n_dot_v * n_dot_v))
+}
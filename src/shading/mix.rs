@@ -0,0 +1,153 @@
+use std::fmt;
+use std::sync::Arc;
+
+use na::Vector3;
+use na::Vector4;
+use rand::Rng;
+use rand::RngCore;
+use shading::Material;
+use shading::Parameter;
+use Hit;
+
+// Blends two materials, showing more of the second as the factor goes from 0
+// to 1. A texture as the factor masks one material with the other, as dirt
+// over paint. Each material keeps its own surface detail.
+#[derive(fmt::Debug)]
+pub struct Mix
+{
+	a: Arc<dyn Material>,
+	b: Arc<dyn Material>,
+	factor: Parameter,
+}
+
+impl Mix
+{
+	pub fn new(a: Arc<dyn Material>, b: Arc<dyn Material>, factor: Parameter) -> Self
+	{
+		Mix {
+			a: a,
+			b: b,
+			factor: factor,
+		}
+	}
+
+	// Blends what each material gives for the hit, with the detail it adds
+	fn blend<F>(&self, hit: &Hit, component: F) -> Vector3<f32>
+	where
+		F: Fn(&dyn Material, &Hit) -> Vector3<f32>,
+	{
+		let factor = self.factor(hit);
+
+		let a = if factor < 1.0 {
+			component(&*self.a, &self.a.detail(hit))
+		} else {
+			Vector3::zeros()
+		};
+		let b = if factor > 0.0 {
+			component(&*self.b, &self.b.detail(hit))
+		} else {
+			Vector3::zeros()
+		};
+
+		a * (1.0 - factor) + b * factor
+	}
+
+	fn factor(&self, hit: &Hit) -> f32
+	{
+		self.factor.value(hit).clamp(0.0, 1.0)
+	}
+}
+
+impl Material for Mix
+{
+	fn emission(&self, hit: &Hit) -> Vector3<f32>
+	{
+		self.blend(hit, |material, hit| material.emission(hit))
+	}
+
+	fn is_emissive(&self) -> bool
+	{
+		self.a.is_emissive() || self.b.is_emissive()
+	}
+
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>
+	{
+		self.blend(hit, |material, hit| material.ambient_component(hit))
+	}
+
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
+	{
+		self.blend(hit, |material, hit| material.diffuse_component(light, hit))
+	}
+
+	fn specular_component(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit)
+		-> Vector3<f32>
+	{
+		self.blend(hit, |material, hit| {
+			material.specular_component(view, light, hit)
+		})
+	}
+
+	// Samples one material or the other in proportion to how much each shows
+	fn sample_direction(
+		&self,
+		view: Vector4<f32>,
+		hit: &Hit,
+		rng: &mut dyn RngCore,
+	) -> Option<(Vector4<f32>, f32)>
+	{
+		let material = if rng.gen::<f32>() < self.factor(hit) {
+			&self.b
+		} else {
+			&self.a
+		};
+
+		let (light, _) = material.sample_direction(view, &material.detail(hit), rng)?;
+		let density = self.direction_density(view, light, hit);
+
+		if density > 0.0 {
+			Some((light, density))
+		} else {
+			None
+		}
+	}
+
+	fn direction_density(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit) -> f32
+	{
+		let factor = self.factor(hit);
+
+		self.a.direction_density(view, light, &self.a.detail(hit)) * (1.0 - factor)
+			+ self.b.direction_density(view, light, &self.b.detail(hit)) * factor
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use shading::testing;
+	use shading::Emissive;
+	use shading::Phong;
+
+	use super::*;
+
+	#[test]
+	fn blends_by_the_factor()
+	{
+		let hit = testing::hit();
+		let light = Vector4::new(0.0, 0.0, 1.0, 0.0);
+
+		let red: Arc<dyn Material> = Arc::new(Phong::new(
+			Vector3::new(1.0, 0.0, 0.0).into(),
+			Vector3::zeros().into(),
+			1.0.into(),
+		));
+		let glowing: Arc<dyn Material> =
+			Arc::new(Emissive::new(Vector3::new(0.0, 0.0, 1.0).into(), 2.0, None));
+
+		let mix = Mix::new(red, glowing, 0.25.into());
+
+		assert!((mix.diffuse_component(light, &hit) - Vector3::new(0.75, 0.0, 0.0)).norm() < 0.001);
+		assert!((mix.emission(&hit) - Vector3::new(0.0, 0.0, 0.5)).norm() < 0.001);
+		assert!(mix.is_emissive());
+	}
+}
@@ -1,7 +1,28 @@
+pub use self::coated::Coated;
 pub use self::cook_torrance::CookTorrance;
+pub use self::emissive::Emissive;
+pub use self::kajiya_kay::KajiyaKay;
 pub use self::material::Material;
+pub use self::material::Transmission;
+pub use self::mix::Mix;
 pub use self::phong::Phong;
+pub use self::principled::Principled;
+pub use self::rough_diffuse::RoughDiffuse;
+pub use self::surface_detail::SurfaceDetail;
+pub use self::texture::Parameter;
+pub use self::texture::Texture;
 
+pub mod coated;
 pub mod cook_torrance;
+pub mod emissive;
+pub mod kajiya_kay;
 pub mod material;
+pub mod microfacet;
+pub mod mix;
 pub mod phong;
+pub mod principled;
+pub mod rough_diffuse;
+pub mod surface_detail;
+#[cfg(test)]
+pub mod testing;
+pub mod texture;
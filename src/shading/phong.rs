@@ -3,18 +3,20 @@ use std::fmt;
 use na::Vector3;
 use na::Vector4;
 use shading::Material;
+use shading::Parameter;
+use Hit;
 
 #[derive(fmt::Debug)]
 pub struct Phong
 {
-	diffuse: Vector3<f32>,
-	specular: Vector3<f32>,
-	shininess: f32,
+	diffuse: Parameter,
+	specular: Parameter,
+	shininess: Parameter,
 }
 
 impl Phong
 {
-	pub fn new(diffuse: Vector3<f32>, specular: Vector3<f32>, shininess: f32) -> Self
+	pub fn new(diffuse: Parameter, specular: Parameter, shininess: Parameter) -> Self
 	{
 		Phong {
 			diffuse: diffuse,
@@ -26,26 +28,24 @@ impl Phong
 
 impl Material for Phong
 {
-	fn ambient_component(&self) -> Vector3<f32>
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>
 	{
-		self.diffuse
+		self.diffuse.colour(hit)
 	}
 
-	fn diffuse_component(&self, light: Vector4<f32>, normal: Vector4<f32>) -> Vector3<f32>
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
 	{
-		self.diffuse * light.dot(&normal).max(0.0)
+		self.diffuse.colour(hit) * light.dot(&hit.normal).max(0.0)
 	}
 
-	fn specular_component(
-		&self,
-		view: Vector4<f32>,
-		light: Vector4<f32>,
-		normal: Vector4<f32>,
-	) -> Vector3<f32>
+	fn specular_component(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit)
+		-> Vector3<f32>
 	{
+		let normal = hit.normal;
+
 		let t = light.dot(&normal) * 2.0;
 		let r = normal.map(|component| component * t) - light;
 
-		self.specular * f32::max(r.dot(&view), 0.0).powf(self.shininess)
+		self.specular.colour(hit) * f32::max(r.dot(&view), 0.0).powf(self.shininess.value(hit))
 	}
 }
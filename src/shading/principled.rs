@@ -0,0 +1,536 @@
+use std::f32;
+use std::fmt;
+
+use na::Vector3;
+use na::Vector4;
+use rand::Rng;
+use rand::RngCore;
+use shading::microfacet::dielectric_fresnel;
+use shading::microfacet::ggx_anisotropic;
+use shading::microfacet::gtr1;
+use shading::microfacet::schlick_weight;
+use shading::microfacet::smith_ggx;
+use shading::microfacet::smith_ggx_anisotropic;
+use shading::Material;
+use shading::Parameter;
+use shading::Transmission;
+use spectrum::RefractiveIndex;
+use Hit;
+
+// Inputs of the principled material, any of which but the index of
+// refraction can be a texture or node. All but the base colour and the index
+// run from 0 to 1.
+#[derive(Clone, fmt::Debug)]
+pub struct Inputs
+{
+	pub base_colour: Parameter,
+	pub metallic: Parameter,
+	pub roughness: Parameter,
+	// Reflectance of dielectrics head on, 0.5 gives the 4% of most materials
+	pub specular: Parameter,
+	// Tints dielectric reflections towards the base colour
+	pub specular_tint: Parameter,
+	// Stretches highlights along U, across it when the surface has no UVs
+	pub anisotropic: Parameter,
+	// Soft rim of reflection at grazing angles, as on cloth
+	pub sheen: Parameter,
+	pub sheen_tint: Parameter,
+	// Second, colourless specular layer over the top, as on car paint
+	pub clearcoat: Parameter,
+	pub clearcoat_gloss: Parameter,
+	// Flattens the diffuse falloff as light scattered under the surface does
+	pub subsurface: Parameter,
+	// Lets light through dielectrics in place of the diffuse lobe, as glass
+	pub transmission: Parameter,
+	// Index of refraction transmitted light passes into
+	pub refractive_index: RefractiveIndex,
+}
+
+impl Default for Inputs
+{
+	fn default() -> Self
+	{
+		Inputs {
+			base_colour: Vector3::repeat(0.8).into(),
+			metallic: 0.0.into(),
+			roughness: 0.5.into(),
+			specular: 0.5.into(),
+			specular_tint: 0.0.into(),
+			anisotropic: 0.0.into(),
+			sheen: 0.0.into(),
+			sheen_tint: 0.5.into(),
+			clearcoat: 0.0.into(),
+			clearcoat_gloss: 1.0.into(),
+			subsurface: 0.0.into(),
+			transmission: 0.0.into(),
+			refractive_index: 1.5.into(),
+		}
+	}
+}
+
+// Inputs looked up at one point on the surface
+struct Surface
+{
+	base_colour: Vector3<f32>,
+	// Taken from the base colour in RGB, as it is given
+	base_luminance: f32,
+	metallic: f32,
+	roughness: f32,
+	specular: f32,
+	specular_tint: f32,
+	anisotropic: f32,
+	sheen: f32,
+	sheen_tint: f32,
+	clearcoat: f32,
+	clearcoat_gloss: f32,
+	subsurface: f32,
+	transmission: f32,
+}
+
+impl Surface
+{
+	// Base colour with its brightness taken out
+	fn tint(&self) -> Vector3<f32>
+	{
+		if self.base_luminance > 0.0 {
+			self.base_colour / self.base_luminance
+		} else {
+			Vector3::repeat(1.0)
+		}
+	}
+
+	// Reflectance head on, metals reflect their base colour
+	fn specular_colour(&self) -> Vector3<f32>
+	{
+		let dielectric =
+			lerp(Vector3::repeat(1.0), self.tint(), self.specular_tint) * (0.08 * self.specular);
+
+		lerp(dielectric, self.base_colour, self.metallic)
+	}
+
+	fn diffuse_weight(&self) -> f32
+	{
+		(1.0 - self.metallic) * (1.0 - self.transmission)
+	}
+
+	// Widths of the specular lobe along and across the tangent
+	fn alphas(&self) -> (f32, f32)
+	{
+		let aspect = f32::sqrt(1.0 - 0.9 * self.anisotropic);
+		let alpha = self.roughness * self.roughness;
+
+		((alpha / aspect).max(0.001), (alpha * aspect).max(0.001))
+	}
+
+	fn clearcoat_alpha(&self) -> f32
+	{
+		lerp(0.1, 0.001, self.clearcoat_gloss)
+	}
+
+	// Chances of sampling the diffuse, specular and clearcoat lobes
+	fn lobe_chances(&self) -> (f32, f32, f32)
+	{
+		let diffuse = self.diffuse_weight();
+		let clearcoat = 0.25 * self.clearcoat;
+		let total = diffuse + 1.0 + clearcoat;
+
+		(diffuse / total, 1.0 / total, clearcoat / total)
+	}
+}
+
+// Uber-material after Disney's principled BRDF, described by the artist
+// friendly inputs above. Lobes are layered so a surface never reflects more
+// light than reaches it, and directions can be drawn in proportion to how
+// much light each lobe reflects. Transmissive surfaces refract light through
+// to the renderer, which traces it on. Light reflected back inside them isn't
+// followed, and shadow rays stop at them.
+#[derive(fmt::Debug)]
+pub struct Principled
+{
+	inputs: Inputs,
+}
+
+impl Principled
+{
+	pub fn new(inputs: Inputs) -> Self
+	{
+		Principled { inputs: inputs }
+	}
+
+	fn surface(&self, hit: &Hit) -> Surface
+	{
+		let inputs = &self.inputs;
+		let value = |parameter: &Parameter| parameter.value(hit).clamp(0.0, 1.0);
+
+		Surface {
+			base_colour: inputs.base_colour.colour(hit),
+			base_luminance: luminance(inputs.base_colour.rgb(hit)),
+			metallic: value(&inputs.metallic),
+			roughness: value(&inputs.roughness),
+			specular: value(&inputs.specular),
+			specular_tint: value(&inputs.specular_tint),
+			anisotropic: value(&inputs.anisotropic),
+			sheen: value(&inputs.sheen),
+			sheen_tint: value(&inputs.sheen_tint),
+			clearcoat: value(&inputs.clearcoat),
+			clearcoat_gloss: value(&inputs.clearcoat_gloss),
+			subsurface: value(&inputs.subsurface),
+			transmission: value(&inputs.transmission),
+		}
+	}
+
+	// Light reflected from the light direction towards the viewer by the
+	// diffuse and sheen lobes, then by the specular and clearcoat lobes, both
+	// scaled by the cosine at the light as the other materials are
+	fn reflectance(
+		&self,
+		view: Vector4<f32>,
+		light: Vector4<f32>,
+		hit: &Hit,
+	) -> (Vector3<f32>, Vector3<f32>)
+	{
+		let normal = hit.normal;
+		let n_dot_l = normal.dot(&light);
+		let n_dot_v = normal.dot(&view);
+
+		if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+			return (Vector3::zeros(), Vector3::zeros());
+		}
+
+		let half = (light + view).normalize();
+		let n_dot_h = normal.dot(&half);
+		let l_dot_h = light.dot(&half);
+
+		let surface = self.surface(hit);
+		let (tangent, bitangent) = hit.tangent_frame();
+
+		let fresnel_l = schlick_weight(n_dot_l);
+		let fresnel_v = schlick_weight(n_dot_v);
+		let fresnel_h = schlick_weight(l_dot_h);
+
+		// Diffuse that brightens at grazing angles as the surface roughens
+		let retro = l_dot_h * l_dot_h * surface.roughness;
+		let fd90 = 0.5 + 2.0 * retro;
+		let diffuse = lerp(1.0, fd90, fresnel_l) * lerp(1.0, fd90, fresnel_v);
+
+		// Flatter falloff standing in for light scattered under the surface
+		let fss = lerp(1.0, retro, fresnel_l) * lerp(1.0, retro, fresnel_v);
+		let subsurface = 1.25 * (fss * (1.0 / (n_dot_l + n_dot_v) - 0.5) + 0.5);
+
+		let sheen = lerp(Vector3::repeat(1.0), surface.tint(), surface.sheen_tint)
+			* (fresnel_h * surface.sheen);
+
+		let diffuse = (surface.base_colour
+			* (lerp(diffuse, subsurface, surface.subsurface) / f32::consts::PI)
+			+ sheen) * surface.diffuse_weight();
+
+		let (alpha_x, alpha_y) = surface.alphas();
+		let distribution = ggx_anisotropic(
+			n_dot_h,
+			half.dot(&tangent),
+			half.dot(&bitangent),
+			alpha_x,
+			alpha_y,
+		);
+		let masking = smith_ggx_anisotropic(
+			n_dot_l,
+			light.dot(&tangent),
+			light.dot(&bitangent),
+			alpha_x,
+			alpha_y,
+		) * smith_ggx_anisotropic(
+			n_dot_v,
+			view.dot(&tangent),
+			view.dot(&bitangent),
+			alpha_x,
+			alpha_y,
+		);
+		let specular = lerp(surface.specular_colour(), Vector3::repeat(1.0), fresnel_h)
+			* (distribution * masking);
+
+		let clearcoat = 0.25
+			* surface.clearcoat
+			* gtr1(n_dot_h, surface.clearcoat_alpha())
+			* lerp(0.04, 1.0, fresnel_h)
+			* smith_ggx(n_dot_l, 0.25)
+			* smith_ggx(n_dot_v, 0.25);
+
+		// Light the coat reflects on the way in or out never reaches the layers
+		// below, nor does light reflected by the dielectric specular reach the
+		// diffuse under it
+		let coat_reflectance = 0.25 * surface.clearcoat;
+		let beneath = (1.0 - coat_reflectance * lerp(0.04, 1.0, fresnel_l))
+			* (1.0 - coat_reflectance * lerp(0.04, 1.0, fresnel_v));
+
+		let dielectric = luminance(surface.specular_colour());
+		let under_specular =
+			(1.0 - lerp(dielectric, 1.0, fresnel_l)) * (1.0 - lerp(dielectric, 1.0, fresnel_v));
+
+		let cosine = n_dot_l * f32::consts::PI;
+
+		(
+			diffuse * (beneath * under_specular * cosine),
+			(specular * beneath + Vector3::repeat(clearcoat)) * cosine,
+		)
+	}
+}
+
+impl Material for Principled
+{
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let surface = self.surface(hit);
+
+		surface.base_colour * surface.diffuse_weight() + surface.specular_colour()
+	}
+
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
+	{
+		// Diffuse light looks much the same from everywhere
+		let view = hit.view_or_normal();
+
+		self.reflectance(view, light, hit).0
+	}
+
+	fn specular_component(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit)
+		-> Vector3<f32>
+	{
+		self.reflectance(view, light, hit).1
+	}
+
+	fn sample_direction(
+		&self,
+		view: Vector4<f32>,
+		hit: &Hit,
+		rng: &mut dyn RngCore,
+	) -> Option<(Vector4<f32>, f32)>
+	{
+		let normal = hit.normal;
+
+		if normal.dot(&view) <= 0.0 {
+			return None;
+		}
+
+		let surface = self.surface(hit);
+		let (tangent, bitangent) = hit.tangent_frame();
+		let to_world = |x: f32, y: f32, z: f32| tangent * x + bitangent * y + normal * z;
+
+		let (diffuse_chance, specular_chance, _) = surface.lobe_chances();
+		let lobe = rng.gen::<f32>();
+		let (u1, u2) = (rng.gen::<f32>(), rng.gen::<f32>());
+		let angle = 2.0 * f32::consts::PI * u2;
+
+		let light = if lobe < diffuse_chance {
+			// Cosine weighted over the hemisphere
+			let radius = u1.sqrt();
+
+			to_world(
+				radius * angle.cos(),
+				radius * angle.sin(),
+				f32::sqrt(1.0 - u1),
+			)
+		} else {
+			let half = if lobe < diffuse_chance + specular_chance {
+				// Slopes of the microfacets, stretched by the lobe's widths
+				let (alpha_x, alpha_y) = surface.alphas();
+				let slope = f32::sqrt(u1 / (1.0 - u1));
+
+				to_world(
+					alpha_x * slope * angle.cos(),
+					alpha_y * slope * angle.sin(),
+					1.0,
+				)
+			} else {
+				let alpha_squared = surface.clearcoat_alpha().powi(2);
+				let cos_theta =
+					f32::sqrt((1.0 - alpha_squared.powf(1.0 - u1)) / (1.0 - alpha_squared));
+				let sin_theta = f32::sqrt(1.0 - cos_theta * cos_theta);
+
+				to_world(sin_theta * angle.cos(), sin_theta * angle.sin(), cos_theta)
+			};
+
+			let half = half.try_normalize(f32::EPSILON)?;
+
+			half * (2.0 * view.dot(&half)) - view
+		};
+
+		let density = self.direction_density(view, light, hit);
+
+		if density > 0.0 {
+			Some((light, density))
+		} else {
+			None
+		}
+	}
+
+	fn direction_density(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit) -> f32
+	{
+		let normal = hit.normal;
+		let n_dot_l = normal.dot(&light);
+
+		if n_dot_l <= 0.0 || normal.dot(&view) <= 0.0 {
+			return 0.0;
+		}
+
+		let half = (light + view).normalize();
+		let n_dot_h = normal.dot(&half);
+		let l_dot_h = light.dot(&half).max(f32::EPSILON);
+
+		let surface = self.surface(hit);
+		let (tangent, bitangent) = hit.tangent_frame();
+		let (diffuse_chance, specular_chance, clearcoat_chance) = surface.lobe_chances();
+		let (alpha_x, alpha_y) = surface.alphas();
+
+		// Half vectors are drawn in proportion to the distribution times the
+		// cosine, reflecting about them squeezes solid angle by 4 l.h
+		let specular = ggx_anisotropic(
+			n_dot_h,
+			half.dot(&tangent),
+			half.dot(&bitangent),
+			alpha_x,
+			alpha_y,
+		);
+		let clearcoat = gtr1(n_dot_h, surface.clearcoat_alpha());
+
+		diffuse_chance * n_dot_l / f32::consts::PI
+			+ (specular_chance * specular + clearcoat_chance * clearcoat) * n_dot_h
+				/ (4.0 * l_dot_h)
+	}
+
+	fn transmission(&self, hit: &Hit) -> Option<Transmission>
+	{
+		let surface = self.surface(hit);
+		let amount = (1.0 - surface.metallic) * surface.transmission;
+
+		if amount <= 0.0 {
+			return None;
+		}
+
+		// Seen from inside, light leaves the material rather than entering it
+		let n_dot_v = hit.normal.dot(&hit.view_or_normal());
+		let refractive_index = self.inputs.refractive_index.channels(hit);
+		let fresnel = refractive_index.map(|index| {
+			if n_dot_v >= 0.0 {
+				dielectric_fresnel(n_dot_v, index)
+			} else {
+				dielectric_fresnel(-n_dot_v, 1.0 / index)
+			}
+		});
+
+		// The coat reflects some of the light before it gets through
+		let coat = 1.0 - 0.25 * surface.clearcoat * lerp(0.04, 1.0, schlick_weight(n_dot_v.abs()));
+
+		Some(Transmission {
+			tint: (surface.base_colour * (amount * coat))
+				.component_mul(&fresnel.map(|reflected| 1.0 - reflected)),
+			refractive_index: refractive_index,
+		})
+	}
+}
+
+fn lerp<T>(from: T, to: T, amount: f32) -> T
+where
+	T: ::std::ops::Mul<f32, Output = T> + ::std::ops::Add<Output = T>,
+{
+	from * (1.0 - amount) + to * amount
+}
+
+fn luminance(colour: Vector3<f32>) -> f32
+{
+	0.3 * colour.x + 0.6 * colour.y + 0.1 * colour.z
+}
+
+#[cfg(test)]
+mod tests
+{
+	use shading::testing;
+
+	use super::*;
+
+	#[test]
+	fn reflects_no_more_than_it_receives()
+	{
+		let hit = testing::hit();
+		let normal = hit.normal;
+		let views = [normal, Vector4::new(0.6, 0.0, 0.8, 0.0)];
+
+		let white = |inputs: Inputs| {
+			Principled::new(Inputs {
+				base_colour: Vector3::repeat(1.0).into(),
+				..inputs
+			})
+		};
+
+		let materials = [
+			white(Inputs::default()),
+			white(Inputs {
+				metallic: 1.0.into(),
+				roughness: 0.2.into(),
+				anisotropic: 0.8.into(),
+				..Inputs::default()
+			}),
+			white(Inputs {
+				roughness: 0.8.into(),
+				sheen: 1.0.into(),
+				clearcoat: 1.0.into(),
+				subsurface: 1.0.into(),
+				..Inputs::default()
+			}),
+		];
+
+		for material in &materials {
+			for &view in &views {
+				// Subsurface darkens surfaces seen head on
+				testing::assert_albedo_within(testing::albedo(material, view, &hit), 0.6);
+			}
+		}
+	}
+
+	#[test]
+	fn transmits_what_it_doesnt_reflect()
+	{
+		let hit = testing::hit();
+		let normal = hit.normal;
+
+		let glass = Principled::new(Inputs {
+			base_colour: Vector3::repeat(1.0).into(),
+			roughness: 0.1.into(),
+			transmission: 1.0.into(),
+			..Inputs::default()
+		});
+
+		for &view in &[normal, Vector4::new(0.6, 0.0, 0.8, 0.0)] {
+			let reflected = testing::albedo(&glass, view, &hit);
+			let transmitted = glass
+				.transmission(&Hit {
+					view: Some(view),
+					..hit
+				})
+				.unwrap()
+				.tint;
+
+			// No diffuse light is left, most of it passes through instead
+			assert!(transmitted.min() > 0.9, "{:?}", transmitted);
+			testing::assert_albedo_within(reflected + transmitted, 0.95);
+		}
+
+		// Leaving the glass at a grazing angle reflects all of the light back in
+		let inside = Hit {
+			view: Some(Vector4::new(0.8, 0.0, -0.6, 0.0)),
+			..hit
+		};
+		assert_eq!(glass.transmission(&inside).unwrap().tint, Vector3::zeros());
+
+		// Metals and opaque dielectrics let nothing through
+		assert!(Principled::new(Inputs::default())
+			.transmission(&hit)
+			.is_none());
+		assert!(Principled::new(Inputs {
+			metallic: 1.0.into(),
+			transmission: 1.0.into(),
+			..Inputs::default()
+		})
+		.transmission(&hit)
+		.is_none());
+	}
+}
@@ -0,0 +1,207 @@
+use std::fmt;
+use std::sync::Arc;
+
+use na::Vector3;
+use na::Vector4;
+use rand::RngCore;
+use shading::Material;
+use shading::Parameter;
+use Hit;
+
+#[derive(fmt::Debug)]
+pub enum Model
+{
+	// Surface of V-shaped facets, roughness is the spread of their slopes in
+	// radians, from 0 for Lambert up to about 1. Rough surfaces look flat and
+	// bright back towards the light, as clay and concrete do.
+	OrenNayar
+	{
+		roughness: Parameter,
+	},
+	// Burley's diffuse, from 0 to 1, darkening smooth surfaces at grazing
+	// angles and lifting the edges of rough ones, as on fabric
+	Retroreflective
+	{
+		roughness: Parameter,
+	},
+}
+
+impl Model
+{
+	// Reflectance relative to Lambert for light and view directions around
+	// the hit's normal
+	fn scale(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit) -> f32
+	{
+		let normal = hit.normal;
+		let n_dot_l = normal.dot(&light);
+		let n_dot_v = normal.dot(&view).max(0.0);
+
+		if n_dot_l <= 0.0 {
+			return 0.0;
+		}
+
+		match self {
+			Model::OrenNayar { roughness } => {
+				let sigma_squared = roughness.value(hit).max(0.0).powi(2);
+				let a = 1.0 - 0.5 * sigma_squared / (sigma_squared + 0.33);
+				let b = 0.45 * sigma_squared / (sigma_squared + 0.09);
+
+				// Cosine of the angle between the directions around the normal,
+				// times the sine of the larger angle to the normal and the
+				// tangent of the smaller
+				let s = light.dot(&view) - n_dot_l * n_dot_v;
+				let t = n_dot_l.max(n_dot_v);
+
+				a + b * s.max(0.0) / t
+			},
+			Model::Retroreflective { roughness } => {
+				let half = match (light + view).try_normalize(f32::EPSILON) {
+					Some(half) => half,
+					None => return 1.0,
+				};
+				let l_dot_h = light.dot(&half);
+
+				let fd90 = 0.5 + 2.0 * roughness.value(hit).clamp(0.0, 1.0) * l_dot_h * l_dot_h;
+				let weight = |cosine: f32| 1.0 + (fd90 - 1.0) * (1.0 - cosine).powi(5);
+
+				weight(n_dot_l) * weight(n_dot_v)
+			},
+		}
+	}
+}
+
+// Swaps the Lambert diffuse of another material, such as Phong or
+// Cook-Torrance, for a rough diffuse model. Everything else is left to the
+// material underneath.
+#[derive(fmt::Debug)]
+pub struct RoughDiffuse
+{
+	material: Arc<dyn Material>,
+	model: Model,
+}
+
+impl RoughDiffuse
+{
+	pub fn new(material: Arc<dyn Material>, model: Model) -> Self
+	{
+		RoughDiffuse {
+			material: material,
+			model: model,
+		}
+	}
+}
+
+impl Material for RoughDiffuse
+{
+	fn detail(&self, hit: &Hit) -> Hit
+	{
+		self.material.detail(hit)
+	}
+
+	fn emission(&self, hit: &Hit) -> Vector3<f32>
+	{
+		self.material.emission(hit)
+	}
+
+	fn is_emissive(&self) -> bool
+	{
+		self.material.is_emissive()
+	}
+
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>
+	{
+		self.material.ambient_component(hit)
+	}
+
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
+	{
+		let view = hit.view_or_normal();
+
+		self.material.diffuse_component(light, hit) * self.model.scale(view, light, hit)
+	}
+
+	fn specular_component(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit)
+		-> Vector3<f32>
+	{
+		self.material.specular_component(view, light, hit)
+	}
+
+	fn sample_direction(
+		&self,
+		view: Vector4<f32>,
+		hit: &Hit,
+		rng: &mut dyn RngCore,
+	) -> Option<(Vector4<f32>, f32)>
+	{
+		self.material.sample_direction(view, hit, rng)
+	}
+
+	fn direction_density(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit) -> f32
+	{
+		self.material.direction_density(view, light, hit)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use shading::testing;
+	use shading::Phong;
+
+	use super::*;
+
+	#[test]
+	fn rough_surfaces_scatter_back_towards_the_light()
+	{
+		let view = Vector4::new(0.8, 0.0, 0.6, 0.0);
+		let hit = Hit {
+			view: Some(view),
+			..testing::hit()
+		};
+		let lambert: Arc<dyn Material> = Arc::new(Phong::new(
+			Vector3::repeat(1.0).into(),
+			Vector3::zeros().into(),
+			1.0.into(),
+		));
+
+		let diffuse = |model: Model, light: Vector4<f32>| {
+			RoughDiffuse::new(Arc::clone(&lambert), model)
+				.diffuse_component(light, &hit)
+				.x
+		};
+
+		// Grazing light from the eye and from the far side
+		let from_eye = view;
+		let opposite = Vector4::new(-0.8, 0.0, 0.6, 0.0);
+
+		// Smooth facets are Lambert
+		let smooth = diffuse(
+			Model::OrenNayar {
+				roughness: 0.0.into(),
+			},
+			from_eye,
+		);
+		assert!((smooth - 0.6).abs() < 0.001);
+
+		let rough = |light| {
+			diffuse(
+				Model::OrenNayar {
+					roughness: 0.5.into(),
+				},
+				light,
+			)
+		};
+		assert!(rough(from_eye) > 0.6);
+		assert!(rough(opposite) < 0.6);
+
+		let retro = |light| {
+			diffuse(
+				Model::Retroreflective {
+					roughness: 1.0.into(),
+				},
+				light,
+			)
+		};
+		assert!(retro(from_eye) > retro(opposite));
+	}
+}
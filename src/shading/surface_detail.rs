@@ -0,0 +1,296 @@
+use std::fmt;
+use std::sync::Arc;
+
+use na::Vector3;
+use na::Vector4;
+use rand::RngCore;
+use shading::Material;
+use shading::Texture;
+use shading::Transmission;
+use util::math;
+use Hit;
+
+// Step in UV used to find the slope of a bump map when the hit has no
+// footprint to size it by
+const DEFAULT_BUMP_STEP: f32 = 0.0005;
+
+#[derive(fmt::Debug)]
+pub enum Detail
+{
+	// Tangent space normals stored as colours, red along U, green along V and
+	// blue out of the surface. Strength scales how far they tilt the normal.
+	NormalMap
+	{
+		texture: Arc<dyn Texture>,
+		strength: f32,
+	},
+	// Heights the surface is shaded as if it had been pushed out by along its
+	// normal, multiplied by scale to give distances in the scene
+	BumpMap
+	{
+		texture: Arc<dyn Texture>,
+		scale: f32,
+	},
+}
+
+// Adds fine detail to another material by bending the normal it shades with,
+// without changing the geometry. Surfaces without tangents are left as is.
+#[derive(fmt::Debug)]
+pub struct SurfaceDetail
+{
+	material: Arc<dyn Material>,
+	detail: Detail,
+}
+
+impl SurfaceDetail
+{
+	pub fn new(material: Arc<dyn Material>, detail: Detail) -> Self
+	{
+		SurfaceDetail {
+			material: material,
+			detail: detail,
+		}
+	}
+
+	fn detailed_normal(&self, hit: &Hit) -> Option<Vector4<f32>>
+	{
+		let (dpdu, dpdv) = hit.tangents?;
+		let normal = hit.normal;
+
+		let normal = match &self.detail {
+			Detail::NormalMap { texture, strength } => {
+				let encoded = texture.colour(hit) * 2.0 - Vector3::repeat(1.0);
+
+				// Tangent frame following U, orthogonal to the normal, with the
+				// bitangent on the same side as V so mirrored UVs still work
+				let tangent = (dpdu - normal * normal.dot(&dpdu)).try_normalize(f32::EPSILON)?;
+				let mut bitangent = math::cross_4d(normal, tangent);
+				if bitangent.dot(&dpdv) < 0.0 {
+					bitangent = -bitangent;
+				}
+
+				tangent * (encoded.x * strength)
+					+ bitangent * (encoded.y * strength)
+					+ normal * encoded.z.max(0.0)
+			},
+			Detail::BumpMap { texture, scale } => {
+				let (du, dv) = match hit.uv_derivatives {
+					Some(((dudx, dvdx), (dudy, dvdy))) => (
+						0.5 * (dudx.abs() + dudy.abs()),
+						0.5 * (dvdx.abs() + dvdy.abs()),
+					),
+					None => (0.0, 0.0),
+				};
+				let du = if du > 0.0 { du } else { DEFAULT_BUMP_STEP };
+				let dv = if dv > 0.0 { dv } else { DEFAULT_BUMP_STEP };
+
+				// Height a step along U and V away, moving the point in model
+				// space too so solid textures have a slope
+				let height = |step_u: f32, step_v: f32| {
+					let (u, v) = hit.uv;
+					let local_point = match (hit.local_point, hit.local_tangents) {
+						(Some(point), Some((along_u, along_v))) => {
+							Some(point + along_u * step_u + along_v * step_v)
+						},
+						(point, _) => point,
+					};
+					let shifted = Hit {
+						uv: (u + step_u, v + step_v),
+						local_point: local_point,
+						..*hit
+					};
+					let colour = texture.colour(&shifted);
+
+					scale * (colour.x + colour.y + colour.z) / 3.0
+				};
+
+				let base = height(0.0, 0.0);
+
+				// Moving along the surface also climbs the bumps
+				let displaced_dpdu = dpdu + normal * ((height(du, 0.0) - base) / du);
+				let displaced_dpdv = dpdv + normal * ((height(0.0, dv) - base) / dv);
+
+				let displaced = math::cross_4d(displaced_dpdu, displaced_dpdv);
+
+				if displaced.dot(&normal) < 0.0 {
+					-displaced
+				} else {
+					displaced
+				}
+			},
+		};
+
+		normal.try_normalize(f32::EPSILON)
+	}
+}
+
+impl Material for SurfaceDetail
+{
+	fn detail(&self, hit: &Hit) -> Hit
+	{
+		let hit = Hit {
+			normal: self.detailed_normal(hit).unwrap_or(hit.normal),
+			..*hit
+		};
+
+		self.material.detail(&hit)
+	}
+
+	fn emission(&self, hit: &Hit) -> Vector3<f32>
+	{
+		self.material.emission(hit)
+	}
+
+	fn is_emissive(&self) -> bool
+	{
+		self.material.is_emissive()
+	}
+
+	fn ambient_component(&self, hit: &Hit) -> Vector3<f32>
+	{
+		self.material.ambient_component(hit)
+	}
+
+	fn diffuse_component(&self, light: Vector4<f32>, hit: &Hit) -> Vector3<f32>
+	{
+		self.material.diffuse_component(light, hit)
+	}
+
+	fn specular_component(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit)
+		-> Vector3<f32>
+	{
+		self.material.specular_component(view, light, hit)
+	}
+
+	fn sample_direction(
+		&self,
+		view: Vector4<f32>,
+		hit: &Hit,
+		rng: &mut dyn RngCore,
+	) -> Option<(Vector4<f32>, f32)>
+	{
+		self.material.sample_direction(view, hit, rng)
+	}
+
+	fn direction_density(&self, view: Vector4<f32>, light: Vector4<f32>, hit: &Hit) -> f32
+	{
+		self.material.direction_density(view, light, hit)
+	}
+
+	fn transmission(&self, hit: &Hit) -> Option<Transmission>
+	{
+		self.material.transmission(hit)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use shading::testing;
+	use shading::Phong;
+
+	use super::*;
+
+	#[derive(fmt::Debug)]
+	struct Flat(Vector3<f32>);
+
+	impl Texture for Flat
+	{
+		fn colour(&self, _hit: &Hit) -> Vector3<f32>
+		{
+			self.0
+		}
+	}
+
+	// Rises along X in model space, as solid textures are sampled
+	#[derive(fmt::Debug)]
+	struct Ramp;
+
+	impl Texture for Ramp
+	{
+		fn colour(&self, hit: &Hit) -> Vector3<f32>
+		{
+			Vector3::repeat(hit.local_point.map_or(0.0, |point| point.x))
+		}
+	}
+
+	#[test]
+	fn bump_map_follows_solid_textures()
+	{
+		let material: Arc<dyn Material> = Arc::new(Phong::new(
+			Vector3::repeat(1.0).into(),
+			Vector3::zeros().into(),
+			1.0.into(),
+		));
+		let detail = Detail::BumpMap {
+			texture: Arc::new(Ramp),
+			scale: 1.0,
+		};
+
+		let hit = testing::hit();
+		let hit = Hit {
+			local_point: Some(Vector4::new(0.0, 0.0, 0.0, 1.0)),
+			local_tangents: hit.tangents,
+			..hit
+		};
+
+		// A slope of one along U tips the normal halfway back against it
+		let bumped = SurfaceDetail::new(material, detail).detail(&hit).normal;
+		let expected = Vector4::new(-1.0, 0.0, 1.0, 0.0).normalize();
+		assert!((bumped - expected).norm() < 0.001, "{:?}", bumped);
+	}
+
+	#[test]
+	fn normal_map_tilts_towards_u()
+	{
+		let material: Arc<dyn Material> = Arc::new(Phong::new(
+			Vector3::repeat(1.0).into(),
+			Vector3::zeros().into(),
+			1.0.into(),
+		));
+
+		let hit = testing::hit();
+
+		let normal_map = |colour: Vector3<f32>| {
+			let detail = Detail::NormalMap {
+				texture: Arc::new(Flat(colour)),
+				strength: 1.0,
+			};
+
+			SurfaceDetail::new(Arc::clone(&material), detail)
+				.detail(&hit)
+				.normal
+		};
+
+		// Straight out of the surface leaves the normal alone
+		let unchanged = normal_map(Vector3::new(0.5, 0.5, 1.0));
+		assert!((unchanged - hit.normal).norm() < 0.001);
+
+		let tilted = normal_map(Vector3::new(1.0, 0.5, 0.5));
+		assert!((tilted - Vector4::new(1.0, 0.0, 0.0, 0.0)).norm() < 0.001);
+
+		// A flat bump map has no slope to bend the normal with
+		let detail = Detail::BumpMap {
+			texture: Arc::new(Flat(Vector3::repeat(0.5))),
+			scale: 1.0,
+		};
+		let bumped = SurfaceDetail::new(Arc::clone(&material), detail)
+			.detail(&hit)
+			.normal;
+		assert!((bumped - hit.normal).norm() < 0.001);
+
+		// Surfaces without tangents have no frame to bend in
+		let no_tangents = Hit {
+			tangents: None,
+			..hit
+		};
+		let detail = Detail::NormalMap {
+			texture: Arc::new(Flat(Vector3::new(1.0, 0.5, 0.5))),
+			strength: 1.0,
+		};
+		let unchanged = SurfaceDetail::new(material, detail)
+			.detail(&no_tangents)
+			.normal;
+		assert!((unchanged - hit.normal).norm() < 0.001);
+	}
+}
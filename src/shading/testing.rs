@@ -0,0 +1,57 @@
+use std::f32;
+
+use na::Vector3;
+use na::Vector4;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use shading::Material;
+use Hit;
+
+// Hit on a surface facing +Z, with unit tangents along X and Y
+pub fn hit() -> Hit
+{
+	Hit::new(
+		1.0,
+		Vector4::new(0.0, 0.0, 1.0, 0.0),
+		(0.0, 0.0),
+		Some((
+			Vector4::new(1.0, 0.0, 0.0, 0.0),
+			Vector4::new(0.0, 1.0, 0.0, 0.0),
+		)),
+	)
+}
+
+// Fraction of light from everywhere reflected towards the view, estimated
+// with the material's own samples
+pub fn albedo(material: &dyn Material, view: Vector4<f32>, hit: &Hit) -> Vector3<f32>
+{
+	let hit = Hit {
+		view: Some(view),
+		..*hit
+	};
+	let mut rng = StdRng::seed_from_u64(1);
+	let samples = 20000;
+
+	let total = (0..samples).fold(Vector3::zeros(), |total, _| {
+		match material.sample_direction(view, &hit, &mut rng) {
+			Some((light, density)) => {
+				let reflected = material.diffuse_component(light, &hit)
+					+ material.specular_component(view, light, &hit);
+
+				// Components carry a factor of pi, as lights do
+				total + reflected / (f32::consts::PI * density)
+			},
+			None => total,
+		}
+	});
+
+	total / samples as f32
+}
+
+// Checks an albedo lies between the lowest expected and a little over one,
+// allowing for noise in the estimate
+pub fn assert_albedo_within(albedo: Vector3<f32>, lowest: f32)
+{
+	assert!(albedo.max() <= 1.02, "albedo {:?}", albedo);
+	assert!(albedo.min() >= lowest, "albedo {:?}", albedo);
+}
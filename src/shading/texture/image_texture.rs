@@ -0,0 +1,342 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use image;
+use na::Vector3;
+use ray::UvDerivatives;
+use shading::texture::Texture;
+use Hit;
+
+// How UVs outside [0, 1] are brought back onto the image
+#[derive(Clone, Copy, fmt::Debug, PartialEq)]
+pub enum WrapMode
+{
+	Repeat,
+	Clamp,
+	Mirror,
+}
+
+impl WrapMode
+{
+	// Texel index for a column or row that may lie off the image
+	fn wrap(self, index: i64, size: u32) -> usize
+	{
+		let size = i64::from(size);
+
+		let index = match self {
+			WrapMode::Repeat => index.rem_euclid(size),
+			WrapMode::Clamp => index.clamp(0, size - 1),
+			WrapMode::Mirror => {
+				let index = index.rem_euclid(2 * size);
+
+				if index < size {
+					index
+				} else {
+					2 * size - 1 - index
+				}
+			},
+		};
+
+		index as usize
+	}
+}
+
+// Most texels averaged along the longer axis of a pixel's footprint, beyond
+// this the footprint is widened and the texture blurs instead
+const MAX_ANISOTROPY: f32 = 8.0;
+
+// One level of the mip pyramid, level 0 is the image itself and each level
+// after it is half the size of the one before
+#[derive(fmt::Debug)]
+struct MipLevel
+{
+	width: u32,
+	height: u32,
+	// Linear colours, row by row from the top of the image
+	texels: Vec<Vector3<f32>>,
+}
+
+impl MipLevel
+{
+	// Averages each 2x2 block of texels, odd rows and columns at the edge are
+	// averaged with themselves
+	fn downsample(&self) -> Self
+	{
+		let width = (self.width / 2).max(1);
+		let height = (self.height / 2).max(1);
+
+		let texel = |x: u32, y: u32| {
+			let x = x.min(self.width - 1) as usize;
+			let y = y.min(self.height - 1) as usize;
+
+			self.texels[y * self.width as usize + x]
+		};
+
+		let mut texels = Vec::with_capacity((width * height) as usize);
+
+		for y in 0..height {
+			for x in 0..width {
+				let (x, y) = (2 * x, 2 * y);
+
+				texels.push(
+					(texel(x, y) + texel(x + 1, y) + texel(x, y + 1) + texel(x + 1, y + 1)) / 4.0,
+				);
+			}
+		}
+
+		MipLevel {
+			width: width,
+			height: height,
+			texels: texels,
+		}
+	}
+
+	fn texel(&self, x: i64, y: i64, wrap: WrapMode) -> Vector3<f32>
+	{
+		let x = wrap.wrap(x, self.width);
+		let y = wrap.wrap(y, self.height);
+
+		self.texels[y * self.width as usize + x]
+	}
+
+	fn bilinear(&self, u: f32, v: f32, wrap: WrapMode) -> Vector3<f32>
+	{
+		// Texel centres lie at half integer positions
+		let x = u * self.width as f32 - 0.5;
+		let y = (1.0 - v) * self.height as f32 - 0.5;
+
+		let (x0, y0) = (x.floor(), y.floor());
+		let (fx, fy) = (x - x0, y - y0);
+		let (x0, y0) = (x0 as i64, y0 as i64);
+
+		let top = self.texel(x0, y0, wrap) * (1.0 - fx) + self.texel(x0 + 1, y0, wrap) * fx;
+		let bottom =
+			self.texel(x0, y0 + 1, wrap) * (1.0 - fx) + self.texel(x0 + 1, y0 + 1, wrap) * fx;
+
+		top * (1.0 - fy) + bottom * fy
+	}
+}
+
+// Image mapped once over UV space with V pointing up the image. Hits that
+// know their footprint are filtered from a mip pyramid built the first time
+// one is needed, other hits are filtered bilinearly from the image.
+#[derive(fmt::Debug)]
+pub struct ImageTexture
+{
+	image: MipLevel,
+	// Levels 1 and up
+	mip_levels: OnceLock<Vec<MipLevel>>,
+	wrap: WrapMode,
+}
+
+impl ImageTexture
+{
+	// Images holding colours are usually stored as sRGB and need decoding
+	// before they are lit, images holding data such as roughness are linear
+	pub fn from_file(file_name: &str, wrap: WrapMode, srgb: bool)
+		-> Result<Self, image::ImageError>
+	{
+		let image = image::open(file_name)?.to_rgb();
+		let (width, height) = image.dimensions();
+
+		let decode = |value: u8| {
+			let value = f32::from(value) / 255.0;
+
+			if srgb {
+				srgb_to_linear(value)
+			} else {
+				value
+			}
+		};
+
+		let texels = image
+			.pixels()
+			.map(|pixel| Vector3::new(decode(pixel[0]), decode(pixel[1]), decode(pixel[2])))
+			.collect();
+
+		Ok(ImageTexture::new(width, height, texels, wrap))
+	}
+
+	fn new(width: u32, height: u32, texels: Vec<Vector3<f32>>, wrap: WrapMode) -> Self
+	{
+		ImageTexture {
+			image: MipLevel {
+				width: width,
+				height: height,
+				texels: texels,
+			},
+			mip_levels: OnceLock::new(),
+			wrap: wrap,
+		}
+	}
+
+	fn level(&self, index: usize) -> &MipLevel
+	{
+		if index == 0 {
+			return &self.image;
+		}
+
+		let mip_levels = self.mip_levels.get_or_init(|| {
+			let mut levels: Vec<MipLevel> = Vec::new();
+
+			loop {
+				let previous = levels.last().unwrap_or(&self.image);
+
+				if previous.width == 1 && previous.height == 1 {
+					break;
+				}
+
+				let next = previous.downsample();
+				levels.push(next);
+			}
+
+			levels
+		});
+
+		mip_levels
+			.get(index - 1)
+			.or_else(|| mip_levels.last())
+			.unwrap_or(&self.image)
+	}
+
+	// Blends the two levels either side of a fractional level
+	fn trilinear(&self, u: f32, v: f32, level: f32) -> Vector3<f32>
+	{
+		let lower = level.floor();
+		let blend = level - lower;
+		let lower = lower as usize;
+
+		let colour = self.level(lower).bilinear(u, v, self.wrap);
+
+		if blend <= 0.0 {
+			return colour;
+		}
+
+		colour * (1.0 - blend) + self.level(lower + 1).bilinear(u, v, self.wrap) * blend
+	}
+
+	pub fn sample(&self, u: f32, v: f32) -> Vector3<f32>
+	{
+		self.image.bilinear(u, v, self.wrap)
+	}
+
+	// The footprint of a pixel is an ellipse in UV with axes along the two
+	// derivatives. The level is picked to fit the shorter axis and samples are
+	// spread along the longer one, as graphics hardware does.
+	pub fn sample_footprint(&self, u: f32, v: f32, derivatives: UvDerivatives) -> Vector3<f32>
+	{
+		let ((dudx, dvdx), (dudy, dvdy)) = derivatives;
+
+		let width = self.image.width as f32;
+		let height = self.image.height as f32;
+
+		let length_x = f32::hypot(dudx * width, dvdx * height);
+		let length_y = f32::hypot(dudy * width, dvdy * height);
+
+		let (major, (major_u, major_v), minor) = if length_x >= length_y {
+			(length_x, (dudx, dvdx), length_y)
+		} else {
+			(length_y, (dudy, dvdy), length_x)
+		};
+
+		if !major.is_finite() {
+			return self.sample(u, v);
+		}
+
+		let minor = minor.max(major / MAX_ANISOTROPY);
+
+		// Magnified, or covering less than a texel, the image itself is fine
+		if major <= 1.0 {
+			return self.sample(u, v);
+		}
+
+		let level = minor.max(1.0).log2();
+		let samples = (major / minor).ceil().clamp(1.0, MAX_ANISOTROPY) as usize;
+
+		let total = (0..samples).fold(Vector3::zeros(), |total, sample| {
+			// Evenly spaced across the longer axis, centred on the hit
+			let offset = (sample as f32 + 0.5) / samples as f32 - 0.5;
+
+			total + self.trilinear(u + major_u * offset, v + major_v * offset, level)
+		});
+
+		total / samples as f32
+	}
+}
+
+impl Texture for ImageTexture
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		match hit.uv_derivatives {
+			Some(derivatives) => self.sample_footprint(hit.uv.0, hit.uv.1, derivatives),
+			None => self.sample(hit.uv.0, hit.uv.1),
+		}
+	}
+}
+
+fn srgb_to_linear(value: f32) -> f32
+{
+	if value <= 0.04045 {
+		value / 12.92
+	} else {
+		((value + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn wrap_modes_and_filtering()
+	{
+		// Black on the left, white on the right
+		let texture = ImageTexture::new(
+			2,
+			1,
+			vec![Vector3::zeros(), Vector3::repeat(1.0)],
+			WrapMode::Clamp,
+		);
+
+		assert!((texture.sample(0.25, 0.5).x - 0.0).abs() < 0.001);
+		assert!((texture.sample(0.5, 0.5).x - 0.5).abs() < 0.001);
+		assert!((texture.sample(0.75, 0.5).x - 1.0).abs() < 0.001);
+		assert!((texture.sample(1.5, 0.5).x - 1.0).abs() < 0.001);
+
+		assert_eq!(WrapMode::Repeat.wrap(-1, 4), 3);
+		assert_eq!(WrapMode::Repeat.wrap(5, 4), 1);
+		assert_eq!(WrapMode::Clamp.wrap(-1, 4), 0);
+		assert_eq!(WrapMode::Clamp.wrap(5, 4), 3);
+		assert_eq!(WrapMode::Mirror.wrap(-1, 4), 0);
+		assert_eq!(WrapMode::Mirror.wrap(5, 4), 2);
+
+		assert!((srgb_to_linear(1.0) - 1.0).abs() < 0.001);
+		assert!((srgb_to_linear(0.5) - 0.214).abs() < 0.001);
+	}
+
+	#[test]
+	fn footprints_average_what_they_cover()
+	{
+		// A footprint covering the whole checker sees its average
+		let checker = (0..16)
+			.map(|index| Vector3::repeat(((index + index / 4) % 2) as f32))
+			.collect();
+		let texture = ImageTexture::new(4, 4, checker, WrapMode::Repeat);
+
+		assert!((texture.sample(0.375, 0.375).x - texture.sample(0.625, 0.375).x).abs() > 0.99);
+		assert!(
+			(texture
+				.sample_footprint(0.375, 0.375, ((1.0, 0.0), (0.0, 1.0)))
+				.x - 0.5)
+				.abs() < 0.001
+		);
+		assert!(
+			(texture
+				.sample_footprint(0.375, 0.375, ((1.0, 0.0), (0.0, 0.05)))
+				.x - 0.5)
+				.abs() < 0.1
+		);
+	}
+}
@@ -0,0 +1,88 @@
+pub use self::image_texture::ImageTexture;
+pub use self::image_texture::WrapMode;
+pub use self::nodes::Coordinates;
+pub use self::nodes::Fresnel;
+pub use self::nodes::Mapping;
+pub use self::nodes::Mix;
+pub use self::nodes::Multiply;
+pub use self::nodes::Remap;
+pub use self::procedural::Checker;
+pub use self::procedural::Colours;
+pub use self::procedural::NoiseTexture;
+pub use self::procedural::NoiseType;
+pub use self::procedural::Pattern;
+pub use self::procedural::PatternType;
+pub use self::procedural::Space;
+
+pub mod image_texture;
+pub mod nodes;
+pub mod procedural;
+
+use std::fmt;
+use std::sync::Arc;
+
+use na::Vector3;
+use spectrum;
+use Hit;
+
+// Colour that varies over a surface, looked up from the hit being shaded
+pub trait Texture: Send + Sync + fmt::Debug
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>;
+}
+
+// Material parameter given either as a constant or as a texture. Scalar
+// parameters use the same type, textured ones are read from the average of
+// the channels so greyscale images give the grey level.
+#[derive(Clone, fmt::Debug)]
+pub enum Parameter
+{
+	Constant(Vector3<f32>),
+	Texture(Arc<dyn Texture>),
+}
+
+impl Parameter
+{
+	// Colour at the hit, as a spectrum over the hit's wavelengths when
+	// rendering spectrally
+	pub fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		spectrum::colour(self.rgb(hit), hit)
+	}
+
+	// Colour at the hit as given, in RGB. Textures always work in RGB, even
+	// when they are built from other parameters.
+	pub fn rgb(&self, hit: &Hit) -> Vector3<f32>
+	{
+		match self {
+			Parameter::Constant(colour) => *colour,
+			Parameter::Texture(texture) => texture.colour(&Hit {
+				wavelengths: None,
+				..*hit
+			}),
+		}
+	}
+
+	pub fn value(&self, hit: &Hit) -> f32
+	{
+		let colour = self.rgb(hit);
+
+		(colour.x + colour.y + colour.z) / 3.0
+	}
+}
+
+impl From<Vector3<f32>> for Parameter
+{
+	fn from(colour: Vector3<f32>) -> Self
+	{
+		Parameter::Constant(colour)
+	}
+}
+
+impl From<f32> for Parameter
+{
+	fn from(value: f32) -> Self
+	{
+		Parameter::Constant(Vector3::repeat(value))
+	}
+}
@@ -0,0 +1,251 @@
+use std::fmt;
+
+use na::Vector3;
+use shading::texture::Texture;
+use shading::Parameter;
+use Hit;
+
+// Nodes for building material inputs out of other inputs. Each node is a
+// texture whose inputs are parameters, so constants, textures and other
+// nodes can be wired into one another and on into a material.
+
+// Blends from a to b by factor, per channel
+#[derive(fmt::Debug)]
+pub struct Mix
+{
+	a: Parameter,
+	b: Parameter,
+	factor: Parameter,
+}
+
+impl Mix
+{
+	pub fn new(a: Parameter, b: Parameter, factor: Parameter) -> Self
+	{
+		Mix {
+			a: a,
+			b: b,
+			factor: factor,
+		}
+	}
+}
+
+impl Texture for Mix
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let factor = self.factor.colour(hit);
+
+		self.a
+			.colour(hit)
+			.component_mul(&(Vector3::repeat(1.0) - factor))
+			+ self.b.colour(hit).component_mul(&factor)
+	}
+}
+
+#[derive(fmt::Debug)]
+pub struct Multiply
+{
+	a: Parameter,
+	b: Parameter,
+}
+
+impl Multiply
+{
+	pub fn new(a: Parameter, b: Parameter) -> Self
+	{
+		Multiply { a: a, b: b }
+	}
+}
+
+impl Texture for Multiply
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		self.a.colour(hit).component_mul(&self.b.colour(hit))
+	}
+}
+
+// Maps each channel from one range onto another, clamped to the new range
+#[derive(fmt::Debug)]
+pub struct Remap
+{
+	input: Parameter,
+	from: (f32, f32),
+	to: (f32, f32),
+}
+
+impl Remap
+{
+	pub fn new(input: Parameter, from: (f32, f32), to: (f32, f32)) -> Self
+	{
+		Remap {
+			input: input,
+			from: from,
+			to: to,
+		}
+	}
+}
+
+impl Texture for Remap
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let (from_min, from_max) = self.from;
+		let (to_min, to_max) = self.to;
+
+		self.input.colour(hit).map(|value| {
+			let amount = ((value - from_min) / (from_max - from_min)).clamp(0.0, 1.0);
+
+			if amount.is_finite() {
+				to_min + amount * (to_max - to_min)
+			} else {
+				to_min
+			}
+		})
+	}
+}
+
+// Schlick's approximation of the light reflected by a dielectric with the
+// given refractive index, rising from head on to grazing angles
+#[derive(fmt::Debug)]
+pub struct Fresnel
+{
+	refractive_index: f32,
+}
+
+impl Fresnel
+{
+	pub fn new(refractive_index: f32) -> Self
+	{
+		Fresnel {
+			refractive_index: refractive_index,
+		}
+	}
+}
+
+impl Texture for Fresnel
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let f0 = ((self.refractive_index - 1.0) / (self.refractive_index + 1.0)).powi(2);
+
+		// Hits that aren't being shaded are treated as seen head on
+		let cosine = hit
+			.view
+			.map_or(1.0, |view| view.dot(&hit.normal).abs().min(1.0));
+
+		Vector3::repeat(f0 + (1.0 - f0) * (1.0 - cosine).powi(5))
+	}
+}
+
+#[derive(Clone, Copy, fmt::Debug, PartialEq)]
+pub enum Coordinates
+{
+	// (u, v, 0)
+	Uv,
+	// Model space position of the hit, (u, v, 0) when it isn't known
+	ObjectPosition,
+	// Shading normal in world space
+	WorldNormal,
+}
+
+impl Texture for Coordinates
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let uv = Vector3::new(hit.uv.0, hit.uv.1, 0.0);
+
+		match self {
+			Coordinates::Uv => uv,
+			Coordinates::ObjectPosition => hit.local_point.map_or(uv, |point| point.xyz()),
+			Coordinates::WorldNormal => hit.normal.xyz(),
+		}
+	}
+}
+
+// Looks up a texture somewhere else: the first two channels of coordinates
+// become the UV and all three the object position, so UV and solid textures
+// can both be moved, scaled or distorted by other nodes
+#[derive(fmt::Debug)]
+pub struct Mapping
+{
+	texture: Parameter,
+	coordinates: Parameter,
+}
+
+impl Mapping
+{
+	pub fn new(texture: Parameter, coordinates: Parameter) -> Self
+	{
+		Mapping {
+			texture: texture,
+			coordinates: coordinates,
+		}
+	}
+}
+
+impl Texture for Mapping
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let coordinates = self.coordinates.colour(hit);
+
+		// The footprint no longer matches the new UVs
+		let mapped = Hit {
+			uv: (coordinates.x, coordinates.y),
+			uv_derivatives: None,
+			local_point: Some(coordinates.insert_row(3, 1.0)),
+			..*hit
+		};
+
+		self.texture.colour(&mapped)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use na::Vector4;
+	use shading::testing;
+
+	use super::*;
+
+	#[test]
+	fn nodes_combine_their_inputs()
+	{
+		let hit = Hit {
+			uv: (0.25, 0.75),
+			local_point: Some(Vector4::new(1.0, 2.0, 3.0, 1.0)),
+			view: Some(Vector4::new(0.0, 0.0, 1.0, 0.0)),
+			..testing::hit()
+		};
+
+		let red = Parameter::from(Vector3::new(1.0, 0.0, 0.0));
+		let blue = Parameter::from(Vector3::new(0.0, 0.0, 1.0));
+
+		let mix = Mix::new(red.clone(), blue, Parameter::from(0.25));
+		assert!((mix.colour(&hit) - Vector3::new(0.75, 0.0, 0.25)).norm() < 0.001);
+
+		let multiply = Multiply::new(red, Parameter::from(0.5));
+		assert!((multiply.colour(&hit) - Vector3::new(0.5, 0.0, 0.0)).norm() < 0.001);
+
+		// Inputs can be other nodes
+		let uv = Parameter::Texture(std::sync::Arc::new(Coordinates::Uv));
+		let remap = Remap::new(uv, (0.0, 0.5), (1.0, 3.0));
+		assert!((remap.colour(&hit) - Vector3::new(2.0, 3.0, 1.0)).norm() < 0.001);
+
+		let position = Coordinates::ObjectPosition.colour(&hit);
+		assert!((position - Vector3::new(1.0, 2.0, 3.0)).norm() < 0.001);
+
+		// Glass reflects 4% head on and everything at grazing angles
+		let fresnel = Fresnel::new(1.5);
+		assert!((fresnel.colour(&hit).x - 0.04).abs() < 0.001);
+
+		let grazing = Hit {
+			view: Some(Vector4::new(1.0, 0.0, 0.0, 0.0)),
+			..hit
+		};
+		assert!((fresnel.colour(&grazing).x - 1.0).abs() < 0.001);
+	}
+}
@@ -0,0 +1,220 @@
+use std::f32;
+use std::fmt;
+
+use na::Vector3;
+use shading::texture::Texture;
+use shading::Parameter;
+use util::noise::Noise;
+use Hit;
+
+// Where a procedural texture is evaluated. Solid textures use the hit point in
+// the model space of the object, so they are carved out of it and move with
+// it. UV textures use (u, v, 0) and follow the surface's parameterization.
+#[derive(Clone, Copy, fmt::Debug, PartialEq)]
+pub enum Space
+{
+	Object,
+	Uv,
+}
+
+impl Space
+{
+	fn point(self, hit: &Hit) -> Vector3<f32>
+	{
+		match (self, hit.local_point) {
+			(Space::Object, Some(point)) => point.xyz(),
+			_ => Vector3::new(hit.uv.0, hit.uv.1, 0.0),
+		}
+	}
+}
+
+// The two colours a pattern moves between, either of which can be a texture
+#[derive(fmt::Debug)]
+pub struct Colours
+{
+	low: Parameter,
+	high: Parameter,
+}
+
+impl Colours
+{
+	pub fn new(low: Parameter, high: Parameter) -> Self
+	{
+		Colours {
+			low: low,
+			high: high,
+		}
+	}
+
+	fn blend(&self, hit: &Hit, amount: f32) -> Vector3<f32>
+	{
+		let amount = amount.clamp(0.0, 1.0);
+
+		self.low.colour(hit) * (1.0 - amount) + self.high.colour(hit) * amount
+	}
+}
+
+// Alternating cubes, or squares in UV, with frequency of them per unit
+#[derive(fmt::Debug)]
+pub struct Checker
+{
+	colours: Colours,
+	frequency: f32,
+	space: Space,
+}
+
+impl Checker
+{
+	pub fn new(colours: Colours, frequency: f32, space: Space) -> Self
+	{
+		Checker {
+			colours: colours,
+			frequency: frequency,
+			space: space,
+		}
+	}
+}
+
+impl Texture for Checker
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let cell = (self.space.point(hit) * self.frequency).map(|value| value.floor() as i64);
+
+		// The UV plane sits at z = 0, so only u and v change the parity there
+		let parity = (cell.x + cell.y + cell.z).rem_euclid(2);
+
+		self.colours.blend(hit, parity as f32)
+	}
+}
+
+#[derive(Clone, Copy, fmt::Debug, PartialEq)]
+pub enum NoiseType
+{
+	Perlin,
+	Fbm,
+	Turbulence,
+	Worley,
+}
+
+// Noise mapped from its range onto the blend between the two colours
+#[derive(fmt::Debug)]
+pub struct NoiseTexture
+{
+	noise: Noise,
+	noise_type: NoiseType,
+	colours: Colours,
+	frequency: f32,
+	octaves: u32,
+	space: Space,
+}
+
+impl NoiseTexture
+{
+	pub fn new(
+		noise_type: NoiseType,
+		colours: Colours,
+		frequency: f32,
+		octaves: u32,
+		seed: u64,
+		space: Space,
+	) -> Self
+	{
+		NoiseTexture {
+			noise: Noise::new(seed),
+			noise_type: noise_type,
+			colours: colours,
+			frequency: frequency,
+			octaves: octaves,
+			space: space,
+		}
+	}
+}
+
+impl Texture for NoiseTexture
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let point = self.space.point(hit) * self.frequency;
+
+		let amount = match self.noise_type {
+			NoiseType::Perlin => 0.5 + 0.5 * self.noise.perlin(point),
+			NoiseType::Fbm => 0.5 + 0.5 * self.noise.fbm(point, self.octaves),
+			NoiseType::Turbulence => self.noise.turbulence(point, self.octaves),
+			NoiseType::Worley => self.noise.worley(point),
+		};
+
+		self.colours.blend(hit, amount)
+	}
+}
+
+#[derive(Clone, Copy, fmt::Debug, PartialEq)]
+pub enum PatternType
+{
+	// Veins running across X, bent by turbulence
+	Marble,
+	// Growth rings around the Y axis, warped by noise, with the high colour at
+	// the dark edge of each ring
+	Wood,
+}
+
+// Natural materials built from noise, mapped onto the blend between the two
+// colours
+#[derive(fmt::Debug)]
+pub struct Pattern
+{
+	pattern_type: PatternType,
+	noise: Noise,
+	colours: Colours,
+	frequency: f32,
+	octaves: u32,
+	space: Space,
+}
+
+impl Pattern
+{
+	pub fn new(
+		pattern_type: PatternType,
+		colours: Colours,
+		frequency: f32,
+		octaves: u32,
+		seed: u64,
+		space: Space,
+	) -> Self
+	{
+		Pattern {
+			pattern_type: pattern_type,
+			noise: Noise::new(seed),
+			colours: colours,
+			frequency: frequency,
+			octaves: octaves,
+			space: space,
+		}
+	}
+}
+
+impl Texture for Pattern
+{
+	fn colour(&self, hit: &Hit) -> Vector3<f32>
+	{
+		let point = self.space.point(hit) * self.frequency;
+
+		let amount = match self.pattern_type {
+			PatternType::Marble => {
+				let phase = point.x + 6.0 * self.noise.turbulence(point, self.octaves);
+
+				(0.5 + 0.5 * f32::sin(phase * f32::consts::PI)).powf(0.5)
+			},
+			PatternType::Wood => {
+				// Stretched along the grain so the warping runs with it
+				let grain = Vector3::new(point.x, point.y * 0.25, point.z);
+				let rings =
+					f32::hypot(point.x, point.z) + 0.3 * self.noise.fbm(grain, self.octaves);
+
+				(rings - rings.floor()).powi(3)
+			},
+		};
+
+		self.colours.blend(hit, amount)
+	}
+}
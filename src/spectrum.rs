@@ -0,0 +1,379 @@
+use std::f32;
+use std::fmt;
+use std::sync::OnceLock;
+
+use na::Matrix3;
+use na::Vector3;
+use Hit;
+
+// Range of visible wavelengths traced in spectral mode, in nanometres
+pub const MIN_WAVELENGTH: f32 = 380.0;
+pub const MAX_WAVELENGTH: f32 = 730.0;
+
+// Sets of three wavelengths shaded for each pixel in spectral mode, more give
+// less colour noise
+pub const SAMPLES: usize = 4;
+
+// Wavelengths standing in for the red, green and blue channels when indices
+// of refraction that vary with wavelength are used in RGB mode
+const RGB_WAVELENGTHS: [f32; 3] = [610.0, 550.0, 465.0];
+
+// Reflectance spectra from Smits' "An RGB-to-Spectrum Conversion for
+// Reflectances", sampled at the centres of ten bins spanning 380 to 720nm
+const SMITS_FIRST: f32 = 397.0;
+const SMITS_STEP: f32 = 34.0;
+const SMITS_WHITE: [f32; 10] = [
+	1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f32; 10] = [
+	0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f32; 10] = [
+	1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const SMITS_YELLOW: [f32; 10] = [
+	0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f32; 10] = [
+	0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f32; 10] = [
+	0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f32; 10] = [
+	1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+// Complex refractive indices of common metals, the real part n then the
+// extinction coefficient k, approximated from Johnson and Christy's
+// measurements and Rakić's for aluminium
+const MEASURED_FIRST: f32 = 400.0;
+const MEASURED_STEP: f32 = 50.0;
+
+const METALS: [(&str, [f32; 7], [f32; 7]); 6] = [
+	(
+		"gold",
+		[1.66, 1.50, 0.97, 0.43, 0.25, 0.17, 0.16],
+		[1.96, 1.88, 1.87, 2.45, 2.98, 3.32, 3.80],
+	),
+	(
+		"silver",
+		[0.05, 0.04, 0.05, 0.06, 0.06, 0.05, 0.04],
+		[2.10, 2.65, 3.09, 3.59, 4.01, 4.48, 4.84],
+	),
+	(
+		"copper",
+		[1.18, 1.24, 1.12, 0.95, 0.27, 0.21, 0.21],
+		[2.21, 2.40, 2.57, 2.58, 3.41, 3.67, 4.21],
+	),
+	(
+		"aluminium",
+		[0.49, 0.62, 0.77, 0.96, 1.20, 1.47, 1.83],
+		[4.86, 5.47, 6.08, 6.69, 7.26, 7.79, 8.31],
+	),
+	(
+		"chrome",
+		[2.03, 2.35, 2.66, 2.99, 3.18, 3.25, 3.23],
+		[2.95, 3.16, 3.30, 3.33, 3.33, 3.30, 3.32],
+	),
+	(
+		"titanium",
+		[2.10, 2.20, 2.34, 2.48, 2.60, 2.70, 2.78],
+		[2.35, 2.42, 2.62, 2.88, 3.08, 3.24, 3.38],
+	),
+];
+
+// Refractive index and extinction coefficient of the named metal
+pub fn metal(name: &str) -> Option<(RefractiveIndex, RefractiveIndex)>
+{
+	METALS
+		.iter()
+		.find(|(metal, _, _)| *metal == name)
+		.map(|(_, n, k)| (RefractiveIndex::measured(n), RefractiveIndex::measured(k)))
+}
+
+// Names of the metals with measured indices
+pub fn metal_names() -> impl Iterator<Item = &'static str>
+{
+	METALS.iter().map(|(name, _, _)| *name)
+}
+
+// Three wavelengths spread evenly over the visible range from one offset
+// between 0 and 1, so one random number samples the whole range
+pub fn hero_wavelengths(offset: f32) -> Vector3<f32>
+{
+	let range = MAX_WAVELENGTH - MIN_WAVELENGTH;
+
+	Vector3::from_fn(|channel, _| MIN_WAVELENGTH + range * (offset + channel as f32) / 3.0)
+}
+
+// Colour given in RGB as seen by the hit being shaded: unchanged in RGB mode,
+// otherwise its spectrum at the wavelengths each channel carries
+pub fn colour(rgb: Vector3<f32>, hit: &Hit) -> Vector3<f32>
+{
+	match hit.wavelengths {
+		Some(wavelengths) => wavelengths.map(|wavelength| from_rgb(rgb, wavelength)),
+		None => rgb,
+	}
+}
+
+// Value at the wavelength of a smooth spectrum with the given colour, after
+// Smits. Reflectances between 0 and 1 stay between 0 and 1, and scaling the
+// colour scales the spectrum, so lights can be converted the same way.
+pub fn from_rgb(rgb: Vector3<f32>, wavelength: f32) -> f32
+{
+	let rgb = rgb.map(|channel| channel.max(0.0));
+	let (r, g, b) = (rgb.x, rgb.y, rgb.z);
+	let at = |spectrum: &[f32; 10]| interpolate(spectrum, SMITS_FIRST, SMITS_STEP, wavelength);
+
+	if r <= g && r <= b {
+		r * at(&SMITS_WHITE)
+			+ if g <= b {
+				(g - r) * at(&SMITS_CYAN) + (b - g) * at(&SMITS_BLUE)
+			} else {
+				(b - r) * at(&SMITS_CYAN) + (g - b) * at(&SMITS_GREEN)
+			}
+	} else if g <= r && g <= b {
+		g * at(&SMITS_WHITE)
+			+ if r <= b {
+				(r - g) * at(&SMITS_MAGENTA) + (b - r) * at(&SMITS_BLUE)
+			} else {
+				(b - g) * at(&SMITS_MAGENTA) + (r - b) * at(&SMITS_RED)
+			}
+	} else {
+		b * at(&SMITS_WHITE)
+			+ if r <= g {
+				(r - b) * at(&SMITS_YELLOW) + (g - r) * at(&SMITS_GREEN)
+			} else {
+				(g - b) * at(&SMITS_YELLOW) + (r - g) * at(&SMITS_RED)
+			}
+	}
+}
+
+// Linear sRGB that light of the given radiance at each of the channels'
+// wavelengths contributes to a pixel. Averaged over wavelengths drawn evenly
+// across the visible range, light that is the same at every wavelength comes
+// out white.
+pub fn to_rgb(radiance: Vector3<f32>, wavelengths: Vector3<f32>) -> Vector3<f32>
+{
+	let response = (0..3).fold(Vector3::zeros(), |response, channel| {
+		response + sensor_response(wavelengths[channel]) * radiance[channel]
+	});
+
+	response.component_div(white()) / 3.0
+}
+
+// Linear sRGB the eye sees from light of a single wavelength, through the CIE
+// 1931 colour matching functions
+fn sensor_response(wavelength: f32) -> Vector3<f32>
+{
+	let xyz_to_rgb = Matrix3::new(
+		3.240_454, -1.537_138, -0.498_531, -0.969_266, 1.876_011, 0.041_556, 0.055_643, -0.204_026,
+		1.057_225,
+	);
+
+	xyz_to_rgb * colour_matching(wavelength)
+}
+
+// Response to light that is the same at every wavelength
+fn white() -> &'static Vector3<f32>
+{
+	static WHITE: OnceLock<Vector3<f32>> = OnceLock::new();
+
+	WHITE.get_or_init(|| {
+		let steps = (MAX_WAVELENGTH - MIN_WAVELENGTH) as usize;
+
+		let total = (0..steps).fold(Vector3::zeros(), |total, step| {
+			total + sensor_response(MIN_WAVELENGTH + step as f32 + 0.5)
+		});
+
+		total / steps as f32
+	})
+}
+
+// CIE 1931 colour matching functions, from the multi-lobe fit of Wyman, Sloan
+// and Shirley's "Simple Analytic Approximations to the CIE XYZ Color Matching
+// Functions"
+fn colour_matching(wavelength: f32) -> Vector3<f32>
+{
+	let lobe = |centre: f32, below: f32, above: f32| {
+		let width = if wavelength < centre { below } else { above };
+		let t = (wavelength - centre) / width;
+
+		f32::exp(-0.5 * t * t)
+	};
+
+	Vector3::new(
+		1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+			- 0.065 * lobe(501.1, 20.4, 26.2),
+		0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1),
+		1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8),
+	)
+}
+
+// Linear interpolation between samples taken every step nanometres from the
+// first, holding the ends beyond the first and last samples
+fn interpolate(samples: &[f32], first: f32, step: f32, wavelength: f32) -> f32
+{
+	let last = samples.len() - 1;
+	let position = ((wavelength - first) / step).clamp(0.0, last as f32);
+
+	let index = (position as usize).min(last - 1);
+	let t = position - index as f32;
+
+	samples[index] * (1.0 - t) + samples[index + 1] * t
+}
+
+// Index of refraction that may vary with wavelength
+#[derive(Clone, Copy, fmt::Debug)]
+pub enum RefractiveIndex
+{
+	Constant(f32),
+	// Cauchy's equation, a + b / wavelength squared with the wavelength in
+	// micrometres, as for glass. BK7 is about 1.5046 and 0.0042.
+	Cauchy(f32, f32),
+	// Measured every MEASURED_STEP nanometres from MEASURED_FIRST, along with
+	// its averages over the red, green and blue channels for RGB mode
+	Measured(&'static [f32], Vector3<f32>),
+}
+
+impl RefractiveIndex
+{
+	// Measured index, averaged over each channel by how strongly the channel
+	// responds to each wavelength
+	pub fn measured(samples: &'static [f32]) -> Self
+	{
+		let steps = (MAX_WAVELENGTH - MIN_WAVELENGTH) as usize;
+
+		let (total, weights) = (0..steps).fold(
+			(Vector3::zeros(), Vector3::zeros()),
+			|(total, weights): (Vector3<f32>, Vector3<f32>), step| {
+				let wavelength = MIN_WAVELENGTH + step as f32 + 0.5;
+				let weight = sensor_response(wavelength).map(|response| response.max(0.0));
+				let index = interpolate(samples, MEASURED_FIRST, MEASURED_STEP, wavelength);
+
+				(total + weight * index, weights + weight)
+			},
+		);
+
+		RefractiveIndex::Measured(samples, total.component_div(&weights))
+	}
+
+	pub fn at(&self, wavelength: f32) -> f32
+	{
+		match *self {
+			RefractiveIndex::Constant(index) => index,
+			RefractiveIndex::Cauchy(a, b) => {
+				let micrometres = wavelength / 1000.0;
+
+				a + b / (micrometres * micrometres)
+			},
+			RefractiveIndex::Measured(samples, _) => {
+				interpolate(samples, MEASURED_FIRST, MEASURED_STEP, wavelength)
+			},
+		}
+	}
+
+	// Index for each colour channel of the hit being shaded. In RGB mode
+	// measured indices give their averages, others are taken at wavelengths
+	// standing in for red, green and blue.
+	pub fn channels(&self, hit: &Hit) -> Vector3<f32>
+	{
+		match (*self, hit.wavelengths) {
+			(RefractiveIndex::Measured(_, averages), None) => averages,
+			(_, wavelengths) => wavelengths
+				.unwrap_or_else(|| Vector3::from(RGB_WAVELENGTHS))
+				.map(|wavelength| self.at(wavelength)),
+		}
+	}
+}
+
+impl From<f32> for RefractiveIndex
+{
+	fn from(index: f32) -> Self
+	{
+		RefractiveIndex::Constant(index)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use shading::testing;
+
+	use super::*;
+
+	// Colour a spectrum with the given colour comes back as, averaged over
+	// wavelengths spread across the visible range
+	fn round_trip(rgb: Vector3<f32>) -> Vector3<f32>
+	{
+		let samples = 200;
+
+		(0..samples).fold(Vector3::zeros(), |total, sample| {
+			let wavelengths = hero_wavelengths((sample as f32 + 0.5) / samples as f32);
+			let radiance = wavelengths.map(|wavelength| from_rgb(rgb, wavelength));
+
+			total + to_rgb(radiance, wavelengths) / samples as f32
+		})
+	}
+
+	#[test]
+	fn colours_survive_the_trip_through_spectra()
+	{
+		let white = round_trip(Vector3::repeat(1.0));
+		assert!((white - Vector3::repeat(1.0)).norm() < 0.01, "{:?}", white);
+
+		// Scaling the colour scales the spectrum
+		let bright = round_trip(Vector3::repeat(4.0));
+		assert!(
+			(bright - Vector3::repeat(4.0)).norm() < 0.04,
+			"{:?}",
+			bright
+		);
+
+		// Saturated colours keep their hue, if not all of their saturation
+		let colours = [
+			Vector3::new(0.8, 0.2, 0.1),
+			Vector3::new(0.1, 0.6, 0.2),
+			Vector3::new(0.2, 0.3, 0.9),
+		];
+
+		for colour in &colours {
+			let back = round_trip(*colour);
+
+			assert!(
+				(back - colour).norm() < 0.15,
+				"{:?} came back as {:?}",
+				colour,
+				back
+			);
+			assert_eq!(back.imax(), colour.imax());
+		}
+
+		let glass = RefractiveIndex::Cauchy(1.5046, 0.0042);
+		assert!(glass.at(450.0) > glass.at(650.0));
+	}
+
+	#[test]
+	fn measured_indices_average_over_channels()
+	{
+		let (n, k) = metal("gold").unwrap();
+		let hit = testing::hit();
+
+		// Gold reflects red far better than blue at normal incidence
+		let reflectance =
+			|n: f32, k: f32| ((n - 1.0).powi(2) + k * k) / ((n + 1.0).powi(2) + k * k);
+		let (n_rgb, k_rgb) = (n.channels(&hit), k.channels(&hit));
+		assert!(reflectance(n_rgb.x, k_rgb.x) > 0.85);
+		assert!(reflectance(n_rgb.z, k_rgb.z) < 0.5);
+
+		// Spectral hits take the curve at their own wavelengths
+		let hit = Hit {
+			wavelengths: Some(Vector3::new(400.0, 425.0, 700.0)),
+			..hit
+		};
+		assert!((n.channels(&hit) - Vector3::new(1.66, 1.58, 0.16)).norm() < 0.001);
+
+		assert!(metal("unobtainium").is_none());
+	}
+}
@@ -1,4 +1,7 @@
+use std::f32;
+
 use na::Matrix4;
+use na::Vector3;
 use na::Vector4;
 use na::U3;
 
@@ -44,6 +47,73 @@ pub fn far_from_zero_neg(number: f32) -> bool
 	number < -EPSILON
 }
 
+// Range of ray parameters spent inside an axis aligned box, None if the ray
+// misses it or the box is entirely behind the ray
+pub fn ray_box_intersection(
+	origin: Vector3<f32>,
+	direction: Vector3<f32>,
+	lower: Vector3<f32>,
+	upper: Vector3<f32>,
+) -> Option<(f32, f32)>
+{
+	let mut t_min = f32::NEG_INFINITY;
+	let mut t_max = f32::INFINITY;
+
+	for axis in 0..3 {
+		let inv_direction = 1.0 / direction[axis];
+		let t0 = (lower[axis] - origin[axis]) * inv_direction;
+		let t1 = (upper[axis] - origin[axis]) * inv_direction;
+
+		t_min = f32::max(t_min, f32::min(t0, t1));
+		t_max = f32::min(t_max, f32::max(t0, t1));
+	}
+
+	if t_min > t_max || t_max < 0.0 {
+		None
+	} else {
+		Some((t_min, t_max))
+	}
+}
+
+// Moller-Trumbore intersection returning the intersect and barycentric
+// coordinates of the second and third vertices
+pub fn ray_triangle_intersection(
+	origin: Vector3<f32>,
+	direction: Vector3<f32>,
+	v0: Vector3<f32>,
+	v1: Vector3<f32>,
+	v2: Vector3<f32>,
+) -> Option<(f32, f32, f32)>
+{
+	let edge1 = v1 - v0;
+	let edge2 = v2 - v0;
+
+	let h = direction.cross(&edge2);
+	let a = edge1.dot(&h);
+
+	// Rays nearly parallel to the triangle give unstable crossings
+	if near_zero(a) {
+		return None;
+	}
+
+	let f = 1.0 / a;
+	let s = origin - v0;
+	let u = f * s.dot(&h);
+
+	if !(0.0..=1.0).contains(&u) {
+		return None;
+	}
+
+	let q = s.cross(&edge1);
+	let v = f * direction.dot(&q);
+
+	if v < 0.0 || u + v > 1.0 {
+		return None;
+	}
+
+	Some((f * edge2.dot(&q), u, v))
+}
+
 pub enum QuadRoots
 {
 	Two(f32, f32),
@@ -75,3 +145,189 @@ pub fn quadratic_roots(a: f32, b: f32, c: f32) -> QuadRoots
 		}
 	}
 }
+
+// Real roots of a*x^4 + b*x^3 + c*x^2 + d*x + e in ascending order.
+//
+// The closed form (Ferrari) solution loses most of its precision to
+// cancellation, which shows up as speckled holes in tori. Instead the roots
+// are isolated between the critical points of the polynomial, found
+// recursively from its derivatives, and each bracket is refined with a
+// safeguarded Newton iteration.
+pub fn quartic_roots(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64>
+{
+	polynomial_roots(&[a, b, c, d, e])
+}
+
+// Real roots of a polynomial with coefficients given from the highest degree
+// down, in ascending order
+pub fn polynomial_roots(coefficients: &[f64]) -> Vec<f64>
+{
+	let coefficients = match coefficients.iter().position(|c| *c != 0.0) {
+		Some(first_non_zero) => &coefficients[first_non_zero..],
+		None => return Vec::new(),
+	};
+
+	match coefficients.len() {
+		0 | 1 => return Vec::new(),
+		2 => return vec![-coefficients[1] / coefficients[0]],
+		_ => (),
+	}
+
+	let degree = coefficients.len() - 1;
+
+	let derivative: Vec<f64> = coefficients[..degree]
+		.iter()
+		.enumerate()
+		.map(|(i, c)| c * (degree - i) as f64)
+		.collect();
+
+	// Cauchy's bound, every real root lies within [-bound, bound]
+	let bound = 1.0
+		+ coefficients[1..]
+			.iter()
+			.fold(0.0, |max, c| f64::max(max, f64::abs(c / coefficients[0])));
+
+	let mut points = vec![-bound];
+	points.extend(
+		polynomial_roots(&derivative)
+			.into_iter()
+			.filter(|x| x.abs() < bound),
+	);
+	points.push(bound);
+
+	let scale = coefficients
+		.iter()
+		.fold(0.0, |max, c| f64::max(max, c.abs()));
+	let tolerance = scale * 1e-12;
+
+	let mut roots: Vec<f64> = Vec::new();
+
+	for window in points.windows(2) {
+		let (lo, hi) = (window[0], window[1]);
+		let (p_lo, _) = evaluate_polynomial(coefficients, lo);
+		let (p_hi, _) = evaluate_polynomial(coefficients, hi);
+
+		// Critical points that touch zero are double roots with no sign change
+		let root = if p_lo.abs() <= tolerance {
+			Some(lo)
+		} else if p_lo.signum() != p_hi.signum() {
+			Some(refine_root(coefficients, lo, hi, p_lo))
+		} else {
+			None
+		};
+
+		if let Some(root) = root {
+			let is_duplicate = roots
+				.last()
+				.is_some_and(|last| (root - last).abs() <= 1e-9 * (1.0 + root.abs()));
+
+			if !is_duplicate {
+				roots.push(root);
+			}
+		}
+	}
+
+	roots
+}
+
+// Returns the value of the polynomial and its derivative at x
+fn evaluate_polynomial(coefficients: &[f64], x: f64) -> (f64, f64)
+{
+	coefficients
+		.iter()
+		.fold((0.0, 0.0), |(p, dp), c| (p * x + c, dp * x + p))
+}
+
+fn refine_root(coefficients: &[f64], mut lo: f64, mut hi: f64, p_lo: f64) -> f64
+{
+	let lo_sign = p_lo.signum();
+	let mut x = 0.5 * (lo + hi);
+
+	for _ in 0..100 {
+		let (p, dp) = evaluate_polynomial(coefficients, x);
+
+		if p == 0.0 {
+			break;
+		}
+
+		if p.signum() == lo_sign {
+			lo = x;
+		} else {
+			hi = x;
+		}
+
+		// Take the Newton step when it stays inside the bracket, otherwise bisect
+		let newton = x - p / dp;
+		let next = if dp != 0.0 && newton > lo && newton < hi {
+			newton
+		} else {
+			0.5 * (lo + hi)
+		};
+
+		let converged = (next - x).abs() <= 1e-14 * (1.0 + x.abs());
+		x = next;
+
+		if converged {
+			break;
+		}
+	}
+
+	x
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn assert_roots(found: Vec<f64>, expected: &[f64])
+	{
+		assert_eq!(found.len(), expected.len(), "roots found: {:?}", found);
+
+		for (f, e) in found.iter().zip(expected) {
+			assert!((f - e).abs() < 1e-6, "expected {} found {}", e, f);
+		}
+	}
+
+	#[test]
+	fn quartic_four_distinct_roots()
+	{
+		// (x - 1)(x - 2)(x - 3)(x - 4)
+		assert_roots(
+			quartic_roots(1.0, -10.0, 35.0, -50.0, 24.0),
+			&[1.0, 2.0, 3.0, 4.0],
+		);
+	}
+
+	#[test]
+	fn quartic_double_and_complex_roots()
+	{
+		// (x + 2)^2 (x^2 + 1)
+		assert_roots(quartic_roots(1.0, 4.0, 5.0, 4.0, 4.0), &[-2.0]);
+
+		// (x^2 + 1)(x^2 + 4)
+		assert_roots(quartic_roots(1.0, 0.0, 5.0, 0.0, 4.0), &[]);
+	}
+
+	#[test]
+	fn quartic_degenerates_to_lower_degree()
+	{
+		// (x - 1)(x + 1)(x - 5)
+		assert_roots(quartic_roots(0.0, 1.0, -5.0, -1.0, 5.0), &[-1.0, 1.0, 5.0]);
+	}
+
+	#[test]
+	fn quartic_widely_spaced_roots()
+	{
+		// (x - 0.001)(x - 1)(x - 100)(x - 1000), similar to a ray grazing a torus
+		let r = [0.001, 1.0, 100.0, 1000.0];
+		let a = 1.0;
+		let b = -(r[0] + r[1] + r[2] + r[3]);
+		let c = r[0] * r[1] + r[0] * r[2] + r[0] * r[3] + r[1] * r[2] + r[1] * r[3] + r[2] * r[3];
+		let d =
+			-(r[0] * r[1] * r[2] + r[0] * r[1] * r[3] + r[0] * r[2] * r[3] + r[1] * r[2] * r[3]);
+		let e = r[0] * r[1] * r[2] * r[3];
+
+		assert_roots(quartic_roots(a, b, c, d, e), &r);
+	}
+}
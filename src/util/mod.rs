@@ -1 +1,3 @@
 pub mod math;
+pub mod noise;
+pub mod scatter;
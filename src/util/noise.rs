@@ -0,0 +1,189 @@
+use std::fmt;
+
+use na::Vector3;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+// Improved Perlin noise along with the fractal sums and cellular noise built
+// on the same hash. Different seeds shuffle the hash, giving unrelated noise.
+#[derive(fmt::Debug)]
+pub struct Noise
+{
+	// The permutation twice over so lookups can add offsets without wrapping
+	permutation: [u8; 512],
+}
+
+impl Noise
+{
+	pub fn new(seed: u64) -> Self
+	{
+		let mut values: Vec<u8> = (0..=255).collect();
+		values.shuffle(&mut StdRng::seed_from_u64(seed));
+
+		let mut permutation = [0; 512];
+
+		for (index, entry) in permutation.iter_mut().enumerate() {
+			*entry = values[index % 256];
+		}
+
+		Noise {
+			permutation: permutation,
+		}
+	}
+
+	fn hash(&self, x: i32, y: i32, z: i32) -> usize
+	{
+		let x = self.permutation[(x & 255) as usize] as usize;
+		let y = self.permutation[x + (y & 255) as usize] as usize;
+
+		self.permutation[y + (z & 255) as usize] as usize
+	}
+
+	// Smooth noise in [-1, 1] that is zero at every integer point
+	pub fn perlin(&self, point: Vector3<f32>) -> f32
+	{
+		let cell = point.map(f32::floor);
+		let (x, y, z) = (cell.x as i32, cell.y as i32, cell.z as i32);
+
+		let local = point - cell;
+		let fade = local.map(|t| t * t * t * (t * (t * 6.0 - 15.0) + 10.0));
+
+		let corner = |dx: i32, dy: i32, dz: i32| {
+			let offset = local - Vector3::new(dx as f32, dy as f32, dz as f32);
+
+			gradient(self.hash(x + dx, y + dy, z + dz), offset)
+		};
+
+		let lerp = |t: f32, a: f32, b: f32| a + t * (b - a);
+
+		let near = lerp(
+			fade.y,
+			lerp(fade.x, corner(0, 0, 0), corner(1, 0, 0)),
+			lerp(fade.x, corner(0, 1, 0), corner(1, 1, 0)),
+		);
+		let far = lerp(
+			fade.y,
+			lerp(fade.x, corner(0, 0, 1), corner(1, 0, 1)),
+			lerp(fade.x, corner(0, 1, 1), corner(1, 1, 1)),
+		);
+
+		lerp(fade.z, near, far)
+	}
+
+	// Fractional Brownian motion, octaves of noise each at twice the frequency
+	// and half the amplitude of the last, scaled back into [-1, 1]
+	pub fn fbm(&self, point: Vector3<f32>, octaves: u32) -> f32
+	{
+		self.octaves(point, octaves, |value| value)
+	}
+
+	// As fbm but summing the magnitude of each octave, giving creases where the
+	// noise crosses zero. Lies in [0, 1].
+	pub fn turbulence(&self, point: Vector3<f32>, octaves: u32) -> f32
+	{
+		self.octaves(point, octaves, f32::abs)
+	}
+
+	fn octaves<F: Fn(f32) -> f32>(&self, point: Vector3<f32>, octaves: u32, shape: F) -> f32
+	{
+		let mut total = 0.0;
+		let mut amplitude = 1.0;
+		let mut total_amplitude = 0.0;
+		let mut point = point;
+
+		for _ in 0..octaves.max(1) {
+			total += shape(self.perlin(point)) * amplitude;
+			total_amplitude += amplitude;
+
+			amplitude *= 0.5;
+			point *= 2.0;
+		}
+
+		total / total_amplitude
+	}
+
+	// Worley noise, the distance to the nearest of a set of points scattered
+	// one to each unit cell. Mostly lies in [0, 1].
+	pub fn worley(&self, point: Vector3<f32>) -> f32
+	{
+		let cell = point.map(f32::floor);
+		let (x, y, z) = (cell.x as i32, cell.y as i32, cell.z as i32);
+
+		let mut nearest = f32::INFINITY;
+
+		for dz in -1..=1 {
+			for dy in -1..=1 {
+				for dx in -1..=1 {
+					let hash = self.hash(x + dx, y + dy, z + dz);
+
+					// Further hashes of the cell give each coordinate of its point
+					let feature = Vector3::new(
+						self.permutation[hash] as f32 / 256.0,
+						self.permutation[hash + 1] as f32 / 256.0,
+						self.permutation[hash + 2] as f32 / 256.0,
+					);
+
+					let offset =
+						cell + Vector3::new(dx as f32, dy as f32, dz as f32) + feature - point;
+					nearest = nearest.min(offset.norm());
+				}
+			}
+		}
+
+		nearest
+	}
+}
+
+// Dot product with one of the twelve edge directions of a cube, picked by hash
+fn gradient(hash: usize, offset: Vector3<f32>) -> f32
+{
+	let (x, y, z) = (offset.x, offset.y, offset.z);
+
+	match hash & 15 {
+		0 | 12 => x + y,
+		1 | 14 => -x + y,
+		2 => x - y,
+		3 => -x - y,
+		4 => x + z,
+		5 => -x + z,
+		6 => x - z,
+		7 => -x - z,
+		8 => y + z,
+		9 | 13 => -y + z,
+		10 => y - z,
+		_ => -y - z,
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn noise_is_seeded_and_bounded()
+	{
+		let noise = Noise::new(1);
+		let other = Noise::new(2);
+
+		let point = Vector3::new(1.3, 2.7, -0.4);
+
+		assert_eq!(noise.perlin(point), Noise::new(1).perlin(point));
+		assert!(noise.perlin(point) != other.perlin(point));
+		assert_eq!(noise.perlin(Vector3::new(3.0, -2.0, 5.0)), 0.0);
+
+		for index in 0..1000 {
+			let point = Vector3::new(
+				index as f32 * 0.37,
+				index as f32 * 0.11,
+				index as f32 * -0.23,
+			);
+
+			assert!(noise.perlin(point).abs() <= 1.0);
+			assert!(noise.fbm(point, 4).abs() <= 1.0);
+			assert!((0.0..=1.0).contains(&noise.turbulence(point, 4)));
+			assert!(noise.worley(point) < f32::sqrt(3.0));
+		}
+	}
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::f32;
+
+use image;
+use image::GrayImage;
+use na::Matrix4;
+use na::Rotation3;
+use na::Vector3;
+use primitives::Primitive;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use util::math;
+
+// Candidate points drawn per requested instance when some points can be
+// rejected, by a density map or by spacing
+const CANDIDATES_PER_INSTANCE: usize = 16;
+
+// Greyscale image laid over a surface from above, white is full density and
+// black is none. The image covers the surface's extents in X and Z, with the
+// top of the image towards negative Z.
+pub struct DensityMap
+{
+	image: GrayImage,
+}
+
+impl DensityMap
+{
+	pub fn from_file(file_name: &str) -> Result<Self, image::ImageError>
+	{
+		Ok(DensityMap {
+			image: image::open(file_name)?.to_luma(),
+		})
+	}
+
+	// Density at a point given as fractions of the image's width and height
+	fn density(&self, u: f32, v: f32) -> f32
+	{
+		let (width, height) = self.image.dimensions();
+
+		let x = ((u * width as f32) as u32).min(width - 1);
+		let y = ((v * height as f32) as u32).min(height - 1);
+
+		self.image.get_pixel(x, y)[0] as f32 / 255.0
+	}
+}
+
+pub struct Options
+{
+	pub count: usize,
+	pub seed: u64,
+	// Rotate each instance by a random angle about its up axis
+	pub random_rotation: bool,
+	// Each instance is scaled uniformly by a random amount in this range
+	pub scale_range: (f32, f32),
+	// Point the up axis of each instance along the surface normal instead of +Y
+	pub align_to_normal: bool,
+	// Poisson disk spacing, no two instances are closer than this
+	pub min_distance: Option<f32>,
+	pub density: Option<DensityMap>,
+}
+
+// Places instances over the surface of a primitive. The returned transforms
+// take each instance from its own space to the space the surface is placed in
+// by surface_transform. There may be fewer than requested when a density map
+// or spacing rules out too many points. None if the surface can't be sampled.
+pub fn scatter(
+	surface: &dyn Primitive,
+	surface_transform: Matrix4<f32>,
+	options: &Options,
+) -> Option<Vec<Matrix4<f32>>>
+{
+	let mut rng = StdRng::seed_from_u64(options.seed);
+
+	let candidate_count = if options.density.is_some() || options.min_distance.is_some() {
+		options.count * CANDIDATES_PER_INSTANCE
+	} else {
+		options.count
+	};
+
+	let candidates = surface.sample_surface(candidate_count, &mut rng)?;
+	let (lower, upper) = surface.get_extents();
+
+	let normal_transform = surface_transform.try_inverse()?;
+
+	let mut spacing = options.min_distance.map(Spacing::new);
+	let mut placements = Vec::with_capacity(options.count);
+
+	for (position, normal) in candidates {
+		if placements.len() == options.count {
+			break;
+		}
+
+		if let Some(density_map) = &options.density {
+			let u = (position.x - lower.x) / (upper.x - lower.x);
+			let v = (position.z - lower.z) / (upper.z - lower.z);
+
+			let density = density_map.density(
+				if u.is_finite() {
+					u.clamp(0.0, 1.0)
+				} else {
+					0.5
+				},
+				if v.is_finite() {
+					v.clamp(0.0, 1.0)
+				} else {
+					0.5
+				},
+			);
+
+			if rng.gen::<f32>() >= density {
+				continue;
+			}
+		}
+
+		let position = (surface_transform * position).xyz();
+
+		if let Some(spacing) = &mut spacing {
+			if !spacing.insert(position) {
+				continue;
+			}
+		}
+
+		let up = if options.align_to_normal {
+			math::transform_normals(normal, normal_transform)
+				.xyz()
+				.normalize()
+		} else {
+			Vector3::y()
+		};
+
+		let angle = if options.random_rotation {
+			rng.gen::<f32>() * 2.0 * f32::consts::PI
+		} else {
+			0.0
+		};
+
+		let (min_scale, max_scale) = options.scale_range;
+		let scale = min_scale + rng.gen::<f32>() * (max_scale - min_scale);
+
+		// Up along the normal, then spun about it and scaled
+		let alignment = Rotation3::rotation_between(&Vector3::y(), &up)
+			.unwrap_or_else(|| Rotation3::from_axis_angle(&Vector3::x_axis(), f32::consts::PI));
+		let spin = Rotation3::from_axis_angle(&Vector3::y_axis(), angle);
+
+		placements.push(
+			Matrix4::new_translation(&position)
+				* (alignment * spin).to_homogeneous()
+				* Matrix4::new_scaling(scale),
+		);
+	}
+
+	Some(placements)
+}
+
+// Accepted points hashed into cells the size of the spacing, so only the
+// neighbouring cells need checking for points that are too close
+struct Spacing
+{
+	min_distance: f32,
+	cells: HashMap<(i64, i64, i64), Vec<Vector3<f32>>>,
+}
+
+impl Spacing
+{
+	fn new(min_distance: f32) -> Self
+	{
+		Spacing {
+			min_distance: min_distance,
+			cells: HashMap::new(),
+		}
+	}
+
+	// Adds the point unless it is too close to one added before
+	fn insert(&mut self, point: Vector3<f32>) -> bool
+	{
+		let cell = point.map(|component| (component / self.min_distance).floor() as i64);
+
+		for z in (cell.z - 1)..=(cell.z + 1) {
+			for y in (cell.y - 1)..=(cell.y + 1) {
+				for x in (cell.x - 1)..=(cell.x + 1) {
+					let too_close = self.cells.get(&(x, y, z)).is_some_and(|points| {
+						points
+							.iter()
+							.any(|other| (other - point).norm() < self.min_distance)
+					});
+
+					if too_close {
+						return false;
+					}
+				}
+			}
+		}
+
+		self.cells
+			.entry((cell.x, cell.y, cell.z))
+			.or_default()
+			.push(point);
+
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use primitives::Sphere;
+
+	use super::*;
+
+	#[test]
+	fn spacing_keeps_points_apart()
+	{
+		let mut spacing = Spacing::new(1.0);
+
+		assert!(spacing.insert(Vector3::new(0.0, 0.0, 0.0)));
+		assert!(!spacing.insert(Vector3::new(0.9, 0.0, 0.0)));
+		assert!(!spacing.insert(Vector3::new(-0.5, 0.5, -0.5)));
+		assert!(spacing.insert(Vector3::new(1.1, 0.0, 0.0)));
+		assert!(spacing.insert(Vector3::new(-0.5, -0.9, -0.5)));
+	}
+
+	#[test]
+	fn needs_a_surface_that_can_be_sampled()
+	{
+		let options = Options {
+			count: 10,
+			seed: 0,
+			random_rotation: false,
+			scale_range: (1.0, 1.0),
+			align_to_normal: false,
+			min_distance: None,
+			density: None,
+		};
+		let sphere = Sphere::new(Vector3::zeros(), 1.0);
+		assert!(scatter(&sphere, Matrix4::identity(), &options).is_none());
+	}
+}
@@ -0,0 +1,30 @@
+-- Bicubic Bezier patches loaded from a .bpt file, tessellated at two rates.
+-- Shading uses the exact surface normals either way.
+
+gold = gr.cook_torrance({0.72, 0.45, 0.2}, {0.72, 0.45, 0.2}, 0.2, 0.3, 0.863, 2.639)
+blue = gr.material({0.2, 0.3, 0.9}, {0.5, 0.5, 0.5}, 25)
+grey = gr.material({0.6, 0.6, 0.6}, {0.0, 0.0, 0.0}, 0)
+
+scene = gr.node('scene')
+
+ground = gr.plane('ground')
+ground:set_material(grey)
+scene:add_child(ground)
+
+-- The optional last argument sets how many quads each patch is split into
+-- along each side for intersection
+coarse = gr.bezier('coarse', 'test/assets/goblet.bpt', 3)
+coarse:set_material(blue)
+coarse:scale(1.5, 1.5, 1.5)
+coarse:translate(-2.5, 0, 0)
+scene:add_child(coarse)
+
+fine = gr.bezier('fine', 'test/assets/goblet.bpt', 16)
+fine:set_material(gold)
+fine:scale(1.5, 1.5, 1.5)
+fine:translate(2.5, 0, 0)
+scene:add_child(fine)
+
+gr.render(scene, 'bezier.png', 512, 512,
+	  {0, 5, 12}, {0, -0.25, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
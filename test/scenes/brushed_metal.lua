@@ -0,0 +1,30 @@
+-- Anisotropic Cook-Torrance: plain aluminium, then brushed along and across
+-- the sphere's U direction, then brushed in a swirl set by a noise texture.
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(gr.material({0.4, 0.4, 0.4}, {0, 0, 0}, 1))
+scene:add_child(floor)
+
+aluminium = {0.91, 0.92, 0.92}
+swirl = gr.noise{type = 'fbm', colours = {{0, 0, 0}, {1, 1, 1}}, frequency = 2, octaves = 3, seed = 3}
+
+materials = {
+	gr.cook_torrance({0, 0, 0}, aluminium, 0, 0.25, 1.2, 7.0),
+	gr.cook_torrance({0, 0, 0}, aluminium, 0, 0.25, 1.2, 7.0, 0.9),
+	gr.cook_torrance({0, 0, 0}, aluminium, 0, 0.25, 1.2, 7.0, 0.9, 0.25),
+	gr.cook_torrance({0, 0, 0}, aluminium, 0, 0.25, 1.2, 7.0, 0.9, swirl),
+}
+
+for i, material in ipairs(materials) do
+	ball = gr.sphere('ball' .. i)
+	ball:translate((i - 2.5) * 2.4, 1, 0)
+	ball:set_material(material)
+	scene:add_child(ball)
+end
+
+gr.render(scene, 'brushed_metal.png', 768, 256,
+	  {0, 2.5, 9}, {0, -0.2, -1}, {0, 1, 0}, 45,
+	  {0.1, 0.1, 0.1}, {gr.light({-6, 8, 8}, {0.9, 0.9, 0.9}, {1, 0, 0}),
+	  gr.light({6, 4, 8}, {0.5, 0.5, 0.6}, {1, 0, 0})})
@@ -0,0 +1,70 @@
+-- Constructive solid geometry: a drilled plate, a lens and a bolt.
+
+steel = gr.cook_torrance({0.56, 0.57, 0.58}, {0.56, 0.57, 0.58}, 0.3, 0.3, 2.5, 3.0)
+brass = gr.material({0.8, 0.6, 0.2}, {0.6, 0.6, 0.4}, 40)
+glass = gr.material({0.6, 0.8, 0.9}, {0.8, 0.8, 0.8}, 80)
+red = gr.material({0.8, 0.2, 0.2}, {0.5, 0.5, 0.5}, 25)
+grey = gr.material({0.6, 0.6, 0.6}, {0.0, 0.0, 0.0}, 0)
+
+scene = gr.node('scene')
+
+ground = gr.plane('ground')
+ground:set_material(grey)
+scene:add_child(ground)
+
+-- A plate with two holes, the inside of the holes is painted red
+plate = gr.cube('plate')
+plate:set_material(steel)
+plate:translate(-0.5, -0.5, -0.5)
+plate:scale(4, 0.5, 3)
+
+hole1 = gr.cylinder('hole1', 0.5, 2)
+hole1:set_material(red)
+hole1:translate(-1, 0, 0)
+
+hole2 = gr.cylinder('hole2', 0.5, 2)
+hole2:set_material(red)
+hole2:translate(1, 0, 0)
+
+drilled = gr.difference('drilled', plate, hole1, hole2)
+drilled:rotate('X', 20)
+drilled:translate(-3.5, 1, 0)
+scene:add_child(drilled)
+
+-- A lens from the intersection of two spheres
+left = gr.sphere('left')
+left:scale(2, 2, 2)
+left:translate(-1.6, 0, 0)
+
+right = gr.sphere('right')
+right:scale(2, 2, 2)
+right:translate(1.6, 0, 0)
+
+lens = gr.intersection('lens', left, right)
+lens:set_material(glass)
+lens:rotate('Y', 60)
+lens:translate(1, 2, 0)
+scene:add_child(lens)
+
+-- A bolt from a hexagonal head, cut from three rotated slabs, and a shaft
+function hex_slab(name, angle)
+	local slab = gr.cube(name)
+	slab:translate(-0.5, -0.5, -0.5)
+	slab:scale(1.2, 0.5, 3)
+	slab:rotate('Y', angle)
+	return slab
+end
+
+head = gr.intersection('head', hex_slab('h1', 0), hex_slab('h2', 60), hex_slab('h3', 120))
+
+shaft = gr.cylinder('shaft', 0.3, 2)
+shaft:translate(0, -1.25, 0)
+
+bolt = gr.union('bolt', head, shaft)
+bolt:set_material(brass)
+bolt:translate(4, 2.5, 0)
+scene:add_child(bolt)
+
+gr.render(scene, 'csg.png', 512, 512,
+	  {0, 5, 12}, {0, -0.3, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
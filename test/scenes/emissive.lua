@@ -0,0 +1,56 @@
+-- Lit only by glowing objects: a neon tube made from a mesh, a glowing orb and
+-- a lamp panel overhead. Each is sampled as a light, giving soft shadows.
+
+grey = gr.material({0.6, 0.6, 0.6}, {0.2, 0.2, 0.2}, 20)
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(grey)
+scene:add_child(floor)
+
+wall = gr.plane('wall')
+wall:rotate('X', 90)
+wall:translate(0, 0, -4)
+wall:set_material(grey)
+scene:add_child(wall)
+
+-- Neon tube lying along X, pink, drawn as a thin mesh cylinder
+neon = gr.mesh('neon', 'test/assets/cylinder.obj')
+neon:scale(0.08, 3, 0.08)
+neon:rotate('Z', 90)
+neon:translate(1.5, 2.5, -3.2)
+neon:set_material(gr.emissive({1.0, 0.2, 0.6}, 12))
+scene:add_child(neon)
+
+-- Warm orb, glowing on top of a glossy surface
+orb = gr.sphere('orb')
+orb:scale(0.4, 0.4, 0.4)
+orb:translate(-2, 0.4, 0)
+orb:set_material(gr.emissive({1.0, 0.6, 0.2}, 9, gr.material({0.2, 0.2, 0.2}, {0.5, 0.5, 0.5}, 50)))
+scene:add_child(orb)
+
+-- Panel overhead, its glow set by a texture node
+panel = gr.mesh('panel', 'test/assets/plane.obj')
+panel:scale(0.8, 1, 0.8)
+panel:translate(1, 4, 0)
+panel:set_material(gr.emissive(gr.mix({0.6, 0.8, 1.0}, {1, 1, 1}, 0.5), 6))
+scene:add_child(panel)
+
+function add(node, x, z)
+	node:set_material(grey)
+	node:translate(x, 0.7, z)
+	scene:add_child(node)
+end
+
+add(gr.sphere('ball'), 0.5, 0.5)
+
+block = gr.cube('block')
+block:translate(-0.5, -0.5, -0.5)
+block:scale(1, 1.4, 1)
+block:rotate('Y', 30)
+add(block, 2.5, -1.5)
+
+gr.render(scene, 'emissive.png', 512, 512,
+	  {0, 3, 10}, {0, -0.2, -1}, {0, 1, 0}, 50,
+	  {0.02, 0.02, 0.02}, {})
@@ -0,0 +1,69 @@
+-- Curves scattered over meshes: a patch of grass blades and a furry cow.
+-- Strands are shaded with the Kajiya-Kay hair model, and the cow's follow
+-- the sampled surface normals.
+
+math.randomseed(7)
+
+grass = gr.hair({0.15, 0.55, 0.1}, {0.3, 0.4, 0.2}, 30)
+fur = gr.hair({0.55, 0.35, 0.2}, {0.4, 0.35, 0.3}, 40)
+hide = gr.material({0.45, 0.28, 0.16}, {0.1, 0.1, 0.1}, 10)
+soil = gr.material({0.3, 0.22, 0.15}, {0.0, 0.0, 0.0}, 0)
+
+scene = gr.node('scene')
+
+ground = gr.plane('ground')
+ground:set_material(soil)
+scene:add_child(ground)
+
+-- Grass blades taper to a point and bend over, scattered over a strip of
+-- lawn in front of the cow with random turns and heights
+lawn = gr.mesh('lawn', 'test/assets/plane.obj')
+lawn:scale(3, 1, 1.5)
+lawn:translate(0, 0, 1.5)
+
+blade = gr.curve('blade', {
+	{0, 0, 0},
+	{0, 0.28, 0},
+	{0.09, 0.56, 0},
+	{0.18, 0.7, 0},
+}, {0.04, 0.035, 0.02, 0.0}, 'flat')
+blade:set_material(grass)
+
+scene:add_child(gr.scatter{
+	name = 'grass',
+	surface = lawn,
+	prototype = blade,
+	count = 2000,
+	seed = 1,
+	random_rotation = true,
+	scale_range = {0.7, 1.3},
+})
+
+-- Hairs along the cow's surface normals, drooping slightly
+cow = gr.node('cow')
+cow:scale(0.4, 0.4, 0.4)
+cow:translate(0, 1.45, -1)
+scene:add_child(cow)
+
+body = gr.mesh('body', 'test/assets/cow.obj')
+body:set_material(hide)
+cow:add_child(body)
+
+-- Points come back in the body's own space, as its children would be placed
+for i, sample in ipairs(gr.sample_mesh(body, 2000, 2)) do
+	local p, n = sample.position, sample.normal
+	local length = 0.4 + 0.3 * math.random()
+
+	local hair = gr.curve('hair' .. i, {
+		{p[1], p[2], p[3]},
+		{p[1] + n[1] * length * 0.4, p[2] + n[2] * length * 0.4, p[3] + n[3] * length * 0.4},
+		{p[1] + n[1] * length * 0.8, p[2] + n[2] * length * 0.8 - 0.1, p[3] + n[3] * length * 0.8},
+		{p[1] + n[1] * length, p[2] + n[2] * length - 0.25, p[3] + n[3] * length},
+	}, {0.06, 0.05, 0.04, 0.02})
+	hair:set_material(fur)
+	body:add_child(hair)
+end
+
+gr.render(scene, 'hair.png', 512, 512,
+	  {0, 3, 9}, {0, -0.3, -1}, {0, 1, 0}, 40,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
@@ -0,0 +1,21 @@
+-- Terrain from a 16-bit grayscale heightmap, intersected without tessellation.
+
+grass = gr.material({0.35, 0.55, 0.25}, {0.1, 0.1, 0.1}, 10)
+water = gr.material({0.15, 0.3, 0.6}, {0.6, 0.6, 0.6}, 50)
+
+scene = gr.node('scene')
+
+-- The heightmap covers a unit square with heights up to the given scale
+terrain = gr.heightfield('terrain', 'test/assets/terrain.png', 0.25)
+terrain:set_material(grass)
+terrain:scale(20, 20, 20)
+scene:add_child(terrain)
+
+sea = gr.plane('sea')
+sea:set_material(water)
+sea:translate(0, 0.6, 0)
+scene:add_child(sea)
+
+gr.render(scene, 'heightfield.png', 512, 512,
+	  {0, 9, 16}, {0, -0.5, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({-20, 30, 10}, {0.9, 0.9, 0.8}, {1, 0, 0})})
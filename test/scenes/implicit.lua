@@ -0,0 +1,47 @@
+-- Implicit surfaces: a gyroid clipped to a sphere defined by a Lua function,
+-- a Lua defined tangle cube, and a native metaball blob.
+
+red = gr.material({0.8, 0.2, 0.2}, {0.5, 0.5, 0.5}, 25)
+blue = gr.material({0.2, 0.3, 0.9}, {0.5, 0.5, 0.5}, 25)
+gold = gr.cook_torrance({0.72, 0.45, 0.2}, {0.72, 0.45, 0.2}, 0.2, 0.3, 0.863, 2.639)
+grey = gr.material({0.6, 0.6, 0.6}, {0.0, 0.0, 0.0}, 0)
+
+scene = gr.node('scene')
+
+ground = gr.plane('ground')
+ground:set_material(grey)
+scene:add_child(ground)
+
+-- Lua functions are sampled once on a grid, the last argument sets the
+-- number of cells along each axis
+gyroid = gr.implicit('gyroid', function(x, y, z)
+	local g = math.sin(3 * x) * math.cos(3 * y)
+		+ math.sin(3 * y) * math.cos(3 * z)
+		+ math.sin(3 * z) * math.cos(3 * x)
+	return math.max(math.abs(g) - 0.4, x * x + y * y + z * z - 2.0)
+end, {{-1.5, -1.5, -1.5}, {1.5, 1.5, 1.5}}, 96)
+gyroid:set_material(gold)
+gyroid:translate(-3.5, 1.5, 0)
+scene:add_child(gyroid)
+
+tangle = gr.implicit('tangle', function(x, y, z)
+	return x^4 - 5 * x^2 + y^4 - 5 * y^2 + z^4 - 5 * z^2 + 11.8
+end, {{-3, -3, -3}, {3, 3, 3}})
+tangle:set_material(red)
+tangle:scale(0.5, 0.5, 0.5)
+tangle:translate(0, 1.5, 0)
+scene:add_child(tangle)
+
+blob = gr.metaballs('blob', {
+	{{0, 0, 0}, 1.2},
+	{{0.9, 0.6, 0}, 0.9},
+	{{-0.5, 1.0, 0.3}, 0.8},
+	{{0.2, -0.6, 0.6}, 0.7},
+}, 0.4)
+blob:set_material(blue)
+blob:translate(3.5, 1.5, 0)
+scene:add_child(blob)
+
+gr.render(scene, 'implicit.png', 512, 512,
+	  {0, 5, 14}, {0, -0.3, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
@@ -48,10 +48,45 @@ sphere:set_material(stone)
 
 -- instancing
 for i = 1,6 do
-   an_arc = gr.node('arc' .. tostring(i))
+   an_arc = gr.instance('arc' .. tostring(i), arc)
    an_arc:rotate('Y', (i-1) * 60)
    scene:add_child(an_arc)
-   an_arc:add_child(arc)
+end
+
+-- ##############################################
+-- the forest
+-- ##############################################
+
+bark = gr.material({0.4, 0.25, 0.1}, {0.0, 0.0, 0.0}, 0)
+leaves = gr.material({0.05, 0.35, 0.1}, {0.1, 0.1, 0.1}, 10)
+
+tree = gr.node('tree')
+
+trunk = gr.cylinder('trunk', 0.15, 1)
+tree:add_child(trunk)
+trunk:set_material(bark)
+trunk:translate(0, 0.5, 0)
+
+for i = 1,3 do
+   crown = gr.cone('crown' .. tostring(i), 1.1 - i * 0.2, 1.2)
+   tree:add_child(crown)
+   crown:set_material(leaves)
+   crown:translate(0, 0.6 + i * 0.55, 0)
+end
+
+-- every tree shares the prototype's objects, only the placement is copied
+math.randomseed(1996)
+
+for i = 1,10000 do
+   local angle = math.random() * 360
+   local distance = 14 + math.random() * 15
+   local size = 0.6 + math.random() * 0.8
+
+   a_tree = gr.instance('tree' .. tostring(i), tree)
+   a_tree:rotate('Y', math.random() * 360)
+   a_tree:scale(size, size, size)
+   a_tree:translate(distance * math.sin(math.rad(angle)), 0, distance * math.cos(math.rad(angle)))
+   scene:add_child(a_tree)
 end
 
 -- render it!
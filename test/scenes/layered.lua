@@ -0,0 +1,38 @@
+-- Layered materials: car paint with a clear coat over metallic flakes,
+-- varnished wood, and steel blended with dirt through a noise mask.
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(gr.principled{base_colour = {0.5, 0.5, 0.5}, roughness = 0.8})
+scene:add_child(floor)
+
+panel = gr.rect('panel', {0, 0, 0}, 6, 3)
+panel:rotate('X', 180)
+panel:translate(0, 6, 2)
+panel:set_material(gr.emissive({1, 0.95, 0.9}, 12))
+scene:add_child(panel)
+
+flakes = gr.principled{base_colour = {0.6, 0.05, 0.05}, metallic = 0.7, roughness = 0.45}
+car_paint = gr.coated(flakes, 0.05)
+
+wood = gr.wood{colours = {{0.75, 0.5, 0.28}, {0.45, 0.25, 0.1}}, frequency = 6, octaves = 3, seed = 7}
+varnish = gr.coated(gr.material(wood, {0, 0, 0}, 1), 0.1, 1.5, {1, 0.85, 0.6})
+
+steel = gr.principled{base_colour = {0.8, 0.8, 0.8}, metallic = 1, roughness = 0.2}
+dirt = gr.principled{base_colour = {0.25, 0.18, 0.1}, roughness = 1}
+mask = gr.noise{type = 'fbm', colours = {{0, 0, 0}, {1, 1, 1}}, frequency = 3, octaves = 4, seed = 5}
+dirty_steel = gr.mix_material(steel, dirt, gr.remap(mask, {0.45, 0.55}, {0, 1}))
+
+materials = {car_paint, varnish, dirty_steel}
+
+for i, material in ipairs(materials) do
+	ball = gr.sphere('ball' .. i)
+	ball:translate((i - 2) * 2.4, 1, 0)
+	ball:set_material(material)
+	scene:add_child(ball)
+end
+
+gr.render(scene, 'layered.png', 768, 320,
+	  {0, 3, 9}, {0, -0.3, -1}, {0, 1, 0}, 40,
+	  {0.05, 0.05, 0.05}, {gr.light({-8, 5, 10}, {0.3, 0.3, 0.3}, {1, 0, 0})})
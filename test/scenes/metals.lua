@@ -0,0 +1,22 @@
+-- Measured metals: gold, silver, copper, aluminium, chrome and titanium. Try
+-- it with --spectral to shade them from their full n and k curves.
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(gr.material({0.4, 0.4, 0.4}, {0, 0, 0}, 1))
+scene:add_child(floor)
+
+metals = {'gold', 'silver', 'copper', 'aluminium', 'chrome', 'titanium'}
+
+for i, name in ipairs(metals) do
+	ball = gr.sphere(name)
+	ball:translate((i - 3.5) * 2.2, 1, 0)
+	ball:set_material(gr.metal(name, 0.3))
+	scene:add_child(ball)
+end
+
+gr.render(scene, 'metals.png', 768, 256,
+	  {0, 2.5, 11}, {0, -0.2, -1}, {0, 1, 0}, 50,
+	  {0.1, 0.1, 0.1}, {gr.light({-6, 8, 8}, {0.9, 0.9, 0.9}, {1, 0, 0}),
+	  gr.light({6, 4, 8}, {0.5, 0.5, 0.6}, {1, 0, 0})})
@@ -0,0 +1,31 @@
+-- Analytic planar primitives: an infinite ground plane, a disk and a rectangle.
+
+grass = gr.material({0.1, 0.7, 0.1}, {0.0, 0.0, 0.0}, 0)
+stone = gr.material({0.8, 0.7, 0.7}, {0.3, 0.3, 0.3}, 10)
+blue = gr.material({0.2, 0.3, 0.9}, {0.5, 0.5, 0.5}, 25)
+
+scene = gr.node('scene')
+
+ground = gr.plane('ground')
+ground:set_material(grass)
+scene:add_child(ground)
+
+disk = gr.disk('disk', {0, 0, 0}, 1.5)
+disk:set_material(stone)
+disk:rotate('X', 90)
+disk:translate(-3, 1.5, 0)
+scene:add_child(disk)
+
+rect = gr.rect('rect', {0, 0, 0}, 3, 2)
+rect:set_material(blue)
+rect:rotate('X', 90)
+rect:translate(3, 1.5, 0)
+scene:add_child(rect)
+
+sphere = gr.nh_sphere('sphere', {0, 1, -3}, 1)
+sphere:set_material(stone)
+scene:add_child(sphere)
+
+gr.render(scene, 'planar.png', 512, 512,
+	  {0, 4, 12}, {0, -0.3, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
@@ -0,0 +1,36 @@
+-- The principled material across a row of spheres: plastic, gold, brushed
+-- steel, velvet, car paint, glass and skin, under a soft lamp panel.
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(gr.principled{base_colour = {0.5, 0.5, 0.5}, roughness = 0.8})
+scene:add_child(floor)
+
+panel = gr.rect('panel', {0, 0, 0}, 6, 3)
+panel:rotate('X', 180)
+panel:translate(0, 6, 2)
+panel:set_material(gr.emissive({1, 0.95, 0.9}, 12))
+scene:add_child(panel)
+
+materials = {
+	gr.principled{base_colour = {0.8, 0.1, 0.1}, roughness = 0.3},
+	gr.principled{base_colour = {1.0, 0.77, 0.34}, metallic = 1, roughness = 0.25},
+	gr.principled{base_colour = {0.8, 0.8, 0.8}, metallic = 1, roughness = 0.35, anisotropic = 0.9},
+	gr.principled{base_colour = {0.3, 0.05, 0.4}, roughness = 1, sheen = 1, sheen_tint = 0.5},
+	gr.principled{base_colour = {0.05, 0.15, 0.5}, metallic = 0.6, roughness = 0.5, clearcoat = 1},
+	gr.principled{base_colour = {0.9, 0.95, 1.0}, roughness = 0.05, transmission = 1, ior = 1.5},
+	gr.principled{base_colour = {0.9, 0.6, 0.5}, roughness = 0.6, subsurface = 1},
+}
+
+for i, material in ipairs(materials) do
+	ball = gr.sphere('ball' .. i)
+	ball:scale(0.7, 0.7, 0.7)
+	ball:translate((i - 4) * 1.6, 0.7, 0)
+	ball:set_material(material)
+	scene:add_child(ball)
+end
+
+gr.render(scene, 'principled.png', 768, 384,
+	  {0, 3, 9}, {0, -0.3, -1}, {0, 1, 0}, 45,
+	  {0.05, 0.05, 0.05}, {gr.light({-8, 5, 10}, {0.3, 0.3, 0.3}, {1, 0, 0})})
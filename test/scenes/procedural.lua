@@ -0,0 +1,42 @@
+-- Procedural textures: solid marble, wood and noise carved out of the objects
+-- they are on, and a UV checker floor whose squares nest a second checker.
+
+white = {0.9, 0.9, 0.88}
+grey = {0.25, 0.25, 0.3}
+
+marble = gr.marble{colours = {{0.3, 0.3, 0.35}, white}, frequency = 1.5, octaves = 5, seed = 3}
+wood = gr.wood{colours = {{0.75, 0.5, 0.28}, {0.45, 0.25, 0.1}}, frequency = 6, octaves = 3, seed = 7}
+cells = gr.noise{type = 'worley', colours = {{0.1, 0.3, 0.6}, {0.8, 0.9, 1.0}}, frequency = 3, seed = 1}
+clouds = gr.noise{type = 'fbm', colours = {{0.2, 0.4, 0.9}, white}, frequency = 2, octaves = 6, seed = 2}
+flames = gr.noise{type = 'turbulence', colours = {{0.9, 0.2, 0.0}, {1.0, 0.9, 0.2}}, frequency = 2, octaves = 6}
+fine_checker = gr.checker{colours = {grey, white}, frequency = 4, space = 'uv'}
+floor_checker = gr.checker{colours = {fine_checker, {0.5, 0.5, 0.55}}, frequency = 0.25, space = 'uv'}
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(gr.material(floor_checker, {0, 0, 0}, 1))
+scene:add_child(floor)
+
+function add(node, texture, x, z)
+	node:set_material(gr.material(texture, {0.3, 0.3, 0.3}, 30))
+	node:translate(x, 1, z)
+	scene:add_child(node)
+end
+
+add(gr.sphere('marble'), marble, -3, 0)
+
+-- Solid textures turn with the object, the rings are cut across by the faces
+block = gr.cube('block')
+block:translate(-0.5, -0.5, -0.5)
+block:scale(1.6, 1.6, 1.6)
+block:rotate('Y', 35)
+add(block, wood, 0, 0)
+
+add(gr.sphere('cells'), cells, 3, 0)
+add(gr.sphere('clouds'), clouds, -1.5, -3)
+add(gr.sphere('flames'), flames, 1.5, -3)
+
+gr.render(scene, 'procedural.png', 512, 512,
+	  {0, 4, 10}, {0, -0.3, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
@@ -0,0 +1,45 @@
+-- Analytic surfaces of revolution: cylinders, a cone, a capsule and a torus.
+
+red = gr.material({0.8, 0.2, 0.2}, {0.5, 0.5, 0.5}, 25)
+green = gr.material({0.2, 0.8, 0.2}, {0.5, 0.5, 0.5}, 25)
+blue = gr.material({0.2, 0.3, 0.9}, {0.5, 0.5, 0.5}, 25)
+gold = gr.cook_torrance({0.72, 0.45, 0.2}, {0.72, 0.45, 0.2}, 0.2, 0.3, 0.863, 2.639)
+grey = gr.material({0.6, 0.6, 0.6}, {0.0, 0.0, 0.0}, 0)
+
+scene = gr.node('scene')
+
+ground = gr.plane('ground')
+ground:set_material(grey)
+scene:add_child(ground)
+
+cylinder = gr.cylinder('cylinder', 1, 2)
+cylinder:set_material(red)
+cylinder:translate(-4.5, 1, 0)
+scene:add_child(cylinder)
+
+tube = gr.cylinder('tube', 1, 2, false)
+tube:set_material(green)
+tube:rotate('X', 60)
+tube:translate(-1.5, 1.5, 0)
+scene:add_child(tube)
+
+cone = gr.cone('cone', 1, 2.5)
+cone:set_material(blue)
+cone:translate(1.5, 1.25, 0)
+scene:add_child(cone)
+
+capsule = gr.capsule('capsule', 0.6, 1.5)
+capsule:set_material(red)
+capsule:rotate('Z', 30)
+capsule:translate(4.5, 1.5, 0)
+scene:add_child(capsule)
+
+torus = gr.torus('torus', 1.5, 0.5)
+torus:set_material(gold)
+torus:rotate('X', 70)
+torus:translate(0, 2.5, -4)
+scene:add_child(torus)
+
+gr.render(scene, 'quadrics.png', 512, 512,
+	  {0, 5, 14}, {0, -0.3, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
@@ -0,0 +1,28 @@
+-- Clay under light from behind the camera: Lambert, Oren-Nayar and
+-- retroreflective diffuse. The rough models stay bright out to the rims
+-- where Lambert falls off.
+
+scene = gr.node('scene')
+
+clay = gr.material({0.75, 0.45, 0.3}, {0, 0, 0}, 1)
+
+floor = gr.plane('floor')
+floor:set_material(gr.oren_nayar(gr.material({0.5, 0.5, 0.5}, {0, 0, 0}, 1), 0.6))
+scene:add_child(floor)
+
+materials = {
+	clay,
+	gr.oren_nayar(clay, 0.6),
+	gr.retroreflective(clay, 1),
+}
+
+for i, material in ipairs(materials) do
+	ball = gr.sphere('ball' .. i)
+	ball:translate((i - 2) * 2.4, 1, 0)
+	ball:set_material(material)
+	scene:add_child(ball)
+end
+
+gr.render(scene, 'rough_diffuse.png', 768, 320,
+	  {0, 2.5, 9}, {0, -0.2, -1}, {0, 1, 0}, 40,
+	  {0.05, 0.05, 0.05}, {gr.light({1, 4, 12}, {0.9, 0.9, 0.9}, {1, 0, 0})})
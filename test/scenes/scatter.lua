@@ -0,0 +1,68 @@
+-- Instances scattered over meshes. Trees follow a density map, leaving a
+-- winding path and a clearing, and keep a minimum spacing between them.
+-- Spikes stand along the surface normals of a ball in the clearing.
+
+math.randomseed(1)
+
+grass = gr.material({0.25, 0.5, 0.15}, {0.0, 0.0, 0.0}, 0)
+bark = gr.material({0.4, 0.25, 0.1}, {0.0, 0.0, 0.0}, 0)
+leaves = gr.material({0.05, 0.35, 0.1}, {0.1, 0.1, 0.1}, 10)
+stone = gr.material({0.7, 0.65, 0.6}, {0.3, 0.3, 0.3}, 20)
+gold = gr.cook_torrance({0.72, 0.45, 0.2}, {0.72, 0.45, 0.2}, 0.2, 0.3, 0.863, 2.639)
+
+scene = gr.node('scene')
+
+ground = gr.mesh('ground', 'test/assets/plane.obj')
+ground:set_material(grass)
+ground:scale(12, 1, 12)
+scene:add_child(ground)
+
+-- Prototypes are never added to the scene themselves
+tree = gr.node('tree')
+
+trunk = gr.cylinder('trunk', 0.12, 0.8)
+trunk:set_material(bark)
+trunk:translate(0, 0.4, 0)
+tree:add_child(trunk)
+
+for i = 1, 3 do
+	crown = gr.cone('crown' .. i, 0.9 - i * 0.15, 1.0)
+	crown:set_material(leaves)
+	crown:translate(0, 0.5 + i * 0.45, 0)
+	tree:add_child(crown)
+end
+
+forest = gr.scatter{
+	name = 'forest',
+	surface = ground,
+	prototype = tree,
+	count = 3000,
+	seed = 7,
+	random_rotation = true,
+	scale_range = {0.4, 0.8},
+	min_distance = 0.5,
+	density = 'test/assets/forest_density.png',
+}
+scene:add_child(forest)
+
+ball = gr.mesh('ball', 'test/assets/buckyball.obj')
+ball:set_material(stone)
+ball:translate(0, 1.2, 0)
+scene:add_child(ball)
+
+spike = gr.cone('spike', 0.07, 0.45)
+spike:set_material(gold)
+spike:translate(0, 0.2, 0)
+
+spikes = gr.scatter{
+	surface = ball,
+	prototype = spike,
+	count = 200,
+	align_to_normal = true,
+	min_distance = 0.15,
+}
+scene:add_child(spikes)
+
+gr.render(scene, 'scatter.png', 512, 512,
+	  {0, 14, 12}, {0, -1, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
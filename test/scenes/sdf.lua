@@ -0,0 +1,42 @@
+-- Sphere traced distance fields: blended spheres, a twisted box, a grid of
+-- tori and a Mandelbulb.
+
+red = gr.material({0.8, 0.2, 0.2}, {0.5, 0.5, 0.5}, 25)
+green = gr.material({0.2, 0.8, 0.2}, {0.5, 0.5, 0.5}, 25)
+blue = gr.material({0.2, 0.3, 0.9}, {0.5, 0.5, 0.5}, 25)
+gold = gr.cook_torrance({0.72, 0.45, 0.2}, {0.72, 0.45, 0.2}, 0.2, 0.3, 0.863, 2.639)
+grey = gr.material({0.6, 0.6, 0.6}, {0.0, 0.0, 0.0}, 0)
+
+scene = gr.node('scene')
+
+ground = gr.plane('ground')
+ground:set_material(grey)
+scene:add_child(ground)
+
+blob = gr.sdf('blob', gr.sdf_smooth_union(
+	gr.sdf_translate(gr.sdf_sphere(0.9), {-0.6, 0, 0}),
+	gr.sdf_translate(gr.sdf_sphere(0.7), {0.7, 0.3, 0}),
+	0.6))
+blob:set_material(red)
+blob:translate(-4.5, 1, 0)
+scene:add_child(blob)
+
+twisted = gr.sdf('twisted', gr.sdf_twist(gr.sdf_box({1.2, 3, 1.2}), 45))
+twisted:set_material(green)
+twisted:translate(-1.5, 1.5, 0)
+scene:add_child(twisted)
+
+rings = gr.sdf('rings', gr.sdf_repeat(gr.sdf_torus(0.4, 0.12), {1, 0.5, 1}, {2, 3, 2}))
+rings:set_material(blue)
+rings:translate(1.5, 0.7, 0)
+scene:add_child(rings)
+
+bulb = gr.sdf('bulb', gr.sdf_mandelbulb(8, 10))
+bulb:set_material(gold)
+bulb:scale(1.5, 1.5, 1.5)
+bulb:translate(4.5, 1.6, 0)
+scene:add_child(bulb)
+
+gr.render(scene, 'sdf.png', 512, 512,
+	  {0, 5, 14}, {0, -0.3, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
@@ -0,0 +1,44 @@
+-- Materials built from node graphs: a glaze that brightens towards the rim, a
+-- marble whose veins follow a stretched copy of the object, a sphere coloured
+-- by its own normal and a floor whose roughness comes from a remapped noise.
+
+white = {0.9, 0.9, 0.88}
+
+-- Glaze, reflecting more at grazing angles like glass does
+glaze_colour = gr.mix({0.1, 0.2, 0.5}, {0.7, 0.8, 1.0}, gr.fresnel(1.5))
+glaze = gr.material(glaze_colour, gr.multiply(gr.fresnel(1.5), 2), 60)
+
+-- Marble looked up through a squashed object position
+stretched = gr.multiply(gr.object_position(), {1, 4, 1})
+veins = gr.mapping(gr.marble{colours = {{0.3, 0.25, 0.2}, white}, octaves = 5, seed = 3}, stretched)
+veined = gr.material(veins, {0.3, 0.3, 0.3}, 30)
+
+-- Normals from [-1, 1] onto colours
+normal_colour = gr.remap(gr.world_normal(), {-1, 1}, {0, 1})
+normals = gr.material(normal_colour, {0.2, 0.2, 0.2}, 20)
+
+-- UV gradient tinting a checker, with roughness from noise
+gradient = gr.mix({0.8, 0.3, 0.2}, {0.2, 0.5, 0.8}, gr.remap(gr.uv(), {-8, 8}, {0, 1}))
+tiles = gr.multiply(gr.checker{colours = {0.6, 1}, frequency = 0.5, space = 'uv'}, gradient)
+roughness = gr.remap(gr.noise{type = 'fbm', frequency = 0.5, octaves = 4}, {0.3, 0.7}, {0.05, 0.6})
+floor_material = gr.cook_torrance(tiles, {1, 1, 1}, 0.7, roughness, 1.5, 0)
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(floor_material)
+scene:add_child(floor)
+
+function add(node, material, x)
+	node:set_material(material)
+	node:translate(x, 1, 0)
+	scene:add_child(node)
+end
+
+add(gr.sphere('glaze'), glaze, -3)
+add(gr.sphere('veined'), veined, 0)
+add(gr.sphere('normals'), normals, 3)
+
+gr.render(scene, 'shader_nodes.png', 512, 512,
+	  {0, 4, 10}, {0, -0.3, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
@@ -0,0 +1,38 @@
+-- Colours rendered through spectra: run with --spectral to trace wavelengths
+-- instead of red, green and blue. The rightmost ball has a Cauchy index like
+-- BK7 glass, so its highlight is slightly bluer than the one beside it. The
+-- glass ball in front has several times the dispersion of flint glass, so
+-- the horizon and balls it shows upside down are fringed with colour. RGB
+-- splits them into red, green and blue alone.
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(gr.material({0.5, 0.5, 0.5}, {0, 0, 0}, 1))
+scene:add_child(floor)
+
+materials = {
+	gr.material({0.8, 0.15, 0.1}, {0.3, 0.3, 0.3}, 25),
+	gr.material({0.1, 0.6, 0.2}, {0.3, 0.3, 0.3}, 25),
+	gr.cook_torrance({0.15, 0.2, 0.8}, {0.04, 0.04, 0.04}, 0.6, 0.2, 1.5, 0),
+	gr.cook_torrance({0.15, 0.2, 0.8}, {0.04, 0.04, 0.04}, 0.6, 0.2, {1.5046, 0.0042}, 0),
+}
+
+for i, material in ipairs(materials) do
+	ball = gr.sphere('ball' .. i)
+	ball:translate((i - 2.5) * 2.4, 1, 0)
+	ball:set_material(material)
+	scene:add_child(ball)
+end
+
+glass = gr.sphere('glass')
+glass:scale(0.9, 0.9, 0.9)
+glass:translate(0, 1, 2)
+glass:set_material(gr.principled{base_colour = {1, 1, 1}, roughness = 0.05,
+	transmission = 1, ior = {1.55, 0.05}})
+scene:add_child(glass)
+
+gr.render(scene, 'spectral.png', 768, 256,
+	  {0, 2.5, 9}, {0, -0.2, -1}, {0, 1, 0}, 45,
+	  {0.1, 0.1, 0.1}, {gr.light({-6, 8, 8}, {0.9, 0.9, 0.9}, {1, 0, 0}),
+	  gr.light({6, 4, 8}, {0.5, 0.5, 0.6}, {1, 0, 0})})
@@ -0,0 +1,33 @@
+-- Loop subdivision of low polygon meshes. The cow is smoothed everywhere,
+-- the dodecahedron keeps edges sharper than the crease angle.
+
+hide = gr.material({0.84, 0.6, 0.53}, {0.3, 0.3, 0.3}, 20)
+blue = gr.material({0.2, 0.3, 0.9}, {0.5, 0.5, 0.5}, 25)
+green = gr.material({0.2, 0.8, 0.2}, {0.5, 0.5, 0.5}, 25)
+grey = gr.material({0.6, 0.6, 0.6}, {0.0, 0.0, 0.0}, 0)
+
+scene = gr.node('scene')
+
+ground = gr.plane('ground')
+ground:set_material(grey)
+scene:add_child(ground)
+
+cow = gr.mesh('cow', 'test/assets/cow.obj', {subdivide = 1})
+cow:set_material(hide)
+cow:scale(0.5, 0.5, 0.5)
+cow:translate(0, 1.8, 0)
+scene:add_child(cow)
+
+smooth = gr.mesh('smooth', 'test/assets/dodeca.obj', {subdivide = 3})
+smooth:set_material(blue)
+smooth:translate(-4.5, 1.5, 0)
+scene:add_child(smooth)
+
+creased = gr.mesh('creased', 'test/assets/dodeca.obj', {subdivide = 3, crease_angle = 30})
+creased:set_material(green)
+creased:translate(4.5, 1.5, 0)
+scene:add_child(creased)
+
+gr.render(scene, 'subdivision.png', 512, 512,
+	  {0, 5, 14}, {0, -0.3, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})
@@ -0,0 +1,35 @@
+-- Normal and bump mapping: the same brick pattern as a bump map on a wall and
+-- as a normal map on a sphere and on a mesh sphere with its own tangents.
+
+brick_heights = gr.texture('test/assets/bricks_height.png')
+brick_normals = gr.texture('test/assets/bricks_normal.png')
+
+brick = gr.material({0.7, 0.35, 0.25}, {0.2, 0.2, 0.2}, 20)
+bumped_brick = gr.bump_map(brick, brick_heights, 0.02)
+normal_brick = gr.normal_map(brick, brick_normals)
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(gr.material({0.5, 0.5, 0.5}, {0, 0, 0}, 1))
+scene:add_child(floor)
+
+wall = gr.rect('wall', {0, 0, 0}, 8, 3)
+wall:set_material(bumped_brick)
+wall:rotate('X', 90)
+wall:translate(0, 1.5, -2)
+scene:add_child(wall)
+
+ball = gr.sphere('ball')
+ball:set_material(normal_brick)
+ball:translate(-1.5, 1, 0.5)
+scene:add_child(ball)
+
+mesh_ball = gr.mesh('mesh_ball', 'test/assets/uv_sphere.obj')
+mesh_ball:set_material(normal_brick)
+mesh_ball:translate(1.5, 1, 0.5)
+scene:add_child(mesh_ball)
+
+gr.render(scene, 'surface_detail.png', 512, 512,
+	  {0, 2, 7}, {0, -0.1, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({-6, 8, 8}, {0.8, 0.8, 0.8}, {1, 0, 0})})
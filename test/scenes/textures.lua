@@ -0,0 +1,38 @@
+-- Image textures in place of constant material parameters: wood on the
+-- quadrics, and a checker used as the roughness of a metal floor.
+
+wood_texture = gr.texture('test/assets/wood.png')
+mirrored_wood = gr.texture('test/assets/wood.png', {wrap = 'mirror'})
+checker = gr.texture('test/assets/checker.png', {wrap = 'repeat'})
+
+wood = gr.material(wood_texture, {0.2, 0.2, 0.2}, 20)
+tiled_wood = gr.material(mirrored_wood, {0.1, 0.1, 0.1}, 10)
+floor_metal = gr.cook_torrance({0.3, 0.3, 0.35}, {0.9, 0.9, 0.9}, 0.3, checker, 1.5, 1.0)
+
+scene = gr.node('scene')
+
+floor = gr.plane('floor')
+floor:set_material(floor_metal)
+floor:scale(4, 1, 4)
+scene:add_child(floor)
+
+ball = gr.nh_sphere('ball', {0, 0, 0}, 1)
+ball:set_material(wood)
+ball:translate(-2.5, 1, 0)
+scene:add_child(ball)
+
+crate = gr.cube('crate')
+crate:set_material(tiled_wood)
+crate:translate(-0.5, 0, -0.5)
+crate:scale(1.6, 1.6, 1.6)
+crate:rotate('Y', 30)
+scene:add_child(crate)
+
+post = gr.cylinder('post', 1, 2, true)
+post:set_material(wood)
+post:translate(2.5, 1, 0)
+scene:add_child(post)
+
+gr.render(scene, 'textures.png', 512, 512,
+	  {0, 4, 9}, {0, -0.35, -1}, {0, 1, 0}, 50,
+	  {0.3, 0.3, 0.3}, {gr.light({10, 20, 20}, {0.8, 0.8, 0.8}, {1, 0, 0})})